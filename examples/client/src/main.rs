@@ -382,10 +382,13 @@ fn handle_client_events(
         {
             DemoClientEvent::Report(connection_report) => match connection_report
             {
-                bevy_simplenet::ClientReport::Connected         => *status = ConnectionStatus::Connected,
-                bevy_simplenet::ClientReport::Disconnected      |
+                bevy_simplenet::ClientReport::Connected         |
+                bevy_simplenet::ClientReport::Reconnected       => *status = ConnectionStatus::Connected,
+                bevy_simplenet::ClientReport::Disconnected(_)   |
                 bevy_simplenet::ClientReport::ClosedByServer(_) |
                 bevy_simplenet::ClientReport::ClosedBySelf      => *status = ConnectionStatus::Connecting,
+                bevy_simplenet::ClientReport::ServerDraining    |
+                bevy_simplenet::ClientReport::GapDetected{..}   => (),
                 bevy_simplenet::ClientReport::IsDead(aborted_reqs) =>
                 {
                     for aborted_req in aborted_reqs
@@ -83,7 +83,7 @@ fn handle_server_events(
                     let current_state = new_button_state;
                     let _ = server.send(client_id, DemoServerMsg::Current(current_state));
                 }
-                bevy_simplenet::ServerReport::Disconnected =>
+                bevy_simplenet::ServerReport::Disconnected(_) =>
                 {
                     // remove client
                     let _ = clients.0.remove(&client_id);
@@ -93,6 +93,9 @@ fn handle_server_events(
                 }
             }
             DemoServerEvent::Msg(()) => continue,
+            DemoServerEvent::RateLimited(_) => continue,
+            DemoServerEvent::ProtocolViolation(_) => continue,
+            DemoServerEvent::DeliveryReceipt{..} => continue,
             DemoServerEvent::Request(request, token) => match request
             {
                 DemoClientRequest::Select =>
@@ -0,0 +1,159 @@
+//local shortcuts
+
+//third-party shortcuts
+use ezsockets::client::ClientConnector;
+use futures::{Sink, Stream};
+use futures::task::AtomicWaker;
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Abort switch of one connection.
+#[derive(Debug, Default)]
+struct AbortSwitch
+{
+    /// set when the connection is aborted
+    aborted: AtomicBool,
+    /// waker of the task receiving from the connection's socket
+    waker: AtomicWaker,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Aborts a client's current connection.
+/// - Shared between the client handler and the client's connector.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ConnectionAborter
+{
+    /// abort switch of the current connection
+    current: Arc<Mutex<Option<Arc<AbortSwitch>>>>,
+}
+
+impl ConnectionAborter
+{
+    /// Abort the current connection.
+    /// - The connection's socket stops receiving, so the client will see the connection as lost.
+    pub(crate) fn abort(&self)
+    {
+        let current = match self.current.lock()
+        {
+            Ok(current) => current.clone(),
+            Err(_) => { tracing::error!("connection aborter poisoned"); return; }
+        };
+        let Some(switch) = current else { return; };
+
+        switch.aborted.store(true, Ordering::Release);
+        switch.waker.wake();
+    }
+
+    /// Make the abort switch for a new connection.
+    fn new_connection(&self) -> Arc<AbortSwitch>
+    {
+        let switch = Arc::new(AbortSwitch::default());
+        match self.current.lock()
+        {
+            Ok(mut current) => *current = Some(switch.clone()),
+            Err(_) => tracing::error!("connection aborter poisoned"),
+        }
+        switch
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wraps a client's socket in order to end its stream when the connection is aborted.
+#[derive(Debug)]
+pub(crate) struct AbortableSocket<S>
+{
+    /// the wrapped socket
+    inner: S,
+    /// abort switch of the socket's connection
+    switch: Arc<AbortSwitch>,
+}
+
+impl<S: Stream + Unpin> Stream for AbortableSocket<S>
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>>
+    {
+        // register before checking the switch, so an abort after the check will wake the task
+        self.switch.waker.register(cx.waker());
+        if self.switch.aborted.load(Ordering::Acquire) { return Poll::Ready(None); }
+
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
+
+impl<M, S: Sink<M> + Unpin> Sink<M> for AbortableSocket<S>
+{
+    type Error = S::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>>
+    {
+        Pin::new(&mut self.inner).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: M) -> Result<(), S::Error>
+    {
+        Pin::new(&mut self.inner).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>>
+    {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>>
+    {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Client connector whose connections can be aborted with a [`ConnectionAborter`].
+/// - Used to drop connections that missed too many heartbeat pongs (see `ClientConfig::max_missed_pongs`).
+pub(crate) struct AbortableClientConnector<C>
+{
+    /// the wrapped connector
+    inner: C,
+    /// aborts the current connection
+    aborter: ConnectionAborter,
+}
+
+impl<C> AbortableClientConnector<C>
+{
+    pub(crate) fn new(inner: C, aborter: ConnectionAborter) -> Self
+    {
+        Self{ inner, aborter }
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: ClientConnector + Send + Sync> ClientConnector for AbortableClientConnector<C>
+{
+    type Handle  = C::Handle;
+    type Message = C::Message;
+    type WSError = C::WSError;
+    type Socket  = AbortableSocket<C::Socket>;
+
+    fn handle(&self) -> Self::Handle
+    {
+        self.inner.handle()
+    }
+
+    async fn connect(&self, client_config: &ezsockets::ClientConfig) -> Result<Self::Socket, Self::WSError>
+    {
+        let socket = self.inner.connect(client_config).await?;
+        Ok(AbortableSocket{ inner: socket, switch: self.aborter.new_connection() })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
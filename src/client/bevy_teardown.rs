@@ -0,0 +1,93 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy_ecs::event::{Event, EventWriter, Events};
+use bevy_ecs::system::{Command, Res};
+use bevy_ecs::world::World;
+
+//standard shortcuts
+use std::marker::PhantomData;
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Bevy event that carries a [`ClientEvent`] drained from a [`Client`] resource.
+///
+/// Register it with `App::add_event::<ClientEventOf<Channel>>()`, then use [`drain_client_events`] and
+/// [`RemoveClient`] to forward client events into it.
+#[derive(Event, Debug)]
+pub struct ClientEventOf<Channel: ChannelPack>(pub ClientEventFrom<Channel>);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// System that drains the pending events of a [`Client`] resource into `Events<ClientEventOf<Channel>>`.
+///
+/// Does nothing if the client resource doesn't exist.
+pub fn drain_client_events<Channel: ChannelPack>(
+    client     : Option<Res<Client<Channel>>>,
+    mut events : EventWriter<ClientEventOf<Channel>>,
+){
+    let Some(client) = client else { return; };
+    while let Some(event) = client.next() { events.send(ClientEventOf(event)); }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command that removes a [`Client`] resource in an orderly way.
+///
+/// The client is closed, and its pending events (including the final [`ClientReport::IsDead`]) are drained into
+/// `Events<ClientEventOf<Channel>>` before it is dropped, so they can be read by systems that run after the command is
+/// applied. A warning is logged if the events resource doesn't exist.
+///
+/// On native targets the command blocks for up to `timeout` waiting for the client to die. On WASM the client can't
+/// die while the command blocks, so only events that are already available are drained.
+///
+/// Bevy does not notify resources when they are removed, so removing the client resource directly (e.g. with
+/// `Commands::remove_resource()`) still discards its pending events.
+pub struct RemoveClient<Channel: ChannelPack>
+{
+    timeout  : Duration,
+    _phantom : PhantomData<fn() -> Channel>,
+}
+
+impl<Channel: ChannelPack> RemoveClient<Channel>
+{
+    /// Make a new command that waits up to `timeout` for the client to die.
+    pub fn new(timeout: Duration) -> Self
+    {
+        Self{ timeout, _phantom: PhantomData::default() }
+    }
+}
+
+impl<Channel: ChannelPack> Command for RemoveClient<Channel>
+{
+    fn apply(self, world: &mut World)
+    {
+        let Some(client) = world.remove_resource::<Client<Channel>>() else { return; };
+
+        // shut down the client
+        #[cfg(not(target_family = "wasm"))]
+        {
+            if !client.close_and_wait_for_death(self.timeout)
+            { tracing::warn!("client did not die before the teardown timeout"); }
+        }
+
+        #[cfg(target_family = "wasm")]
+        {
+            let _ = self.timeout;
+            if !client.is_closed() { client.close(); }
+        }
+
+        // drain the client's events
+        let Some(mut events) = world.get_resource_mut::<Events<ClientEventOf<Channel>>>()
+        else
+        {
+            tracing::warn!("client events were not registered, discarding client events during teardown");
+            return;
+        };
+        while let Some(event) = client.next() { events.send(ClientEventOf(event)); }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -8,7 +8,52 @@ use bincode::Options;
 use core::fmt::Debug;
 use std::marker::PhantomData;
 use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The status of a [`Client`]. See [`Client::status()`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ClientStatus
+{
+    /// The client is trying to connect or reconnect to the server.
+    Connecting,
+    /// The client is connected to the server.
+    Connected,
+    /// [`Client::close()`] was called but the client backend has not died yet.
+    ClosingBySelf,
+    /// The client is dead (no longer connected to the server and won't reconnect).
+    Dead,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Get the connect error of a client that died before connecting.
+#[cfg(not(target_family = "wasm"))]
+fn connect_error(death_reason: Option<DeathReason>) -> ConnectError
+{
+    tracing::debug!(?death_reason, "client died before connecting");
+    let Some(DeathReason::ClosedByServer(Some(close_frame))) = death_reason
+    else { return ConnectError::ConnectionFailed; };
+
+    match CloseReason::from_close_frame(&close_frame)
+    {
+        Some(CloseReason::AuthFailed)      => ConnectError::AuthFailed,
+        Some(CloseReason::VersionMismatch) => ConnectError::VersionMismatch,
+        _                                  => ConnectError::ConnectionFailed,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Close frame sent when a client closes itself.
+fn client_close_frame() -> ezsockets::CloseFrame
+{
+    ezsockets::CloseFrame{
+        code   : ezsockets::CloseCode::Normal,
+        reason : String::from("client done")
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -22,12 +67,26 @@ use std::sync::atomic::{AtomicBool, Ordering};
 ///    will block the client backend.
 /// 3) Call [`Client::next()`] to drain any lingering events. [`ClientReport::IsDead`] will be the last event.
 /// 4) Drop the client.
+///
+/// Events that have not been drained when the client is dropped will be lost (a warning is logged).
 #[derive(Debug)]
 #[cfg_attr(feature = "bevy", derive(bevy_ecs::system::Resource))]
 pub struct Client<Channel: ChannelPack>
 {
     /// this client's id
     client_id: u128,
+    /// config: hook applied to serialized messages before they are sent
+    on_send: Option<Arc<dyn BytesHookFn>>,
+    /// config: send messages as base64 text frames
+    text_frames: bool,
+    /// observer for lifecycle events
+    observer: Arc<dyn Observer>,
+    /// config: max number of pending messages for `try_send()`
+    max_pending_msgs: usize,
+    /// config: chunk size for streamed messages
+    stream_chunk_size: u32,
+    /// id for the next streamed message
+    next_stream_id: AtomicU64,
     /// core websockets client
     client: ezsockets::Client<ClientHandler<Channel>>,
     /// sender for client events
@@ -36,12 +95,28 @@ pub struct Client<Channel: ChannelPack>
     client_event_receiver: crossbeam::channel::Receiver<ClientEventFrom<Channel>>,
     /// synchronized tracker for pending requests
     pending_requests: Arc<Mutex<PendingRequestTracker>>,
+    /// the server's max message size (`u32::MAX` until the server reports it)
+    server_max_msg_size: Arc<AtomicU32>,
+    /// tracker for messages in the outbound buffer
+    pending_messages: Mutex<PendingMessageTracker>,
     /// signal for when the internal client is connected
     client_connected_signal: Arc<AtomicBool>,
     /// signal for when the internal client is shut down
     client_closed_signal: Arc<AtomicBool>,
     /// flag indicating the client closed itself
     closed_by_self: Arc<AtomicBool>,
+    /// flag that allows reconnecting after the connection drops (shared with the client handler)
+    auto_reconnect: Arc<AtomicBool>,
+    /// synchronized tracker for on-demand pings
+    pending_pings: Arc<Mutex<PingTracker>>,
+    /// config: max time to wait for the pong of an on-demand ping
+    ping_timeout: std::time::Duration,
+    /// the final cause of the client's death (shared with the client handler)
+    death_reason: Arc<Mutex<Option<DeathReason>>>,
+    /// receives a notification when the client handler connects or dies
+    connection_updates: crossbeam::channel::Receiver<()>,
+    /// wakes tasks waiting for outbound messages to finish sending (shared with the client handler)
+    send_status: Arc<SendStatusNotifier>,
 }
 
 impl<Channel: ChannelPack> Client<Channel>
@@ -51,25 +126,100 @@ impl<Channel: ChannelPack> Client<Channel>
     /// Returns `Ok(MessageSignal)` on success. The signal can be used to track the message status. Messages
     /// will fail if the underlying client becomes disconnected.
     ///
-    /// Returns `Err` if the client is not connected.
+    /// Returns `Err` if the client is not connected or the message is larger than the server's max message size (see
+    /// [`Client::server_max_msg_size()`]). Use [`Client::try_send()`] to find out why a message failed.
+    ///
+    /// Messages and requests share one ordered stream, so the server will emit them in the order they were sent.
     pub fn send(&self, msg: Channel::ClientMsg) -> Result<MessageSignal, ()>
     {
         // check if connected
         if !self.is_connected() { tracing::warn!("tried to send message to disconnected client"); return Err(()); }
 
+        self.send_msg(msg).map_err(|_| ())
+    }
+
+    /// Send a one-shot message to the server without tracking its status.
+    ///
+    /// This is cheaper than [`Client::send()`] for high-frequency messages because no [`MessageSignal`] is returned or
+    /// tracked. The trade-off is that you can't find out if the message failed to send. Messages sent this way do
+    /// not count against [`ClientConfig::max_pending_msgs`].
+    ///
+    /// Returns `Err` if the client is not connected or the message is larger than the server's max message size (see
+    /// [`Client::server_max_msg_size()`]).
+    pub fn send_and_forget(&self, msg: Channel::ClientMsg) -> Result<(), ()>
+    {
+        // check if connected
+        if !self.is_connected() { tracing::warn!("tried to send message to disconnected client"); return Err(()); }
+
         // forward message to server
-        let Ok(ser_msg) = bincode::DefaultOptions::new().serialize(&ServerMetaEventFrom::<Channel>::Msg(msg))
+        let Ok(mut ser_msg) = bincode::DefaultOptions::new().serialize(&ServerMetaEventFrom::<Channel>::Msg(msg))
         else { tracing::error!("failed serializing client message"); return Err(()); };
+        apply_bytes_hook(&self.on_send, &mut ser_msg);
+        self.check_msg_size(&ser_msg).map_err(|_| ())?;
 
-        match self.client.binary(ser_msg)
+        if let Err(_) = self.send_frame(ser_msg)
         {
-            Ok(signal) => Ok(signal),
-            Err(_) =>
-            {
-                tracing::warn!("tried to send message to dead client");
-                Err(())
-            }
+            tracing::warn!("tried to send message to dead client");
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    /// Try to send a one-shot message to the server.
+    ///
+    /// Returns `Err(TrySendError::Full)` without sending if the number of messages waiting in the client's outbound
+    /// buffer is at least [`ClientConfig::max_pending_msgs`]. This lets latency-sensitive code drop stale messages
+    /// instead of queueing them.
+    ///
+    /// Returns `Err(TrySendError::MessageTooLarge)` without sending if the serialized message is larger than the
+    /// server's max message size.
+    ///
+    /// Otherwise behaves like [`Client::send()`].
+    pub fn try_send(&self, msg: Channel::ClientMsg) -> Result<MessageSignal, TrySendError>
+    {
+        // check if connected
+        if !self.is_connected() { return Err(TrySendError::NotConnected); }
+
+        // check if the outbound buffer is full
+        let Ok(mut pending_messages) = self.pending_messages.lock() else { return Err(TrySendError::Failed); };
+        if pending_messages.num_pending() >= self.max_pending_msgs
+        {
+            tracing::debug!("outbound buffer is full, rejecting message");
+            return Err(TrySendError::Full);
         }
+        drop(pending_messages);
+
+        self.send_msg(msg)
+    }
+
+    /// Send a one-shot message to the server as a stream of chunks.
+    ///
+    /// Use this for messages that may be larger than the server's max message size. Chunks are sized with
+    /// [`ClientConfig::stream_chunk_size`]. The server will receive the message as a normal client message once all
+    /// chunks have arrived. Each chunk counts against the server's rate limit.
+    ///
+    /// Returns `Ok(MessageSignal)` on success. The signal tracks the status of the final chunk.
+    ///
+    /// Returns `Err` if the client is not connected.
+    pub fn send_stream(&self, msg: Channel::ClientMsg) -> Result<MessageSignal, ()>
+    {
+        // check if connected
+        if !self.is_connected() { tracing::warn!("tried to send stream to disconnected client"); return Err(()); }
+
+        // serialize message
+        let Ok(ser_msg) = bincode::DefaultOptions::new().serialize(&msg)
+        else { tracing::error!("failed serializing streamed client message"); return Err(()); };
+
+        // send chunks
+        let stream_id = self.next_stream_id.fetch_add(1u64, Ordering::Relaxed);
+        let mut last_signal = None;
+        for chunk in split_into_chunks(stream_id, ser_msg, self.stream_chunk_size)
+        {
+            last_signal = Some(self.send_control(ServerMetaEventFrom::<Channel>::Chunk(chunk))?);
+        }
+
+        last_signal.ok_or(())
     }
 
     /// Send a request to the server.
@@ -77,7 +227,10 @@ impl<Channel: ChannelPack> Client<Channel>
     /// Returns `Ok(RequestSignal)` on success. The signal can be used to track the message status. Requests
     /// will fail if the underlying client becomes disconnected.
     ///
-    /// Returns `Err` if the client is not connected.
+    /// Returns `Err` if the client is not connected or the request is larger than the server's max message size (see
+    /// [`Client::server_max_msg_size()`]).
+    ///
+    /// Messages and requests share one ordered stream, so the server will emit them in the order they were sent.
     pub fn request(&self, request: Channel::ClientRequest) -> Result<RequestSignal, ()>
     {
         // lock pending requests
@@ -92,15 +245,18 @@ impl<Channel: ChannelPack> Client<Channel>
         let request_id = pending_requests.reserve_id();
 
         // forward message to server
-        let Ok(ser_msg) = bincode::DefaultOptions::new().serialize(
+        let Ok(mut ser_msg) = bincode::DefaultOptions::new().serialize(
                 &ServerMetaEventFrom::<Channel>::Request(request, request_id)
             )
         else { tracing::error!("failed serializing client request"); return Err(()); };
+        apply_bytes_hook(&self.on_send, &mut ser_msg);
+        self.check_msg_size(&ser_msg).map_err(|_| ())?;
 
-        match self.client.binary(ser_msg)
+        match self.send_frame(ser_msg)
         {
             Ok(signal) =>
             {
+                let signal = self.track_pending_msg(signal);
                 let request_signal = pending_requests.add_request(request_id, signal);
                 Ok(request_signal)
             }
@@ -112,6 +268,85 @@ impl<Channel: ChannelPack> Client<Channel>
         }
     }
 
+    /// Subscribe to a topic.
+    ///
+    /// The client will receive messages published to the topic with [`Server::publish()`] as normal server messages.
+    ///
+    /// Subscriptions are tied to the client's current connection. After a reconnect, you need to subscribe again
+    /// (e.g. when [`ClientReport::Reconnected`] is received).
+    ///
+    /// Returns `Err` if the client is not connected.
+    pub fn subscribe(&self, topic: &str) -> Result<MessageSignal, ()>
+    {
+        self.send_control(ServerMetaEventFrom::<Channel>::Subscribe(String::from(topic)))
+    }
+
+    /// Unsubscribe from a topic.
+    ///
+    /// Returns `Err` if the client is not connected.
+    pub fn unsubscribe(&self, topic: &str) -> Result<MessageSignal, ()>
+    {
+        self.send_control(ServerMetaEventFrom::<Channel>::Unsubscribe(String::from(topic)))
+    }
+
+    /// Measure the round-trip time to the server.
+    ///
+    /// Sends a ping immediately, and the returned future resolves with the round-trip time once the server's pong
+    /// arrives. The future resolves to `None` if the client is not connected, if it disconnects before the pong
+    /// arrives, or if the pong doesn't arrive within [`ClientConfig::keepalive_timeout`].
+    ///
+    /// This is independent of the automatic heartbeat. See [`PingSignal`] for how the future waits.
+    pub fn ping(&self) -> PingSignal
+    {
+        let ping_id = match self.pending_pings.lock()
+        {
+            Ok(mut pending_pings) => pending_pings.reserve_id(),
+            Err(_) => { tracing::error!("ping tracker poisoned"); return PingSignal::failed(self.pending_pings.clone()); }
+        };
+
+        // the ping is tracked before it is sent, so the pong can't arrive before the tracker expects it
+        let signal = PingSignal::new(ping_id, self.ping_timeout, self.pending_pings.clone());
+        if self.send_control(ServerMetaEventFrom::<Channel>::Ping(ping_id)).is_err()
+        {
+            return PingSignal::failed(self.pending_pings.clone());  //dropping the signal discards the ping
+        }
+
+        // wake the signal when the ping times out
+        let pending_pings = self.pending_pings.clone();
+        self.send_status.spawn_after(
+                self.ping_timeout,
+                move ||
+                {
+                    match pending_pings.lock()
+                    {
+                        Ok(mut pending_pings) => pending_pings.wake(ping_id),
+                        Err(_) => tracing::error!("ping tracker poisoned"),
+                    }
+                }
+            );
+
+        signal
+    }
+
+    /// Get a future that resolves when all messages sent so far have finished sending.
+    ///
+    /// The future resolves once every message and request sent before this method was called is no longer
+    /// [`MessageStatus::Sending`] (i.e. its [`MessageSignal`] has transitioned to `Sent` or `Failed`). Messages sent
+    /// with [`Client::send_and_forget()`] are not tracked, so they are only guaranteed to be flushed if a tracked
+    /// message was sent after them.
+    ///
+    /// See [`MessageFlush`] for how the future waits.
+    pub fn flush(&self) -> MessageFlush
+    {
+        let signals = match self.pending_messages.lock()
+        {
+            Ok(mut pending_messages) => pending_messages.pending_signals(),
+            Err(_) => { tracing::error!("pending message tracker poisoned"); Vec::default() }
+        };
+
+        MessageFlush::new(signals, self.send_status.clone())
+    }
+
     /// Try to get the next client event.
     ///
     /// When the client dies, the last event emitted will be `ClientEvent::Report(ClientReport::IsDead))`.
@@ -121,15 +356,93 @@ impl<Channel: ChannelPack> Client<Channel>
         Some(msg)
     }
 
+    /// Drain all available client events, partitioned by type.
+    ///
+    /// See [`GroupedEvents`] for how event order is preserved.
+    pub fn drain_grouped(&self) -> GroupedEvents<Channel>
+    {
+        self.client_event_receiver.try_iter().collect()
+    }
+
+    /// Get the number of client events waiting to be consumed.
+    ///
+    /// A steadily growing number indicates the client's events are produced faster than they are consumed.
+    pub fn queued_events(&self) -> usize
+    {
+        self.client_event_receiver.len()
+    }
+
+    /// Panic if any client events are waiting to be consumed.
+    ///
+    /// The panic message lists all unconsumed events. Intended for tests, to check that every event was handled.
+    #[cfg(feature = "testing")]
+    #[track_caller]
+    pub fn assert_drained(&self)
+    {
+        let events: Vec<ClientEventFrom<Channel>> = self.client_event_receiver.try_iter().collect();
+        if events.is_empty() { return; }
+
+        panic!("client has {} unconsumed event(s): {:#?}", events.len(), events);
+    }
+
+    /// Invoke a callback for each client event, as an alternative to polling [`Client::next()`].
+    ///
+    /// Spawns a thread that drains the client's events and passes them to the callback. The thread exits after
+    /// [`ClientReport::IsDead`] is passed to the callback.
+    ///
+    /// This is mutually exclusive with [`Client::next()`]. If you use both, then each event will be received by only one
+    /// of them.
+    ///
+    /// Only available on native targets.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn on_event(&self, mut callback: Box<dyn FnMut(ClientEventFrom<Channel>) + Send>)
+    {
+        let client_event_receiver = self.client_event_receiver.clone();
+        std::thread::spawn(
+                move ||
+                {
+                    while let Ok(event) = client_event_receiver.recv()
+                    {
+                        let is_dead = matches!(event, ClientEventFrom::<Channel>::Report(ClientReport::IsDead(_)));
+                        (callback)(event);
+                        if is_dead { break; }
+                    }
+                }
+            );
+    }
+
     /// Access this client's id.
     pub fn id(&self) -> u128
     {
         self.client_id
     }
 
+    /// Get the server's max message size (bytes).
+    ///
+    /// Returns `None` until the server confirms the client's first session is ready. Messages and requests sent
+    /// before then are not checked against the server's limit.
+    pub fn server_max_msg_size(&self) -> Option<u32>
+    {
+        let max_msg_size = self.server_max_msg_size.load(Ordering::Acquire);
+        if max_msg_size == u32::MAX { return None; }
+        Some(max_msg_size)
+    }
+
+    /// Get the client's status.
+    ///
+    /// This is the canonical way to inspect the client's state. It combines [`Client::is_connected()`],
+    /// [`Client::is_closed()`], and [`Client::is_dead()`] into one value.
+    pub fn status(&self) -> ClientStatus
+    {
+        if self.is_dead() { return ClientStatus::Dead; }
+        if self.closed_by_self.load(Ordering::Acquire) { return ClientStatus::ClosingBySelf; }
+        if self.client_connected_signal.load(Ordering::Acquire) { return ClientStatus::Connected; }
+        ClientStatus::Connecting
+    }
+
     /// Test if the client is connected.
     ///
-    /// Messages and requests cannot be submitted when the client is not connected.
+    /// Messages and requests cannot be submitted when the client is not connected. See also [`Client::status()`].
     pub fn is_connected(&self) -> bool
     {
         self.client_connected_signal.load(Ordering::Acquire) && !self.is_closed()
@@ -139,22 +452,48 @@ impl<Channel: ChannelPack> Client<Channel>
     /// - Note that [`ClientReport::IsDead`] will be emitted by [`Client::next()`] when the client backend dies.
     ///
     /// Once this returns true you can drain the client by calling [`Client::next()`] until no more values appear.
-    /// After [`ClientReport::IsDead`] appears, [`Client::next()`] will always return `None`.
+    /// After [`ClientReport::IsDead`] appears, [`Client::next()`] will always return `None`. See also
+    /// [`Client::status()`].
     pub fn is_dead(&self) -> bool
     {
         self.client_closed_signal.load(Ordering::Acquire)
     }
 
+    /// Get the final cause of the client's death.
+    ///
+    /// Returns `None` while the client is alive. Unlike [`ClientReport::IsDead`], the cause remains available after
+    /// the client's events have been drained.
+    pub fn death_reason(&self) -> Option<DeathReason>
+    {
+        if !self.is_dead() { return None; }
+        match self.death_reason.lock()
+        {
+            Ok(death_reason) => death_reason.clone(),
+            Err(_) => { tracing::error!("death reason poisoned"); None }
+        }
+    }
+
     /// Test if the client is closed.
     ///
     /// Returns true after [`Client::close()`] has been called, or once the internal client dies.
     ///
-    /// Messages and requests cannot be submitted once the client is closed.
+    /// Messages and requests cannot be submitted once the client is closed. See also [`Client::status()`].
     pub fn is_closed(&self) -> bool
     {
         self.closed_by_self.load(Ordering::Acquire) || self.is_dead()
     }
 
+    /// Enable or disable automatic reconnects.
+    ///
+    /// When disabled, the client will not try to reconnect after its connection drops (or is closed by the server),
+    /// and will die instead (emitting [`ClientReport::IsDead`]). When enabled (the default), reconnects follow
+    /// [`ClientConfig::reconnect_on_disconnect`] and [`ClientConfig::reconnect_on_server_close`]. Changes take effect
+    /// the next time the connection drops; reconnect attempts already in progress are not interrupted.
+    pub fn set_auto_reconnect(&self, enabled: bool)
+    {
+        self.auto_reconnect.store(enabled, Ordering::Release);
+    }
+
     /// Close the client.
     ///
     /// Any in-progress messages may or may not fail once this method is called. New messages and requests cannot be
@@ -168,17 +507,94 @@ impl<Channel: ChannelPack> Client<Channel>
         tracing::info!("client closing self");
 
         // close the client
-        let closure_frame =
-            ezsockets::CloseFrame{
-                code   : ezsockets::CloseCode::Normal,
-                reason : String::from("client done")
-            };
-        if self.client.close(Some(closure_frame)).is_err()
+        if self.client.close(Some(client_close_frame())).is_err()
         {
             tracing::warn!("tried to close an already dead client");
             return;
         }
 
+        self.mark_closed_by_self();
+    }
+
+    /// Close the client and block until its background task has stopped.
+    ///
+    /// Returns `true` if the client died (and emitted [`ClientReport::IsDead`]) before the timeout. The client is
+    /// consumed, so any events that haven't been consumed yet are discarded.
+    ///
+    /// Not available on WASM targets, because blocking would block the client backend.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn close_and_wait(self, timeout: std::time::Duration) -> bool
+    {
+        if !self.close_and_wait_for_death(timeout) { return false; }
+
+        // discard lingering events, since the caller gave up the client
+        self.client_event_receiver.try_iter().for_each(drop);
+
+        true
+    }
+
+    /// Close the client and block until it dies, without discarding its events.
+    #[cfg(not(target_family = "wasm"))]
+    pub(crate) fn close_and_wait_for_death(&self, timeout: std::time::Duration) -> bool
+    {
+        if !self.is_closed() { self.close(); }
+        let deadline = std::time::Instant::now() + timeout;
+
+        while !self.is_dead()
+        {
+            // wait for the client handler to die
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            match self.connection_updates.recv_timeout(remaining)
+            {
+                Ok(()) => (),
+                Err(crossbeam::channel::RecvTimeoutError::Timeout) =>
+                { tracing::debug!("client timed out while closing"); return false; }
+                Err(crossbeam::channel::RecvTimeoutError::Disconnected) => return self.is_dead(),
+            }
+        }
+
+        true
+    }
+
+    /// Close the client after all previously-sent messages have been flushed.
+    ///
+    /// The close frame is sent only after every message and request sent before this method was called is no longer
+    /// [`MessageStatus::Sending`] (i.e. its [`MessageSignal`] has transitioned to `Sent` or `Failed`). Messages sent
+    /// with [`Client::send_and_forget()`] are not tracked, so they are only guaranteed to be flushed if a tracked
+    /// message was sent after them.
+    ///
+    /// As with [`Client::close()`], the client is marked closed and [`ClientReport::ClosedBySelf`] is emitted
+    /// immediately, so new messages and requests cannot be sent after this method is called. The client will
+    /// eventually emit [`ClientReport::IsDead`] once the close handshake completes.
+    pub fn close_after_flush(&self)
+    {
+        // sanity check
+        if self.is_closed() { tracing::warn!("tried to close an already closed client"); return; }
+        tracing::info!("client closing self after flushing");
+
+        // collect messages that are still sending
+        let signals = match self.pending_messages.lock()
+        {
+            Ok(mut pending_messages) => pending_messages.pending_signals(),
+            Err(_) => { tracing::error!("pending message tracker poisoned"); Vec::default() }
+        };
+
+        // close the client once the messages are flushed
+        if self.client.call(ClientHandlerCall::CloseAfterFlush(signals, client_close_frame())).is_err()
+        {
+            tracing::warn!("tried to close an already dead client");
+            return;
+        }
+
+        self.mark_closed_by_self();
+    }
+}
+
+impl<Channel: ChannelPack> Client<Channel>
+{
+    /// Mark the client as closed after initiating a close.
+    fn mark_closed_by_self(&self)
+    {
         // forward event to other end of channel
         if let Err(err) = self.client_event_sender.send(ClientEventFrom::<Channel>::Report(ClientReport::ClosedBySelf))
         {
@@ -188,14 +604,100 @@ impl<Channel: ChannelPack> Client<Channel>
         // note: request failures will be emitted for all pending requests when the internal client is dropped
 
         // mark the client as closed
+        record_death_reason(&self.death_reason, DeathReason::ClosedBySelf);
         self.closed_by_self.store(true, Ordering::Release);
     }
+
+    /// Send a one-shot message to the server.
+    /// - Assumes the caller already checked if the client is connected.
+    fn send_msg(&self, msg: Channel::ClientMsg) -> Result<MessageSignal, TrySendError>
+    {
+        // serialize message
+        let Ok(mut ser_msg) = bincode::DefaultOptions::new().serialize(&ServerMetaEventFrom::<Channel>::Msg(msg))
+        else { tracing::error!("failed serializing client message"); return Err(TrySendError::Failed); };
+        apply_bytes_hook(&self.on_send, &mut ser_msg);
+        self.check_msg_size(&ser_msg)?;
+
+        // forward message to server
+        match self.send_frame(ser_msg)
+        {
+            Ok(signal) => Ok(self.track_pending_msg(signal)),
+            Err(_) =>
+            {
+                tracing::warn!("tried to send message to dead client");
+                Err(TrySendError::Failed)
+            }
+        }
+    }
+
+    /// Check a serialized message against the server's max message size and the channel's type-specific size limits.
+    fn check_msg_size(&self, ser_msg: &[u8]) -> Result<(), TrySendError>
+    {
+        let mut limit = self.server_max_msg_size.load(Ordering::Acquire);
+        if let Some(type_limit) = server_meta_event_size_limit::<Channel>(ser_msg) { limit = limit.min(type_limit); }
+        if ser_msg.len() <= limit as usize { return Ok(()); }
+
+        tracing::warn!(size = ser_msg.len(), limit, "tried to send message larger than its max message size");
+        Err(TrySendError::MessageTooLarge{ size: ser_msg.len(), limit })
+    }
+
+    /// Send a serialized message to the server.
+    fn send_frame(&self, bytes: Vec<u8>) -> Result<MessageSignal, ()>
+    {
+        let size = bytes.len();
+        let signal = send_client_frame(&self.client, self.text_frames, bytes)?;
+        self.observer.on_msg_out(self.client_id, size);
+        Ok(signal)
+    }
+
+    /// Record a message in the outbound buffer tracker.
+    fn track_pending_msg(&self, signal: MessageSignal) -> MessageSignal
+    {
+        match self.pending_messages.lock()
+        {
+            Ok(mut pending_messages) => pending_messages.add(signal.clone()),
+            Err(_) => tracing::error!("pending message tracker poisoned"),
+        }
+        signal
+    }
+
+    /// Send a control message to the server.
+    fn send_control(&self, control_msg: ServerMetaEventFrom<Channel>) -> Result<MessageSignal, ()>
+    {
+        // check if connected
+        if !self.is_connected() { tracing::warn!("tried to send control message to disconnected client"); return Err(()); }
+
+        // forward message to server
+        let is_chunk = matches!(control_msg, ServerMetaEventFrom::<Channel>::Chunk(_));
+        let Ok(mut ser_msg) = bincode::DefaultOptions::new().serialize(&control_msg)
+        else { tracing::error!("failed serializing client control message"); return Err(()); };
+        apply_bytes_hook(&self.on_send, &mut ser_msg);
+
+        // check the message size
+        // - stream chunks are exempt, their size is controlled by `ClientConfig::stream_chunk_size`
+        if !is_chunk { self.check_msg_size(&ser_msg).map_err(|_| ())?; }
+
+        match self.send_frame(ser_msg)
+        {
+            Ok(signal) => Ok(self.track_pending_msg(signal)),
+            Err(_) =>
+            {
+                tracing::warn!("tried to send control message to dead client");
+                Err(())
+            }
+        }
+    }
 }
 
 impl<Channel: ChannelPack> Drop for Client<Channel>
 {
     fn drop(&mut self)
     {
+        // log events that will never be read
+        // - this can happen if the client is dropped (e.g. removed as a bevy resource) without being drained
+        let num_lost_events = self.client_event_receiver.len();
+        if num_lost_events > 0 { tracing::warn!(num_lost_events, "dropping client with undrained events"); }
+
         if self.is_dead() { return; }
         self.close();
     }
@@ -209,6 +711,7 @@ impl<Channel: ChannelPack> Drop for Client<Channel>
 pub struct ClientFactory<Channel: ChannelPack>
 {
     protocol_version : &'static str,
+    observer         : Arc<dyn Observer>,
     _phantom         : PhantomData<Channel>,
 }
 
@@ -217,7 +720,14 @@ impl<Channel: ChannelPack> ClientFactory<Channel>
     /// Make a new server factory with a given protocol version.
     pub fn new(protocol_version: &'static str) -> Self
     {
-        ClientFactory{ protocol_version, _phantom: PhantomData::default() }
+        ClientFactory{ protocol_version, observer: Arc::new(NoopObserver), _phantom: PhantomData::default() }
+    }
+
+    /// Set the [`Observer`] for clients made by this factory.
+    pub fn with_observer(mut self, observer: Arc<dyn Observer>) -> Self
+    {
+        self.observer = observer;
+        self
     }
 
     /// New client.
@@ -225,14 +735,17 @@ impl<Channel: ChannelPack> ClientFactory<Channel>
         runtime_handle : enfync::builtin::Handle,
         url            : url::Url,
         auth           : AuthRequest,
-        config         : ClientConfig,
+        mut config     : ClientConfig,
         connect_msg    : Channel::ConnectMsg,
     ) -> Client<Channel>
     {
+        // compress then encrypt sent payloads after the send hook
+        config.on_send = with_payload_compression(config.on_send.take(), &config.payload_compression);
+        config.on_send = with_payload_encryption(config.on_send.take(), &config.payload_encryption);
+
         // prepare to make client connection
         // note: urls cannot contain raw bytes so we must serialize as json
-        let auth_msg_ser    = serde_json::to_string(&auth).expect("could not serialize authentication");
-        let connect_msg_ser = serde_json::to_string(&connect_msg).expect("could not serialize connect msg");
+        let auth_msg_ser = serde_json::to_string(&auth).expect("could not serialize authentication");
 
         let client_config = ezsockets::ClientConfig::new(url)
             .reconnect_interval(config.reconnect_interval)
@@ -240,8 +753,44 @@ impl<Channel: ChannelPack> ClientFactory<Channel>
             .max_reconnect_attempts(config.max_reconnect_attempts)
             .query_parameter(VERSION_MSG_KEY, self.protocol_version)
             .query_parameter(TYPE_MSG_KEY, env_type_as_str(env_type()))
-            .query_parameter(AUTH_MSG_KEY, auth_msg_ser.as_str())
-            .query_parameter(CONNECT_MSG_KEY, connect_msg_ser.as_str());
+            .query_parameter(AUTH_MSG_KEY, auth_msg_ser.as_str());
+
+        // add the connect message to the url, or prepare it to be sent in the first binary frame
+        let (client_config, connect_msg_frame) = match config.binary_connect_msg
+        {
+            false =>
+            {
+                let connect_msg_ser = serde_json::to_string(&connect_msg).expect("could not serialize connect msg");
+                (client_config.query_parameter(CONNECT_MSG_KEY, connect_msg_ser.as_str()), None)
+            }
+            true =>
+            {
+                let mut connect_msg_ser = bincode::DefaultOptions::new()
+                    .serialize(&connect_msg)
+                    .expect("could not serialize connect msg");
+                apply_bytes_hook(&config.on_send, &mut connect_msg_ser);
+                (client_config, Some(connect_msg_ser))
+            }
+        };
+
+        // select base64 text frames
+        let client_config = match config.text_frames
+        {
+            false => client_config,
+            true  => client_config.query_parameter(FORMAT_MSG_KEY, BASE64_MSG_FORMAT),
+        };
+
+        // add custom request headers
+        let client_config = match &config.user_agent
+        {
+            Some(user_agent) => client_config.header("User-Agent", user_agent.as_str()),
+            None             => client_config,
+        };
+        let client_config = match &config.origin
+        {
+            Some(origin) => client_config.header("Origin", origin.as_str()),
+            None         => client_config,
+        };
 
         // prepare client's socket config
         let mut socket_config = ezsockets::SocketConfig::default();
@@ -262,30 +811,79 @@ impl<Channel: ChannelPack> ClientFactory<Channel>
         // prepare client connector
         let client_connector = {
                 #[cfg(not(target_family = "wasm"))]
-                { ezsockets::ClientConnectorTokio::from(runtime_handle.clone()) }
+                {
+                    TimeoutClientConnector::new(
+                            ezsockets::ClientConnectorTokio::from(runtime_handle.clone()),
+                            config.connect_timeout
+                        )
+                }
 
                 #[cfg(target_family = "wasm")]
                 { ezsockets::ClientConnectorWasm::default() }
             };
+        let connection_aborter = ConnectionAborter::default();
+        let client_connector = AbortableClientConnector::new(client_connector, connection_aborter.clone());
 
         // make client core with our handler
+        let on_send = config.on_send.clone();
+        let text_frames = config.text_frames;
+        let client_id = auth.client_id();
+        let observer = self.observer.clone();
+        let observer_clone = observer.clone();
+        let max_pending_msgs = config.max_pending_msgs;
+        let stream_chunk_size = config.stream_chunk_size;
+        let stream_reassembler = StreamReassembler::new(u32::MAX, config.stream_timeout);
         let client_event_sender_clone = client_event_sender.clone();
         let pending_requests = Arc::new(Mutex::new(PendingRequestTracker::default()));
         let pending_requests_clone = pending_requests.clone();
+        let server_max_msg_size = Arc::new(AtomicU32::new(u32::MAX));
+        let server_max_msg_size_clone = server_max_msg_size.clone();
         let client_connected_signal = Arc::new(AtomicBool::new(false));
         let client_closed_signal = Arc::new(AtomicBool::new(false));
         let client_connected_signal_clone = client_connected_signal.clone();
         let client_closed_signal_clone = client_closed_signal.clone();
+        let auto_reconnect = Arc::new(AtomicBool::new(true));
+        let auto_reconnect_clone = auto_reconnect.clone();
+        let pending_pings = Arc::new(Mutex::new(PingTracker::default()));
+        let pending_pings_clone = pending_pings.clone();
+        let pong_tracker = config.max_missed_pongs.map(PongTracker::new);
+        let death_reason = Arc::new(Mutex::new(None));
+        let death_reason_clone = death_reason.clone();
+        let (connection_notifier, connection_updates) = crossbeam::channel::bounded(1);
+        let ping_timeout = config.keepalive_timeout;
+        let send_status = SendStatusNotifier::new(runtime_handle.clone());
+        let send_status_clone = send_status.clone();
         let (client, _client_task_handle) = ezsockets::connect_with(
                 move |client|
                 {
                     ClientHandler::<Channel>{
+                            span: log_span(&config.log_id),
                             config,
                             client,
+                            client_id,
+                            observer                : observer_clone,
+                            connect_msg_frame,
+                            has_connected           : false,
+                            failed_connect_attempts : 0,
+                            last_server_seq         : None,
+                            awaiting_ready          : false,
+                            recv_activity           : RecvActivity::new(),
+                            reconnecting_since      : None,
+                            stream_reassembler,
                             client_event_sender     : client_event_sender_clone,
                             pending_requests        : pending_requests_clone,
+                            server_max_msg_size     : server_max_msg_size_clone,
                             client_connected_signal : client_connected_signal_clone,
                             client_closed_signal    : client_closed_signal_clone,
+                            auto_reconnect          : auto_reconnect_clone,
+                            pending_pings           : pending_pings_clone,
+                            pong_tracker,
+                            heartbeat_generation    : Arc::new(AtomicU64::new(0u64)),
+                            connection_aborter,
+                            missed_pongs            : false,
+                            death_reason            : death_reason_clone,
+                            connection_notifier,
+                            send_status             : send_status_clone,
                         }
                 },
                 client_config,
@@ -296,15 +894,79 @@ impl<Channel: ChannelPack> ClientFactory<Channel>
         tracing::info!("created new client");
 
         Client{
-                client_id: auth.client_id(),
+                client_id,
+                on_send,
+                text_frames,
+                observer,
+                max_pending_msgs,
+                stream_chunk_size,
+                next_stream_id: AtomicU64::new(0u64),
                 client,
                 client_event_sender,
                 client_event_receiver,
                 pending_requests,
+                server_max_msg_size,
+                pending_messages: Mutex::new(PendingMessageTracker::default()),
                 client_connected_signal,
                 client_closed_signal,
                 closed_by_self: Arc::new(AtomicBool::new(false)),
+                auto_reconnect,
+                pending_pings,
+                ping_timeout,
+                death_reason,
+                connection_updates,
+                send_status,
+            }
+    }
+
+    /// New client that blocks until it is connected.
+    ///
+    /// Returns `Err` if the client dies before connecting or does not connect before the timeout. On failure the
+    /// client is dropped.
+    ///
+    /// The [`ClientConfig::connection_ready_barrier`] is always enabled, so the client only counts as connected once
+    /// the server has accepted its session. The client's [`ClientReport::Connected`] event will still be emitted by
+    /// [`Client::next()`].
+    ///
+    /// Not available on WASM targets, because blocking would block the client backend.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn new_client_blocking(&self,
+        runtime_handle : enfync::builtin::Handle,
+        url            : url::Url,
+        auth           : AuthRequest,
+        mut config     : ClientConfig,
+        connect_msg    : Channel::ConnectMsg,
+        timeout        : std::time::Duration,
+    ) -> Result<Client<Channel>, ConnectError>
+    {
+        // wait for the server to accept the session
+        // - without the barrier the client would count as connected before the server can reject it
+        config.connection_ready_barrier = true;
+        let client = self.new_client(runtime_handle, url, auth, config, connect_msg);
+        let deadline = std::time::Instant::now() + timeout;
+
+        loop
+        {
+            if client.is_connected() { return Ok(client); }
+            if client.is_dead() { return Err(connect_error(client.death_reason())); }
+
+            // wait for the client handler to connect or die
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            match client.connection_updates.recv_timeout(remaining)
+            {
+                Ok(()) => (),
+                Err(crossbeam::channel::RecvTimeoutError::Timeout) =>
+                {
+                    tracing::debug!("client timed out while connecting");
+                    return Err(ConnectError::TimedOut);
+                }
+                Err(crossbeam::channel::RecvTimeoutError::Disconnected) =>
+                {
+                    // the client handler is gone, so the client is dead
+                    return Err(connect_error(client.death_reason()));
+                }
             }
+        }
     }
 }
 
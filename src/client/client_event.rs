@@ -16,13 +16,36 @@ pub enum ClientReport
     ///
     /// This event synchronizes with the request/response pattern. All requests sent before the client became connected
     /// will receive a result event (Response/Ack/Reject/SendFailed/ResponseLost) before `Connected` is emitted.
+    ///
+    /// This is only emitted for the client's first connection. Subsequent connections emit [`ClientReport::Reconnected`].
     Connected,
+    /// The client reconnected to the server after having been connected before.
+    ///
+    /// This has the same synchronization guarantees as [`ClientReport::Connected`].
+    Reconnected,
     /// The client disconnected from the server.
-    Disconnected,
+    ///
+    /// The reason is either [`DisconnectReason::TimedOut`] or [`DisconnectReason::ConnectionLost`], depending on
+    /// whether the server was silent for at least [`ClientConfig::keepalive_timeout`] before the connection ended.
+    Disconnected(DisconnectReason),
     /// The client was closed by the server.
+    ///
+    /// Use [`CloseReason::from_close_frame()`] to get the reason the server closed the client, and
+    /// [`CloseReason::payload_from_close_frame()`] to decode a structured close payload.
     ClosedByServer(Option<ezsockets::CloseFrame>),
     /// The client closed itself.
     ClosedBySelf,
+    /// The server is draining connections and will soon close the client (see [`Server::begin_lame_duck()`]).
+    ///
+    /// Clients should migrate (e.g. close and connect to a different server) before the server's grace period
+    /// expires.
+    ServerDraining,
+    /// Messages sent by the server were lost (e.g. they were evicted from the server's resend buffer while the client
+    /// was reconnecting). Contains the inclusive range of missing sequence numbers.
+    ///
+    /// Only messages the server sends with a resend buffer are sequenced (see [`ServerConfig::resend_buffer_size`]).
+    /// Use this to trigger app-level reconciliation.
+    GapDetected{ missing_from: u64, missing_to: u64 },
     /// The client has died and will not try to reconnect.
     ///
     /// Stores the pending request ids for requests that were [`RequestStatus::Sending`] at the time the client died.
@@ -34,15 +57,40 @@ pub enum ClientReport
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// The final cause of a client's death. See [`Client::death_reason()`].
+#[derive(Debug, Clone)]
+pub enum DeathReason
+{
+    /// The client closed itself (e.g. with [`Client::close()`]).
+    ClosedBySelf,
+    /// The client was closed by the server and did not reconnect.
+    ///
+    /// Use [`CloseReason::from_close_frame()`] to get the reason the server closed the client. Clients rejected by the
+    /// server while connecting or reconnecting die with this reason, with [`CloseReason::AuthFailed`] or
+    /// [`CloseReason::VersionMismatch`]. Rejected clients never reconnect.
+    ClosedByServer(Option<ezsockets::CloseFrame>),
+    /// The client disconnected from the server and did not reconnect.
+    Disconnected(DisconnectReason),
+    /// The client failed to reconnect within [`ClientConfig::max_reconnect_duration`].
+    ReconnectTimedOut,
+    /// The client ran out of connection attempts: [`ClientConfig::max_initial_connect_attempts`] before it first
+    /// connected, or [`ClientConfig::max_reconnect_attempts`] while reconnecting.
+    ReconnectAttemptsExhausted,
+    /// The client backend shut down because of an internal error.
+    InternalError,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// An event received by a client.
 ///
 /// The `SendFailed` and `ResponseLost` events will only be emitted in these scenarios:
-/// - Between [`ClientReport::Disconnected`] and [`ClientReport::Connected`] reports (if the `reconnect_on_disconnect`
-///   config is set).
+/// - Between [`ClientReport::Disconnected`] and [`ClientReport::Connected`]/[`ClientReport::Reconnected`] reports (if
+///   the `reconnect_on_disconnect` config is set).
 /// - Between [`ClientReport::Disconnected`] and [`ClientReport::IsDead`] reports (if the `reconnect_on_disconnect`
 ///   config is not set).
-/// - Between [`ClientReport::ClosedByServer`] and [`ClientReport::Connected`] reports (if the `reconnect_on_server_close`
-///   config is set).
+/// - Between [`ClientReport::ClosedByServer`] and [`ClientReport::Connected`]/[`ClientReport::Reconnected`] reports (if
+///   the `reconnect_on_server_close` config is set).
 /// - Between [`ClientReport::ClosedByServer`] and [`ClientReport::IsDead`] reports (if the `reconnect_on_server_close`
 ///   config is not set).
 /// - Between [`ClientReport::ClosedBySelf`] and [`ClientReport::IsDead`] reports.
@@ -62,6 +110,9 @@ pub enum ClientEvent<ServerMsg, ServerResponse>
     /// This will not be followed by a subsequent response (you either get a response, ack, or rejection).
     Ack(u64),
     /// The server rejected a client request.
+    ///
+    /// The server may reject a request at any time before responding to it, so this can also be used as a
+    /// cancellation notice for requests that are still being prepared.
     Reject(u64),
     /// Sending a request failed.
     SendFailed(u64),
@@ -78,3 +129,92 @@ pub type ClientEventFrom<Channel> = ClientEvent<
 >;
 
 //-------------------------------------------------------------------------------------------------------------------
+
+/// Client events partitioned by type. See [`Client::drain_grouped()`].
+///
+/// Events keep their relative order within each group, but the order between groups is lost. Use [`Client::next()`]
+/// if you need to know, for example, whether a message arrived before or after a reconnect.
+#[derive(Debug)]
+pub struct GroupedEvents<Channel: ChannelPack>
+{
+    /// Connection reports.
+    pub reports: Vec<ClientReport>,
+    /// One-shot server messages.
+    pub msgs: Vec<Channel::ServerMsg>,
+    /// Responses to client requests, with their request ids.
+    pub responses: Vec<(Channel::ServerResponse, u64)>,
+    /// Ids of acknowledged requests.
+    pub acks: Vec<u64>,
+    /// Ids of rejected requests.
+    pub rejections: Vec<u64>,
+    /// Ids of requests that failed to send.
+    pub send_failures: Vec<u64>,
+    /// Ids of requests whose responses were lost.
+    pub responses_lost: Vec<u64>,
+}
+
+impl<Channel: ChannelPack> GroupedEvents<Channel>
+{
+    /// Add an event to its group.
+    pub fn push(&mut self, event: ClientEventFrom<Channel>)
+    {
+        match event
+        {
+            ClientEvent::Report(report)                 => self.reports.push(report),
+            ClientEvent::Msg(msg)                       => self.msgs.push(msg),
+            ClientEvent::Response(response, request_id) => self.responses.push((response, request_id)),
+            ClientEvent::Ack(request_id)                => self.acks.push(request_id),
+            ClientEvent::Reject(request_id)             => self.rejections.push(request_id),
+            ClientEvent::SendFailed(request_id)         => self.send_failures.push(request_id),
+            ClientEvent::ResponseLost(request_id)       => self.responses_lost.push(request_id),
+        }
+    }
+
+    /// Check if there are no events in any group.
+    pub fn is_empty(&self) -> bool
+    {
+        self.reports.is_empty()
+            && self.msgs.is_empty()
+            && self.responses.is_empty()
+            && self.acks.is_empty()
+            && self.rejections.is_empty()
+            && self.send_failures.is_empty()
+            && self.responses_lost.is_empty()
+    }
+}
+
+impl<Channel: ChannelPack> Default for GroupedEvents<Channel>
+{
+    fn default() -> Self
+    {
+        Self{
+            reports        : Vec::default(),
+            msgs           : Vec::default(),
+            responses      : Vec::default(),
+            acks           : Vec::default(),
+            rejections     : Vec::default(),
+            send_failures  : Vec::default(),
+            responses_lost : Vec::default(),
+        }
+    }
+}
+
+impl<Channel: ChannelPack> Extend<ClientEventFrom<Channel>> for GroupedEvents<Channel>
+{
+    fn extend<I: IntoIterator<Item = ClientEventFrom<Channel>>>(&mut self, events: I)
+    {
+        for event in events { self.push(event); }
+    }
+}
+
+impl<Channel: ChannelPack> FromIterator<ClientEventFrom<Channel>> for GroupedEvents<Channel>
+{
+    fn from_iter<I: IntoIterator<Item = ClientEventFrom<Channel>>>(events: I) -> Self
+    {
+        let mut grouped = Self::default();
+        grouped.extend(events);
+        grouped
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -7,9 +7,70 @@ use bincode::Options;
 //standard shortcuts
 use core::fmt::Debug;
 use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::vec::Vec;
 
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+
+#[cfg(target_family = "wasm")]
+use wasm_timer::Instant;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Calls from the [`Client`] to its client handler.
+#[derive(Debug)]
+pub(crate) enum ClientHandlerCall
+{
+    /// Close the client once none of the messages tracked by the signals are sending.
+    CloseAfterFlush(Vec<MessageSignal>, ezsockets::CloseFrame),
+    /// Send a heartbeat ping to the server (see [`ClientConfig::max_missed_pongs`]).
+    /// - Contains the generation of the heartbeat that made the call, calls from stopped heartbeats are ignored.
+    Heartbeat(u64),
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Send a serialized message to the server, as a base64 text frame if the client uses text frames.
+pub(crate) fn send_client_frame<Channel: ChannelPack>(
+    client      : &ezsockets::Client<ClientHandler<Channel>>,
+    text_frames : bool,
+    bytes       : Vec<u8>
+) -> Result<MessageSignal, ()>
+{
+    let result = match text_frames
+    {
+        true  => client.text(encode_text_frame(&bytes)),
+        false => client.binary(bytes),
+    };
+    result.map_err(|_| ())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Record the cause of a client's death.
+/// - Only the first recorded cause is kept, since it is the one that led to the client shutting down.
+pub(crate) fn record_death_reason(death_reason: &Mutex<Option<DeathReason>>, reason: DeathReason)
+{
+    match death_reason.lock()
+    {
+        Ok(mut death_reason) => { death_reason.get_or_insert(reason); }
+        Err(_) => tracing::error!("death reason poisoned"),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Check if the server closed the client because it rejected the client's connection request.
+fn is_connection_rejection(death_reason: &DeathReason) -> bool
+{
+    let DeathReason::ClosedByServer(Some(close_frame)) = death_reason else { return false; };
+    matches!(
+            CloseReason::from_close_frame(close_frame),
+            Some(CloseReason::AuthFailed) | Some(CloseReason::VersionMismatch)
+        )
+}
+
 //-------------------------------------------------------------------------------------------------------------------
 
 #[derive(Debug)]
@@ -17,28 +78,85 @@ pub(crate) struct ClientHandler<Channel: ChannelPack>
 {
     /// config
     pub(crate) config: ClientConfig,
+    /// tracing span for this client's logs
+    pub(crate) span: tracing::Span,
     /// core websockets client
     pub(crate) client: ezsockets::Client<ClientHandler<Channel>>,
+    /// this client's id
+    pub(crate) client_id: u128,
+    /// observer for lifecycle events
+    pub(crate) observer: Arc<dyn Observer>,
+    /// connect message to send in the first binary frame after connecting (if not sent in the connection request)
+    pub(crate) connect_msg_frame: Option<Vec<u8>>,
+    /// whether the client has connected at least once (used to distinguish reconnects)
+    pub(crate) has_connected: bool,
+    /// number of failed connection attempts since the client last connected
+    pub(crate) failed_connect_attempts: usize,
+    /// sequence number of the last sequenced message received from the server (used for server resends)
+    /// - resent messages at or below this number are duplicates and are dropped
+    pub(crate) last_server_seq: Option<u64>,
+    /// indicates the client is waiting for the server to confirm the session is ready
+    pub(crate) awaiting_ready: bool,
+    /// tracks when the client last received a frame (used to classify disconnects)
+    pub(crate) recv_activity: RecvActivity,
+    /// when the client started reconnecting after its connection was lost (`None` while connected)
+    pub(crate) reconnecting_since: Option<Instant>,
+    /// reassembles streamed messages
+    pub(crate) stream_reassembler: StreamReassembler,
     /// send client events to the client
     pub(crate) client_event_sender: crossbeam::channel::Sender<ClientEventFrom<Channel>>,
     /// synchronized tracker for pending requests
     pub(crate) pending_requests: Arc<Mutex<PendingRequestTracker>>,
+    /// the server's max message size (shared with the client)
+    pub(crate) server_max_msg_size: Arc<AtomicU32>,
     /// signal to communicate when the client handler is connected; synchronizes with connection events
     pub(crate) client_connected_signal: Arc<AtomicBool>,
     /// signal to communicate when the client handler is dead; synchronizes with draining the pending request cache
     pub(crate) client_closed_signal: Arc<AtomicBool>,
+    /// flag that allows reconnecting after the connection drops (shared with the client)
+    pub(crate) auto_reconnect: Arc<AtomicBool>,
+    /// synchronized tracker for on-demand pings
+    pub(crate) pending_pings: Arc<Mutex<PingTracker>>,
+    /// tracks heartbeat pings sent to the server (`None` if missed pongs are not detected)
+    pub(crate) pong_tracker: Option<PongTracker>,
+    /// generation of the current heartbeat (incremented when a heartbeat starts or stops)
+    pub(crate) heartbeat_generation: Arc<AtomicU64>,
+    /// aborts the current connection (e.g. after missed pongs)
+    pub(crate) connection_aborter: ConnectionAborter,
+    /// set when the current connection was aborted after missed pongs (used to classify the disconnect)
+    pub(crate) missed_pongs: bool,
+    /// the final cause of the client's death (shared with the client)
+    pub(crate) death_reason: Arc<Mutex<Option<DeathReason>>>,
+    /// notifies the client when the client handler connects or dies
+    pub(crate) connection_notifier: crossbeam::channel::Sender<()>,
+    /// wakes tasks waiting for outbound messages to finish sending (shared with the client)
+    pub(crate) send_status: Arc<SendStatusNotifier>,
 }
 
 #[async_trait::async_trait]
 impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
 {
-    type Call = ();
+    type Call = ClientHandlerCall;
 
     /// Text from server.
-    /// - Does nothing on native.
+    /// - Decodes base64 text frames if the client uses text frames.
+    /// - Otherwise does nothing on native.
     /// - Echoes the text back to the server on WASM for custom Ping/Pong protocol.
     async fn on_text(&mut self, text: String) -> Result<(), ezsockets::Error>
     {
+        let span = self.span.clone();
+        let _entered = span.enter();
+        self.recv_activity.touch();
+
+        // decode text frames
+        // - base64 text can't contain ':', so it can't be confused with WASM ping/pong text
+        if self.config.text_frames && !text.contains(':')
+        {
+            let Ok(bytes) = decode_text_frame(&text)
+            else { tracing::warn!("received text frame that failed to decode"); return Ok(()); };
+            return self.on_binary(bytes).await;
+        }
+
         match env_type()
         {
             EnvType::Native =>
@@ -77,9 +195,25 @@ impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
     }
 
     /// Binary from server.
-    async fn on_binary(&mut self, bytes: Vec<u8>) -> Result<(), ezsockets::Error>
+    async fn on_binary(&mut self, mut bytes: Vec<u8>) -> Result<(), ezsockets::Error>
     {
+        let span = self.span.clone();
+        let _entered = span.enter();
+
         tracing::trace!("received binary from server");
+        self.recv_activity.touch();
+        self.observer.on_msg_in(self.client_id, bytes.len());
+        if decrypt_payload(&self.config.payload_encryption, &mut bytes).is_err()
+        {
+            tracing::warn!("received server msg that failed to decrypt");
+            return Ok(());
+        }
+        if decompress_payload(&self.config.payload_compression, &mut bytes, usize::MAX).is_err()
+        {
+            tracing::warn!("received server msg that failed to decompress");
+            return Ok(());
+        }
+        apply_bytes_hook(&self.config.on_recv, &mut bytes);
 
         // deserialize message
         let Ok(server_msg) = bincode::DefaultOptions::new().deserialize(&bytes[..])
@@ -137,8 +271,113 @@ impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
                 }
 
                 // rejection
+                self.observer.on_rejection(self.client_id, request_id);
                 ClientEventFrom::<Channel>::Reject(request_id)
             }
+            ClientMetaEventFrom::<Channel>::Chunk(chunk) =>
+            {
+                // try to reassemble the stream
+                let bytes = match self.stream_reassembler.add_chunk(chunk)
+                {
+                    Ok(Some(bytes)) => bytes,
+                    Ok(None) => return Ok(()),
+                    Err(err) => { tracing::warn!(?err, "ignoring invalid stream chunk from server"); return Ok(()); }
+                };
+
+                // deserialize the streamed message
+                let Ok(msg) = bincode::DefaultOptions::new().deserialize(&bytes[..])
+                else
+                {
+                    tracing::warn!("received streamed server msg that failed to deserialize");
+                    return Ok(());
+                };
+
+                // msg
+                ClientEventFrom::<Channel>::Msg(msg)
+            }
+            ClientMetaEventFrom::<Channel>::SeqMsg(msg, seq) =>
+            {
+                // discard message if it was already received
+                // - this can happen if the server resends messages after a reconnect
+                if let Some(last_seq) = self.last_server_seq
+                {
+                    if seq <= last_seq { tracing::trace!(seq, "ignoring duplicate server msg"); return Ok(()); }
+                    if seq > last_seq + 1 { self.report_gap(last_seq + 1, seq - 1)?; }
+                }
+                self.last_server_seq = Some(seq);
+
+                // msg
+                ClientEventFrom::<Channel>::Msg(msg)
+            }
+            ClientMetaEventFrom::<Channel>::ResumeRequest =>
+            {
+                // tell the server the last sequenced message received
+                let Ok(mut ser_msg) = bincode::DefaultOptions::new().serialize(
+                        &ServerMetaEventFrom::<Channel>::Resume(self.last_server_seq)
+                    )
+                else { tracing::error!("failed serializing resume message"); return Ok(()); };
+                apply_bytes_hook(&self.config.on_send, &mut ser_msg);
+
+                if let Err(_) = self.send_frame(ser_msg)
+                {
+                    tracing::warn!("failed to send resume message");
+                }
+                return Ok(());
+            }
+            ClientMetaEventFrom::<Channel>::Ready(max_msg_size) =>
+            {
+                // record the server's max message size
+                self.server_max_msg_size.store(max_msg_size, Ordering::Release);
+
+                // finish connecting if waiting for the server
+                if !self.awaiting_ready { return Ok(()); }
+                self.awaiting_ready = false;
+                return self.finish_connecting();
+            }
+            ClientMetaEventFrom::<Channel>::Resumed(base_seq) =>
+            {
+                // messages between the last received and the base were evicted from the server's resend log
+                if let Some(last_seq) = self.last_server_seq
+                {
+                    if base_seq > last_seq { self.report_gap(last_seq + 1, base_seq)?; }
+                }
+
+                // subsequent sequenced messages will follow the base
+                self.last_server_seq = Some(base_seq);
+                return Ok(());
+            }
+            ClientMetaEventFrom::<Channel>::Draining =>
+            {
+                tracing::info!("server is draining connections");
+                ClientEventFrom::<Channel>::Report(ClientReport::ServerDraining)
+            }
+            ClientMetaEventFrom::<Channel>::Pong(ping_id) =>
+            {
+                // any pong shows the connection is alive
+                if let Some(pong_tracker) = &mut self.pong_tracker { pong_tracker.on_pong(); }
+
+                // record the pong for the pending ping (pings are not reported as client events)
+                // - heartbeat pings are not tracked as pending pings
+                match self.pending_pings.lock()
+                {
+                    Ok(mut pending_pings) =>
+                    {
+                        if !pending_pings.set_pong(ping_id) && self.pong_tracker.is_none()
+                        { tracing::debug!(ping_id, "ignoring pong for unknown ping"); }
+                    }
+                    Err(_) => tracing::error!("ping tracker poisoned"),
+                }
+                return Ok(());
+            }
+            ClientMetaEventFrom::<Channel>::Ping(ping_id) =>
+            {
+                // answer the server's heartbeat ping
+                if self.send_control_msg(ServerMetaEventFrom::<Channel>::Pong(ping_id)).is_err()
+                {
+                    tracing::warn!("failed to send pong");
+                }
+                return Ok(());
+            }
         };
 
         // forward to client owner
@@ -153,55 +392,84 @@ impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
 
     /// Call from associated client.
     ///
-    /// Does nothing.
-    async fn on_call(&mut self, _msg: ()) -> Result<(), ezsockets::Error>
+    /// Closes the client after its outbound messages are flushed.
+    async fn on_call(&mut self, call: ClientHandlerCall) -> Result<(), ezsockets::Error>
     {
-        // ignore call
-        tracing::error!("on_call() invocation (not handled)");
+        let span = self.span.clone();
+        let _entered = span.enter();
+
+        match call
+        {
+            ClientHandlerCall::CloseAfterFlush(signals, close_frame) =>
+            {
+                // wait for messages to finish sending
+                // - wait in a background task so the handler can keep processing other events, then re-issue the
+                //   call once the flush resolves
+                let mut flush = MessageFlush::new(signals, self.send_status.clone());
+                if !flush.is_done()
+                {
+                    let client = self.client.clone();
+                    self.send_status.spawn(async move {
+                            flush.await;
+                            if client.call(ClientHandlerCall::CloseAfterFlush(Vec::default(), close_frame)).is_err()
+                            {
+                                tracing::debug!("client died before its outbound messages were flushed");
+                            }
+                        });
+                    return Ok(());
+                }
+
+                // close the client
+                tracing::info!("outbound messages flushed, closing");
+                if self.client.close(Some(close_frame)).is_err()
+                {
+                    tracing::warn!("tried to close an already dead client");
+                }
+            }
+            ClientHandlerCall::Heartbeat(generation) =>
+            {
+                if generation != self.heartbeat_generation.load(Ordering::Acquire) { return Ok(()); }
+                self.handle_heartbeat();
+            }
+        }
+
         Ok(())
     }
 
     /// Respond to the client acquiring a connection.
     async fn on_connect(&mut self) -> Result<(), ezsockets::Error>
     {
-        tracing::info!("connected");
+        let span = self.span.clone();
+        let _entered = span.enter();
 
-        // lock the pending requests cache
-        let Ok(mut pending_requests) = self.pending_requests.lock() else { return Ok(()); };
+        tracing::info!("connected");
+        self.recv_activity.touch();
+        self.failed_connect_attempts = 0;
 
-        // clean up existing requests
-        // - do this before sending connection event so the event stream is synchronized
-        let aborted_sends = Self::final_request_cleanup(&mut pending_requests, &self.client_event_sender);
+        // discard partial streams from the previous connection
+        self.stream_reassembler.clear();
 
-        // convert aborted sends to `SendFailed`
-        // - `aborted_sends` may not be empty if the socket for the previous connection did not fully shut down yet.
-        //   It is possible for messages to linger in the socket's internal sink even after the socket has
-        //   been dropped. Those messages are guaranteed to fail because on_connect() synchronizes with the
-        //   server's old session completely shutting down, so we can treat them as such here.
-        for aborted_send in aborted_sends
+        // send the connect message
+        // - do this before the client is marked as connected so it is the first frame sent by this connection
+        if let Some(connect_msg_frame) = &self.connect_msg_frame
         {
-            if let Err(err) = self.client_event_sender.send(ClientEventFrom::<Channel>::SendFailed(aborted_send))
+            if let Err(_) = self.send_frame(connect_msg_frame.clone())
             {
-                tracing::debug!(?err, "failed to forward client event to client");
-                return Err(Box::new(ClientError::SendError));
+                tracing::warn!("failed to send connect message");
             }
         }
 
-        // mark the client as connected
-        // - We do this within the pending requests lock in order to synchronize with the client API.
-        // - We place this between the last drained request and the Connected report in order to synchronize with
-        //   the event stream. All request failures occur between disconnected and connected client reports except
-        //   when the client is dying.
-        self.client_connected_signal.store(true, Ordering::Release);
+        // start pinging the server to detect missed pongs
+        self.start_heartbeat();
 
-        // forward connection event to client owner
-        if let Err(err) = self.client_event_sender.send(ClientEventFrom::<Channel>::Report(ClientReport::Connected))
+        // wait for the server to confirm the session is ready
+        if self.config.connection_ready_barrier
         {
-            tracing::debug!(?err, "failed to forward connection event to client");
-            return Err(Box::new(ClientError::SendError));
+            self.awaiting_ready = true;
+            return Ok(());
         }
 
-        Ok(())
+        self.finish_connecting()
     }
 
     /// Respond to the client failing a connection attempt.
@@ -210,6 +478,9 @@ impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
         _error: ezsockets::WSError
     ) -> Result<ezsockets::client::ClientCloseMode, ezsockets::Error>
     {
+        let span = self.span.clone();
+        let _entered = span.enter();
+
         // lock the pending requests cache
         let Ok(mut pending_requests) = self.pending_requests.lock()
         else { return Ok(ezsockets::client::ClientCloseMode::Close); };
@@ -217,6 +488,33 @@ impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
         // clean up pending requests
         Self::clean_pending_requests(&mut pending_requests, &self.client_event_sender);
 
+        // stop reconnecting if the reconnect duration has elapsed
+        if let (Some(reconnecting_since), Some(max_reconnect_duration)) =
+            (self.reconnecting_since, self.config.max_reconnect_duration)
+        {
+            if reconnecting_since.elapsed() >= max_reconnect_duration
+            {
+                tracing::info!("max reconnect duration elapsed, closing client...");
+                record_death_reason(&self.death_reason, DeathReason::ReconnectTimedOut);
+                return Ok(ezsockets::client::ClientCloseMode::Close);
+            }
+        }
+
+        // stop reconnecting if the connection attempts are exhausted
+        // - the client backend enforces the same limits, but we close here so the cause of death is recorded
+        self.failed_connect_attempts += 1;
+        let max_attempts = match self.has_connected
+        {
+            true  => self.config.max_reconnect_attempts,
+            false => self.config.max_initial_connect_attempts,
+        };
+        if self.failed_connect_attempts >= max_attempts
+        {
+            tracing::info!(max_attempts, "connection attempts exhausted, closing client...");
+            record_death_reason(&self.death_reason, DeathReason::ReconnectAttemptsExhausted);
+            return Ok(ezsockets::client::ClientCloseMode::Close);
+        }
+
         //todo: don't try to reconnect if auth token expired
         Ok(ezsockets::client::ClientCloseMode::Reconnect)
     }
@@ -224,7 +522,16 @@ impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
     /// Respond to the client being disconnected.
     async fn on_disconnect(&mut self) -> Result<ezsockets::client::ClientCloseMode, ezsockets::Error>
     {
+        let span = self.span.clone();
+        let _entered = span.enter();
+
         tracing::info!("disconnected");
+        self.observer.on_disconnect(self.client_id);
+        let reason = match std::mem::take(&mut self.missed_pongs)
+        {
+            true  => DisconnectReason::TimedOut,
+            false => self.recv_activity.lost_connection_reason(self.config.keepalive_timeout),
+        };
 
         // lock the pending requests cache
         let Ok(mut pending_requests) = self.pending_requests.lock()
@@ -233,9 +540,13 @@ impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
         // mark the client as disconnected
         // - We do this within the pending requests lock in order to synchronize with the client API.
         self.client_connected_signal.store(false, Ordering::Release);
+        self.awaiting_ready = false;
+        self.stop_heartbeat();
+        self.discard_pending_pings();
+        self.send_status.notify();  //messages that were still sending will fail
 
         // forward event to client owner
-        if let Err(err) = self.client_event_sender.send(ClientEventFrom::<Channel>::Report(ClientReport::Disconnected))
+        if let Err(err) = self.client_event_sender.send(ClientEventFrom::<Channel>::Report(ClientReport::Disconnected(reason.clone())))
         {
             tracing::debug!(?err, "failed to forward connection event to client");
             return Err(Box::new(ClientError::SendError));
@@ -245,12 +556,17 @@ impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
         // - do this after sending the client report so request failures appear between client disconnected and client
         //   connected reports (except when the client is dying)
         Self::clean_pending_requests(&mut pending_requests, &self.client_event_sender);
+        drop(pending_requests);
 
         // choose response
-        match self.config.reconnect_on_disconnect
+        match self.config.reconnect_on_disconnect && self.auto_reconnect.load(Ordering::Acquire)
         {
-            true  => return Ok(ezsockets::client::ClientCloseMode::Reconnect),
-            false => return Ok(ezsockets::client::ClientCloseMode::Close),
+            true  => return Ok(self.start_reconnecting()),
+            false =>
+            {
+                record_death_reason(&self.death_reason, DeathReason::Disconnected(reason));
+                return Ok(ezsockets::client::ClientCloseMode::Close);
+            }
         }
     }
 
@@ -260,7 +576,12 @@ impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
         close_frame: Option<ezsockets::CloseFrame>
     ) -> Result<ezsockets::client::ClientCloseMode, ezsockets::Error>
     {
+        let span = self.span.clone();
+        let _entered = span.enter();
+
         tracing::info!(?close_frame, "closed by server");
+        let death_reason = DeathReason::ClosedByServer(close_frame.clone());
+        self.observer.on_disconnect(self.client_id);
 
         // lock the pending requests cache
         let Ok(mut pending_requests) = self.pending_requests.lock()
@@ -269,6 +590,10 @@ impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
         // mark the client as disconnected
         // - We do this within the pending requests lock in order to synchronize with the client API.
         self.client_connected_signal.store(false, Ordering::Release);
+        self.awaiting_ready = false;
+        self.stop_heartbeat();
+        self.discard_pending_pings();
+        self.send_status.notify();  //messages that were still sending will fail
 
         // forward event to client owner
         if let Err(err) = self.client_event_sender.send(
@@ -283,18 +608,186 @@ impl<Channel: ChannelPack> ezsockets::ClientExt for ClientHandler<Channel>
         // - do this after sending the client report so request failures appear between client disconnected and client
         //   connected reports (except when the client is dying)
         Self::clean_pending_requests(&mut pending_requests, &self.client_event_sender);
+        drop(pending_requests);
 
         // choose response
-        match self.config.reconnect_on_server_close
+        // - don't reconnect if the server rejected the client's connection request, since retrying can't succeed
+        let rejected = is_connection_rejection(&death_reason);
+        match !rejected && self.config.reconnect_on_server_close && self.auto_reconnect.load(Ordering::Acquire)
         {
-            true  => return Ok(ezsockets::client::ClientCloseMode::Reconnect),
-            false => return Ok(ezsockets::client::ClientCloseMode::Close),
+            true  => return Ok(self.start_reconnecting()),
+            false =>
+            {
+                record_death_reason(&self.death_reason, death_reason);
+                return Ok(ezsockets::client::ClientCloseMode::Close);
+            }
         }
     }
 }
 
 impl<Channel: ChannelPack> ClientHandler<Channel>
 {
+    /// Emit a report that sequenced server messages were lost.
+    fn report_gap(&self, missing_from: u64, missing_to: u64) -> Result<(), ezsockets::Error>
+    {
+        tracing::debug!(missing_from, missing_to, "server msgs were lost");
+        let report = ClientReport::GapDetected{ missing_from, missing_to };
+        if let Err(err) = self.client_event_sender.send(ClientEventFrom::<Channel>::Report(report))
+        {
+            tracing::debug!(?err, "failed to forward gap report to client");
+            return Err(Box::new(ClientError::SendError));
+        }
+
+        Ok(())
+    }
+
+    /// Send a serialized message to the server.
+    fn send_frame(&self, bytes: Vec<u8>) -> Result<MessageSignal, ()>
+    {
+        let size = bytes.len();
+        let signal = send_client_frame(&self.client, self.config.text_frames, bytes)?;
+        self.observer.on_msg_out(self.client_id, size);
+        Ok(signal)
+    }
+
+    /// Start reconnecting after the connection was lost.
+    fn start_reconnecting(&mut self) -> ezsockets::client::ClientCloseMode
+    {
+        self.reconnecting_since = Some(Instant::now());
+        ezsockets::client::ClientCloseMode::Reconnect
+    }
+
+    /// Start sending heartbeat pings for the current connection (if missed pongs are detected).
+    fn start_heartbeat(&mut self)
+    {
+        let Some(max_missed_pongs) = self.config.max_missed_pongs else { return; };
+        self.pong_tracker = Some(PongTracker::new(max_missed_pongs));
+        self.missed_pongs = false;
+
+        // the heartbeat stops once its generation is replaced
+        let generation = self.heartbeat_generation.fetch_add(1, Ordering::AcqRel) + 1;
+        let heartbeat_generation = self.heartbeat_generation.clone();
+        let client = self.client.clone();
+        self.send_status.spawn_repeating(
+                self.config.heartbeat_interval,
+                move ||
+                {
+                    if heartbeat_generation.load(Ordering::Acquire) != generation { return false; }
+                    // this fails if the client is dead
+                    client.call(ClientHandlerCall::Heartbeat(generation)).is_ok()
+                }
+            );
+    }
+
+    /// Stop sending heartbeat pings.
+    fn stop_heartbeat(&self)
+    {
+        self.heartbeat_generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Send a heartbeat ping to the server, or abort the connection if the server missed too many pongs.
+    fn handle_heartbeat(&mut self)
+    {
+        let Some(pong_tracker) = &mut self.pong_tracker else { return; };
+        if !pong_tracker.ping_due()
+        {
+            tracing::info!("server missed too many pongs, dropping connection...");
+            self.missed_pongs = true;
+            self.stop_heartbeat();
+            self.connection_aborter.abort();
+            return;
+        }
+
+        // use an untracked ping id so the pong can't resolve an on-demand ping
+        let ping_id = match self.pending_pings.lock()
+        {
+            Ok(mut pending_pings) => pending_pings.reserve_untracked_id(),
+            Err(_) => { tracing::error!("ping tracker poisoned"); return; }
+        };
+        if self.send_control_msg(ServerMetaEventFrom::<Channel>::Ping(ping_id)).is_err()
+        {
+            tracing::warn!("failed to send heartbeat ping");
+        }
+    }
+
+    /// Serialize a control message and send it to the server.
+    fn send_control_msg(&self, control_msg: ServerMetaEventFrom<Channel>) -> Result<MessageSignal, ()>
+    {
+        let Ok(mut ser_msg) = bincode::DefaultOptions::new().serialize(&control_msg)
+        else { tracing::error!("failed serializing client control message"); return Err(()); };
+        apply_bytes_hook(&self.config.on_send, &mut ser_msg);
+
+        self.send_frame(ser_msg)
+    }
+
+    /// Discard pending pings, since their pongs won't arrive after a disconnect.
+    fn discard_pending_pings(&self)
+    {
+        match self.pending_pings.lock()
+        {
+            Ok(mut pending_pings) => pending_pings.clear(),
+            Err(_) => tracing::error!("ping tracker poisoned"),
+        }
+    }
+
+    /// Notify the client that the client handler connected or died.
+    /// - Notifications are coalesced, since the client re-checks the connection status when woken.
+    fn notify_connection_update(&self)
+    {
+        let _ = self.connection_notifier.try_send(());
+    }
+
+    /// Mark the client as connected and emit a connection report.
+    fn finish_connecting(&mut self) -> Result<(), ezsockets::Error>
+    {
+        // lock the pending requests cache
+        let Ok(mut pending_requests) = self.pending_requests.lock() else { return Ok(()); };
+
+        // clean up existing requests
+        // - do this before sending connection event so the event stream is synchronized
+        let aborted_sends = Self::final_request_cleanup(&mut pending_requests, &self.client_event_sender);
+
+        // convert aborted sends to `SendFailed`
+        // - `aborted_sends` may not be empty if the socket for the previous connection did not fully shut down yet.
+        //   It is possible for messages to linger in the socket's internal sink even after the socket has
+        //   been dropped. Those messages are guaranteed to fail because on_connect() synchronizes with the
+        //   server's old session completely shutting down, so we can treat them as such here.
+        for aborted_send in aborted_sends
+        {
+            if let Err(err) = self.client_event_sender.send(ClientEventFrom::<Channel>::SendFailed(aborted_send))
+            {
+                tracing::debug!(?err, "failed to forward client event to client");
+                return Err(Box::new(ClientError::SendError));
+            }
+        }
+
+        // mark the client as connected
+        // - We do this within the pending requests lock in order to synchronize with the client API.
+        // - We place this between the last drained request and the Connected report in order to synchronize with
+        //   the event stream. All request failures occur between disconnected and connected client reports except
+        //   when the client is dying.
+        self.client_connected_signal.store(true, Ordering::Release);
+        self.reconnecting_since = None;
+        self.observer.on_connect(self.client_id);
+        self.notify_connection_update();
+
+        // forward connection event to client owner
+        let report = match self.has_connected
+        {
+            false => ClientReport::Connected,
+            true  => ClientReport::Reconnected,
+        };
+        self.has_connected = true;
+
+        if let Err(err) = self.client_event_sender.send(ClientEventFrom::<Channel>::Report(report))
+        {
+            tracing::debug!(?err, "failed to forward connection event to client");
+            return Err(Box::new(ClientError::SendError));
+        }
+
+        Ok(())
+    }
+
     fn clean_pending_requests(
         pending_requests    : &mut PendingRequestTracker,
         client_event_sender : &crossbeam::channel::Sender<ClientEventFrom<Channel>>
@@ -386,6 +879,9 @@ impl<Channel: ChannelPack> Drop for ClientHandler<Channel>
             tracing::debug!(?err, "failed to forward 'client is dead' report to client");
         }
 
+        // record the cause of death if no other cause was recorded
+        record_death_reason(&self.death_reason, DeathReason::InternalError);
+
         // mark the client as dead
         // - We do this within the pending requests lock but after cleaning pending requests in order to synchronize
         //   with the client API. We want to prevent the client from sending requests after this lock zone, and we also
@@ -393,6 +889,9 @@ impl<Channel: ChannelPack> Drop for ClientHandler<Channel>
         //   calls to `Client::next()` will reliably drain the client.
         self.client_connected_signal.store(false, Ordering::Release);
         self.client_closed_signal.store(true, Ordering::Release);
+        self.notify_connection_update();
+        self.send_status.notify();
+        self.discard_pending_pings();
     }
 }
 
@@ -1,15 +1,17 @@
 //local shortcuts
+use crate::*;
 
 //third-party shortcuts
 
 //standard shortcuts
 use core::fmt::Debug;
+use std::sync::Arc;
 use std::time::Duration;
 
 //-------------------------------------------------------------------------------------------------------------------
 
 /// Config for the [`Client`].
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ClientConfig
 {
     /// Try to reconnect if the client is disconnected. Defaults to `true`.
@@ -22,10 +24,91 @@ pub struct ClientConfig
     pub max_initial_connect_attempts: usize,
     /// Maximum number of reconnect attempts when reconnecting. Defaults to infinite.
     pub max_reconnect_attempts: usize,
+    /// Maximum time to spend on a single connection attempt before it fails. Defaults to `None` (no limit).
+    ///
+    /// Use this so stalled handshakes fail fast and the client moves on to its next attempt. Ignored on WASM, where the
+    /// browser controls connection attempts.
+    pub connect_timeout: Option<Duration>,
+    /// Maximum time to spend reconnecting after the connection is lost. Defaults to `None` (no limit).
+    ///
+    /// The client dies once a reconnect attempt fails after this much time has elapsed since the connection was lost,
+    /// regardless of [`ClientConfig::max_reconnect_attempts`].
+    pub max_reconnect_duration: Option<Duration>,
     /// Duration between socket heartbeat pings if the connection is inactive. Defaults to 5 seconds.
     pub heartbeat_interval: Duration,
     /// Duration after which a socket will shut down if the connection is inactive. Defaults to 10 seconds
+    ///
+    /// Heartbeat pongs count as activity. See [`ClientConfig::max_missed_pongs`] for detecting unresponsive
+    /// connections sooner.
     pub keepalive_timeout: Duration,
+    /// Max number of consecutive heartbeat pings the server may leave unanswered before the connection is dropped.
+    /// Defaults to `None` (unresponsive connections are only dropped by [`ClientConfig::keepalive_timeout`]).
+    ///
+    /// When set, the client pings the server every [`ClientConfig::heartbeat_interval`] and drops the connection
+    /// after this many pongs are missed in a row, then reconnects according to
+    /// [`ClientConfig::reconnect_on_disconnect`]. The disconnect is reported as [`DisconnectReason::TimedOut`]. This
+    /// detects half-open connections sooner than the keepalive timeout, since pings to a dead server can sit in the
+    /// socket's send buffer without failing. Heartbeat pings count against the server's rate limit.
+    pub max_missed_pongs: Option<u32>,
+    /// Send the connect message in the first binary frame after connecting instead of in the connection request URL.
+    /// Defaults to `false`.
+    ///
+    /// The connect message will be serialized with `bincode` instead of JSON, which is more compact and can represent
+    /// raw bytes. The server will not report the connection until it receives the connect message.
+    pub binary_connect_msg: bool,
+    /// Wait for the server to confirm that the client's session is registered before the client is considered
+    /// connected. Defaults to `false`.
+    ///
+    /// Without the barrier, the client becomes connected as soon as its socket connects, which may be before the
+    /// server has registered the session. With the barrier, [`ClientReport::Connected`] is emitted (and sends are
+    /// allowed) only after the server's confirmation arrives, at the cost of one round trip.
+    pub connection_ready_barrier: bool,
+    /// Send and receive messages as base64-encoded text frames instead of binary frames. Defaults to `false`.
+    ///
+    /// Use this if the client's network mishandles binary websocket frames (e.g. some corporate proxies). Messages are
+    /// about 33% larger, and count against the server's max message size after decoding.
+    pub text_frames: bool,
+    /// `User-Agent` header for the connection request. Defaults to `None` (the websocket stack's default).
+    ///
+    /// Ignored on WASM, where the browser controls request headers.
+    pub user_agent: Option<String>,
+    /// `Origin` header for the connection request. Defaults to `None` (no origin).
+    ///
+    /// Ignored on WASM, where the browser controls request headers.
+    pub origin: Option<String>,
+    /// Max number of messages that may be waiting in the outbound buffer before [`Client::try_send()`] fails.
+    /// Defaults to 1000.
+    ///
+    /// Other send methods ignore this limit.
+    pub max_pending_msgs: usize,
+    /// Chunk size for streams sent with [`Client::send_stream()`] (bytes). Defaults to 100KB.
+    ///
+    /// This should be smaller than the server's max message size.
+    pub stream_chunk_size: u32,
+    /// Duration after which a partially-received stream is discarded if no new chunks arrive. Defaults to 10 seconds.
+    pub stream_timeout: Duration,
+    /// Identifier attached to the client's internal logs as the `log_id` field of a tracing span. Defaults to `None`.
+    ///
+    /// Use this to filter logs per-instance when running many clients in one process (e.g. in a load-test harness).
+    pub log_id: Option<String>,
+    /// Hook applied to serialized messages immediately before they are sent to the server. Defaults to `None`.
+    ///
+    /// The hook runs on the thread that calls [`Client::send()`] or [`Client::request()`], so it should be cheap and
+    /// must not block.
+    pub on_send: Option<Arc<dyn BytesHookFn>>,
+    /// Hook applied to messages immediately after they are received from the server. Defaults to `None`.
+    ///
+    /// The hook runs on the client's IO task, so it should be cheap and must not block.
+    pub on_recv: Option<Arc<dyn BytesHookFn>>,
+    /// Cipher for application-layer encryption of message payloads. Defaults to `None`.
+    ///
+    /// The server must use a matching `ServerConfig::payload_encryption`.
+    /// Use [`ClientConfig::binary_connect_msg`] to also encrypt the connect message.
+    pub payload_encryption: Option<Arc<dyn PayloadCipher>>,
+    /// Per-message compression of message payloads. Defaults to `None`.
+    ///
+    /// The server must use matching `ServerConfig::payload_compression`.
+    pub payload_compression: Option<PayloadCompression>,
 }
 
 impl Default for ClientConfig
@@ -38,8 +121,24 @@ impl Default for ClientConfig
                 reconnect_interval           : Duration::from_secs(2),
                 max_initial_connect_attempts : usize::MAX,
                 max_reconnect_attempts       : usize::MAX,
+                max_reconnect_duration       : None,
+                connect_timeout              : None,
                 heartbeat_interval           : Duration::from_secs(5),
                 keepalive_timeout            : Duration::from_secs(10),
+                max_missed_pongs             : None,
+                binary_connect_msg           : false,
+                connection_ready_barrier     : false,
+                text_frames                  : false,
+                user_agent                   : None,
+                origin                       : None,
+                max_pending_msgs             : 1_000usize,
+                stream_chunk_size            : 100_000u32,
+                stream_timeout               : Duration::from_secs(10),
+                log_id                       : None,
+                on_send                      : None,
+                on_recv                      : None,
+                payload_encryption           : None,
+                payload_compression          : None,
             }
     }
 }
@@ -30,3 +30,71 @@ impl std::fmt::Display for ClientError
 impl std::error::Error for ClientError {}
 
 //-------------------------------------------------------------------------------------------------------------------
+
+/// Errors emitted by [`Client::try_send()`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TrySendError
+{
+    /// The client's outbound buffer is full. See [`ClientConfig::max_pending_msgs`].
+    Full,
+    /// The client is not connected.
+    NotConnected,
+    /// The serialized message is larger than the server's max message size (see [`Client::server_max_msg_size()`]) or the
+    /// channel's type-specific size limit (see [`ChannelPack::MAX_CLIENT_MSG_SIZE`]).
+    MessageTooLarge{ size: usize, limit: u32 },
+    /// The message could not be sent (e.g. serialization failed or the client died).
+    Failed,
+}
+
+impl std::fmt::Display for TrySendError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let _ = write!(f, "TrySendError::");
+        match self
+        {
+            TrySendError::Full         => write!(f, "Full"),
+            TrySendError::NotConnected => write!(f, "NotConnected"),
+            TrySendError::MessageTooLarge{ size, limit } => write!(f, "MessageTooLarge({size} > {limit})"),
+            TrySendError::Failed       => write!(f, "Failed"),
+        }
+    }
+}
+impl std::error::Error for TrySendError {}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Errors emitted when connecting a client with [`ClientFactory::new_client_blocking()`].
+///
+/// The server reports authentication failures and protocol version mismatches with a close frame (see
+/// [`CloseReason`]), which is decoded into [`ConnectError::AuthFailed`] and [`ConnectError::VersionMismatch`].
+#[derive(Debug)]
+pub enum ConnectError
+{
+    /// The client died before connecting (e.g. the server rejected the connection or could not be reached).
+    ConnectionFailed,
+    /// The server rejected the client's authentication.
+    AuthFailed,
+    /// The client's protocol version does not match the server's.
+    VersionMismatch,
+    /// The client did not connect before the timeout elapsed.
+    TimedOut,
+}
+
+impl std::fmt::Display for ConnectError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let _ = write!(f, "ConnectError::");
+        match self
+        {
+            ConnectError::ConnectionFailed => write!(f, "ConnectionFailed"),
+            ConnectError::AuthFailed       => write!(f, "AuthFailed"),
+            ConnectError::VersionMismatch  => write!(f, "VersionMismatch"),
+            ConnectError::TimedOut         => write!(f, "TimedOut"),
+        }
+    }
+}
+impl std::error::Error for ConnectError {}
+
+//-------------------------------------------------------------------------------------------------------------------
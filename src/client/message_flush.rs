@@ -0,0 +1,62 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Future that resolves when a set of messages have finished sending. See [`Client::flush()`].
+///
+/// Message signals don't notify on status changes, so the client re-checks pending flushes at a short fixed interval
+/// while any flush is waiting, and immediately when the connection is lost. The future is runtime-agnostic (it works
+/// on native and WASM targets), but the client's runtime must stay alive for it to resolve.
+#[derive(Debug)]
+pub struct MessageFlush
+{
+    /// signals of messages that may still be sending
+    pending: Vec<MessageSignal>,
+    /// wakes the future when send statuses should be re-checked
+    notifier: Arc<SendStatusNotifier>,
+}
+
+impl MessageFlush
+{
+    pub(crate) fn new(pending: Vec<MessageSignal>, notifier: Arc<SendStatusNotifier>) -> Self
+    {
+        Self{ pending, notifier }
+    }
+
+    /// Check if all messages have finished sending.
+    pub fn is_done(&mut self) -> bool
+    {
+        self.pending.retain(|signal| signal.status() == MessageStatus::Sending);
+        self.pending.is_empty()
+    }
+}
+
+impl Future for MessageFlush
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()>
+    {
+        if self.is_done() { return Poll::Ready(()); }
+
+        // wait for the next send status check
+        self.notifier.register(cx.waker());
+
+        // re-check in case the messages finished sending while registering
+        if self.is_done() { return Poll::Ready(()); }
+
+        Poll::Pending
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
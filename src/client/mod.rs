@@ -1,17 +1,35 @@
 //module tree
+mod abortable_connector;
+#[cfg(feature = "bevy")]
+mod bevy_teardown;
 mod client;
 mod client_event;
 mod client_handler;
 mod config;
 mod errors;
+mod message_flush;
+mod pending_message_tracker;
 mod pending_request_tracker;
+mod ping_signal;
+mod ping_tracker;
 mod request_signal;
+#[cfg(not(target_family = "wasm"))]
+mod timeout_connector;
 
 //API exports
+pub(crate) use crate::client::abortable_connector::*;
+#[cfg(feature = "bevy")]
+pub use crate::client::bevy_teardown::*;
 pub use crate::client::client::*;
 pub use crate::client::client_event::*;
 pub(crate) use crate::client::client_handler::*;
 pub use crate::client::config::*;
 pub use crate::client::errors::*;
+pub use crate::client::message_flush::*;
+pub(crate) use crate::client::pending_message_tracker::*;
 pub(crate) use crate::client::pending_request_tracker::*;
+pub use crate::client::ping_signal::*;
+pub(crate) use crate::client::ping_tracker::*;
 pub use crate::client::request_signal::*;
+#[cfg(not(target_family = "wasm"))]
+pub(crate) use crate::client::timeout_connector::*;
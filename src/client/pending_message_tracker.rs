@@ -0,0 +1,191 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use enfync::Handle;
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::Waker;
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks messages that are waiting in the client's outbound buffer.
+/// - Messages are sent in order, so messages that finished sending are pruned from the front.
+#[derive(Debug, Default)]
+pub(crate) struct PendingMessageTracker
+{
+    /// signals of messages that may still be sending
+    pending: VecDeque<MessageSignal>,
+}
+
+impl PendingMessageTracker
+{
+    /// Add a message that was just sent.
+    pub(crate) fn add(&mut self, signal: MessageSignal)
+    {
+        self.prune();
+        self.pending.push_back(signal);
+    }
+
+    /// Get the number of messages that are still sending.
+    pub(crate) fn num_pending(&mut self) -> usize
+    {
+        self.prune();
+        self.pending.len()
+    }
+
+    /// Get the signals of messages that are still sending.
+    pub(crate) fn pending_signals(&mut self) -> Vec<MessageSignal>
+    {
+        self.prune();
+        self.pending.iter().cloned().collect()
+    }
+
+    /// Remove messages that finished sending.
+    fn prune(&mut self)
+    {
+        while let Some(signal) = self.pending.front()
+        {
+            if signal.status() == MessageStatus::Sending { break; }
+            self.pending.pop_front();
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Interval between send status checks while tasks are waiting for messages to finish sending.
+const SEND_STATUS_CHECK_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Sleep on the client's runtime.
+async fn sleep(duration: Duration)
+{
+    #[cfg(not(target_family = "wasm"))]
+    tokio::time::sleep(duration).await;
+
+    #[cfg(target_family = "wasm")]
+    let _ = wasm_timer::Delay::new(duration).await;
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wakes tasks that are waiting for outbound messages to finish sending (see [`MessageFlush`]).
+/// - Message signals don't notify on status changes, so one watcher task re-checks on behalf of all waiting tasks
+///   every [`SEND_STATUS_CHECK_INTERVAL`]. The watcher only runs while tasks are waiting.
+/// - The client handler wakes waiting tasks immediately when the connection is lost, since that fails all messages
+///   that are still sending.
+pub(crate) struct SendStatusNotifier
+{
+    /// runtime for the watcher task
+    runtime_handle: enfync::builtin::Handle,
+    /// wakers of tasks waiting for a send status change
+    wakers: Mutex<Vec<Waker>>,
+    /// indicates the watcher task is running
+    watching: AtomicBool,
+}
+
+impl SendStatusNotifier
+{
+    pub(crate) fn new(runtime_handle: enfync::builtin::Handle) -> Arc<Self>
+    {
+        Arc::new(Self{ runtime_handle, wakers: Mutex::new(Vec::default()), watching: AtomicBool::new(false) })
+    }
+
+    /// Register a task to be woken on the next send status check.
+    pub(crate) fn register(self: &Arc<Self>, waker: &Waker)
+    {
+        match self.wakers.lock()
+        {
+            Ok(mut wakers) =>
+            {
+                if !wakers.iter().any(|registered| registered.will_wake(waker)) { wakers.push(waker.clone()); }
+            }
+            Err(_) =>
+            {
+                // fall back to re-polling immediately
+                tracing::error!("send status wakers poisoned");
+                waker.wake_by_ref();
+                return;
+            }
+        }
+
+        // start the watcher if it isn't running
+        if self.watching.swap(true, Ordering::AcqRel) { return; }
+        let notifier = self.clone();
+        let _ = self.runtime_handle.spawn(async move { notifier.watch().await; });
+    }
+
+    /// Spawn a task on the client's runtime (e.g. to wait for a [`MessageFlush`] in the background).
+    pub(crate) fn spawn(&self, task: impl std::future::Future<Output = ()> + Send + 'static)
+    {
+        let _ = self.runtime_handle.spawn(task);
+    }
+
+    /// Run a callback on the client's runtime after a delay (e.g. to wake a task when it times out).
+    pub(crate) fn spawn_after(&self, delay: Duration, callback: impl FnOnce() + Send + 'static)
+    {
+        self.spawn(async move { sleep(delay).await; callback(); });
+    }
+
+    /// Run a callback on the client's runtime at a fixed interval until it returns `false` (e.g. to send heartbeats).
+    pub(crate) fn spawn_repeating(&self, interval: Duration, mut callback: impl FnMut() -> bool + Send + 'static)
+    {
+        self.spawn(async move { loop { sleep(interval).await; if !callback() { break; } } });
+    }
+
+    /// Wake all registered tasks so they re-check their messages.
+    pub(crate) fn notify(&self)
+    {
+        let wakers = match self.wakers.lock()
+        {
+            Ok(mut wakers) => std::mem::take(&mut *wakers),
+            Err(_) => { tracing::error!("send status wakers poisoned"); return; }
+        };
+
+        for waker in wakers { waker.wake(); }
+    }
+
+    /// Periodically wake registered tasks until no tasks are waiting.
+    async fn watch(self: Arc<Self>)
+    {
+        loop
+        {
+            sleep(SEND_STATUS_CHECK_INTERVAL).await;
+
+            // take the wakers that registered since the last check
+            // - stop watching if none did; the flag is cleared while holding the lock so a task registering
+            //   concurrently will start a new watcher
+            let wakers = match self.wakers.lock()
+            {
+                Ok(mut wakers) =>
+                {
+                    if wakers.is_empty() { self.watching.store(false, Ordering::Release); return; }
+                    std::mem::take(&mut *wakers)
+                }
+                Err(_) =>
+                {
+                    tracing::error!("send status wakers poisoned");
+                    self.watching.store(false, Ordering::Release);
+                    return;
+                }
+            };
+
+            for waker in wakers { waker.wake(); }
+        }
+    }
+}
+
+impl Debug for SendStatusNotifier
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "SendStatusNotifier [watching: {}]", self.watching.load(Ordering::Acquire))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
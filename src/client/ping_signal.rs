@@ -0,0 +1,97 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+
+#[cfg(target_family = "wasm")]
+use wasm_timer::Instant;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Future that resolves with the round-trip time of a ping. See [`Client::ping()`].
+///
+/// Resolves to `None` if the ping could not be sent, if the client disconnects before the pong arrives, or if the
+/// pong doesn't arrive before the timeout.
+///
+/// The future is woken when the pong arrives, when the client disconnects, and when the timeout elapses, so it works
+/// on native and WASM targets without busy-polling. The client's runtime must stay alive for the timeout to fire.
+#[derive(Debug)]
+pub struct PingSignal
+{
+    /// the ping's id (`None` if the ping failed to send)
+    id: Option<u64>,
+    /// when the ping was sent
+    sent_at: Instant,
+    /// max time to wait for the pong
+    timeout: Duration,
+    /// tracker shared with the client handler
+    tracker: Arc<Mutex<PingTracker>>,
+}
+
+impl PingSignal
+{
+    pub(crate) fn new(id: u64, timeout: Duration, tracker: Arc<Mutex<PingTracker>>) -> Self
+    {
+        Self{ id: Some(id), sent_at: Instant::now(), timeout, tracker }
+    }
+
+    pub(crate) fn failed(tracker: Arc<Mutex<PingTracker>>) -> Self
+    {
+        Self{ id: None, sent_at: Instant::now(), timeout: Duration::default(), tracker }
+    }
+}
+
+impl Future for PingSignal
+{
+    type Output = Option<Duration>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Duration>>
+    {
+        let Some(id) = self.id else { return Poll::Ready(None); };
+        let Ok(mut tracker) = self.tracker.lock()
+        else { tracing::error!("ping tracker poisoned"); return Poll::Ready(None); };
+
+        let result = match tracker.status(id)
+        {
+            // the ping was discarded (e.g. the client disconnected)
+            None => None,
+            Some(Some(received_at)) => Some(received_at.duration_since(self.sent_at)),
+            Some(None) if self.sent_at.elapsed() >= self.timeout => None,
+            Some(None) =>
+            {
+                // wait for the pong, a disconnect, or the timeout
+                tracker.register_waker(id, cx.waker());
+                return Poll::Pending;
+            }
+        };
+
+        tracker.remove(id);
+        drop(tracker);
+        self.id = None;
+
+        Poll::Ready(result)
+    }
+}
+
+impl Drop for PingSignal
+{
+    fn drop(&mut self)
+    {
+        let Some(id) = self.id else { return; };
+        let Ok(mut tracker) = self.tracker.lock() else { return; };
+        tracker.remove(id);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,113 @@
+//local shortcuts
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::collections::HashMap;
+use std::task::Waker;
+
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+
+#[cfg(target_family = "wasm")]
+use wasm_timer::Instant;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// An on-demand ping waiting for its pong.
+#[derive(Debug, Default)]
+struct PendingPing
+{
+    /// when the pong was received (`None` until it arrives)
+    pong: Option<Instant>,
+    /// waker of the task waiting for the pong
+    waker: Option<Waker>,
+}
+
+impl PendingPing
+{
+    fn wake(&mut self)
+    {
+        if let Some(waker) = self.waker.take() { waker.wake(); }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks on-demand pings in order to correlate them with the server's pongs.
+/// - Tasks waiting on a ping are woken when its pong arrives, when it is discarded, and when it times out.
+#[derive(Debug, Default)]
+pub(crate) struct PingTracker
+{
+    /// counter for pings
+    ping_counter: u64,
+    /// pending pings
+    pending_pings: HashMap<u64, PendingPing>,
+}
+
+impl PingTracker
+{
+    /// Reserve a ping id.
+    pub(crate) fn reserve_id(&mut self) -> u64
+    {
+        let id = self.ping_counter;
+        self.ping_counter += 1;
+        self.pending_pings.insert(id, PendingPing::default());
+        id
+    }
+
+    /// Reserve a ping id without tracking the ping (e.g. for heartbeat pings).
+    pub(crate) fn reserve_untracked_id(&mut self) -> u64
+    {
+        let id = self.ping_counter;
+        self.ping_counter += 1;
+        id
+    }
+
+    /// Record the pong for a pending ping.
+    pub(crate) fn set_pong(&mut self, id: u64) -> bool
+    {
+        let Some(pending_ping) = self.pending_pings.get_mut(&id) else { return false; };
+        pending_ping.pong = Some(Instant::now());
+        pending_ping.wake();
+
+        true
+    }
+
+    /// Get the status of a ping (`None` if the ping is unknown).
+    pub(crate) fn status(&self, id: u64) -> Option<Option<Instant>>
+    {
+        self.pending_pings.get(&id).map(|pending_ping| pending_ping.pong)
+    }
+
+    /// Register the waker of a task waiting for a ping's pong.
+    /// - Returns `false` if the ping is unknown.
+    pub(crate) fn register_waker(&mut self, id: u64, waker: &Waker) -> bool
+    {
+        let Some(pending_ping) = self.pending_pings.get_mut(&id) else { return false; };
+        pending_ping.waker = Some(waker.clone());
+
+        true
+    }
+
+    /// Wake the task waiting for a ping (e.g. when the ping times out).
+    pub(crate) fn wake(&mut self, id: u64)
+    {
+        if let Some(pending_ping) = self.pending_pings.get_mut(&id) { pending_ping.wake(); }
+    }
+
+    /// Remove a ping.
+    pub(crate) fn remove(&mut self, id: u64)
+    {
+        self.pending_pings.remove(&id);
+    }
+
+    /// Remove all pings (e.g. when the client disconnects, since their pongs will never arrive).
+    pub(crate) fn clear(&mut self)
+    {
+        for (_, mut pending_ping) in self.pending_pings.drain() { pending_ping.wake(); }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,64 @@
+//local shortcuts
+
+//third-party shortcuts
+use ezsockets::client::ClientConnector;
+
+//standard shortcuts
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+type TokioConnector = ezsockets::ClientConnectorTokio;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Client connector that fails connection attempts that don't finish before a timeout.
+/// - Wraps the tokio client connector.
+pub(crate) struct TimeoutClientConnector
+{
+    /// the wrapped connector
+    inner: TokioConnector,
+    /// max duration of a connection attempt (`None` for no limit)
+    timeout: Option<Duration>,
+}
+
+impl TimeoutClientConnector
+{
+    pub(crate) fn new(inner: TokioConnector, timeout: Option<Duration>) -> Self
+    {
+        Self{ inner, timeout }
+    }
+}
+
+#[async_trait::async_trait]
+impl ClientConnector for TimeoutClientConnector
+{
+    type Handle  = <TokioConnector as ClientConnector>::Handle;
+    type Message = <TokioConnector as ClientConnector>::Message;
+    type WSError = <TokioConnector as ClientConnector>::WSError;
+    type Socket  = <TokioConnector as ClientConnector>::Socket;
+
+    fn handle(&self) -> Self::Handle
+    {
+        self.inner.handle()
+    }
+
+    async fn connect(&self, client_config: &ezsockets::ClientConfig) -> Result<Self::Socket, Self::WSError>
+    {
+        let Some(timeout) = self.timeout else { return self.inner.connect(client_config).await; };
+
+        match tokio::time::timeout(timeout, self.inner.connect(client_config)).await
+        {
+            Ok(result) => result,
+            Err(_) =>
+            {
+                tracing::debug!(?timeout, "connection attempt timed out");
+                Err(Self::WSError::from(
+                        std::io::Error::new(std::io::ErrorKind::TimedOut, "connection attempt timed out")
+                    ))
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
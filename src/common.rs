@@ -2,16 +2,22 @@
 
 //third-party shortcuts
 use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
 
 //standard shortcuts
 use core::fmt::Debug;
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 //-------------------------------------------------------------------------------------------------------------------
 
 /// Id for client sessions on the server. Equals the client id.
 pub type SessionID = u128;
 
+/// Max length in bytes of a close frame's reason (the websocket protocol limits control frame payloads to 125 bytes,
+/// two of which hold the close code).
+pub const MAX_CLOSE_REASON_LEN: usize = 123;
+
 //-------------------------------------------------------------------------------------------------------------------
 
 /// Represents the message types that can be sent between a client and server.
@@ -32,6 +38,343 @@ pub trait ChannelPack: Clone + Debug + 'static
     type ClientMsg: Clone + Debug + Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static;
     /// A client request. The server may respond to it, acknowledge it, or reject it.
     type ClientRequest: Clone + Debug + Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static;
+
+    /// Max serialized size of a client message (bytes). Defaults to `None`.
+    ///
+    /// Servers close sessions that send larger messages, and clients refuse to send them. This is checked in addition
+    /// to the server's max message size. Messages sent with [`Client::send_stream()`] are not checked.
+    const MAX_CLIENT_MSG_SIZE: Option<u32> = None;
+    /// Max serialized size of a client request (bytes). Defaults to `None`.
+    ///
+    /// Servers close sessions that send larger requests, and clients refuse to send them. This is checked in addition
+    /// to the server's max message size.
+    const MAX_CLIENT_REQUEST_SIZE: Option<u32> = None;
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wrapper trait for `Fn(&mut Vec<u8>)`.
+///
+/// Used to observe or modify serialized messages before they are handed to the socket and after they are read from
+/// it (e.g. for traffic capture, custom encryption, or fault injection).
+///
+/// Where hooks run:
+/// - Server `on_send` hooks run on the server's connection handler task, and `on_recv` hooks run on session IO tasks.
+/// - Client `on_recv` hooks run on the client's IO task.
+/// - Client `on_send` hooks run on the thread that calls the client's send methods (e.g. [`Client::send()`]), not on
+///   the IO task. Outgoing messages are size-checked after the hook and their [`MessageSignal`] is returned
+///   immediately, so the hooked bytes must be available before the message is handed to the IO task.
+///
+/// Hooks are invoked for every message, so they should be cheap and must not block.
+pub trait BytesHookFn: Fn(&mut Vec<u8>) + Send + Sync + 'static {}
+impl<F> BytesHookFn for F where F: Fn(&mut Vec<u8>) + Send + Sync + 'static {}
+
+impl Debug for dyn BytesHookFn
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "BytesHookFn") }
+}
+
+/// Apply an optional bytes hook.
+pub(crate) fn apply_bytes_hook(hook: &Option<Arc<dyn BytesHookFn>>, bytes: &mut Vec<u8>)
+{
+    let Some(hook) = hook else { return; };
+    (hook)(bytes);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Application-layer cipher for message payloads.
+///
+/// Ciphers encrypt serialized messages after the `on_send` hook and decrypt them before the `on_recv` hook, so
+/// payloads stay confidential even if TLS is terminated upstream (e.g. at a proxy). Keys must be agreed on out of
+/// band (e.g. a pre-shared key, or a key derived from the client's auth credentials).
+///
+/// Clients and servers must use matching ciphers. Connect messages are only encrypted when sent with
+/// `ClientConfig::binary_connect_msg`, and JSON debug frames are never encrypted.
+pub trait PayloadCipher: Send + Sync + 'static
+{
+    /// Encrypt a serialized message in place.
+    fn encrypt(&self, bytes: &mut Vec<u8>);
+    /// Decrypt a received message in place. Returns `Err` if the message can't be decrypted.
+    fn decrypt(&self, bytes: &mut Vec<u8>) -> Result<(), ()>;
+}
+
+impl Debug for dyn PayloadCipher
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "PayloadCipher") }
+}
+
+/// Combine an optional `on_send` hook with an optional payload cipher, so the cipher encrypts the hook's output.
+pub(crate) fn with_payload_encryption(
+    on_send : Option<Arc<dyn BytesHookFn>>,
+    cipher  : &Option<Arc<dyn PayloadCipher>>,
+) -> Option<Arc<dyn BytesHookFn>>
+{
+    let Some(cipher) = cipher.clone() else { return on_send; };
+
+    Some(Arc::new(
+            move |bytes: &mut Vec<u8>|
+            {
+                apply_bytes_hook(&on_send, bytes);
+                cipher.encrypt(bytes);
+            }
+        ))
+}
+
+/// Decrypt a received payload with an optional cipher.
+pub(crate) fn decrypt_payload(cipher: &Option<Arc<dyn PayloadCipher>>, bytes: &mut Vec<u8>) -> Result<(), ()>
+{
+    let Some(cipher) = cipher else { return Ok(()); };
+    cipher.decrypt(bytes)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Compression codec for message payloads. See [`PayloadCompression`].
+pub trait PayloadCodec: Send + Sync + 'static
+{
+    /// Compress a serialized message.
+    fn compress(&self, bytes: &[u8]) -> Vec<u8>;
+    /// Decompress a received message. Returns `Err` if the message can't be decompressed.
+    ///
+    /// Implementations must return `Err` as soon as the decompressed output would exceed `max_size` bytes, without
+    /// decompressing the rest of the message. Otherwise a small compressed message could expand into an arbitrarily
+    /// large allocation before the receiver's max message size is checked. Servers pass
+    /// [`ServerConfig::max_msg_size`], and clients pass `usize::MAX` since they don't limit server messages.
+    fn decompress(&self, bytes: &[u8], max_size: usize) -> Result<Vec<u8>, ()>;
+}
+
+impl Debug for dyn PayloadCodec
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "PayloadCodec") }
+}
+
+/// Per-message compression of message payloads.
+///
+/// Messages are compressed after the `on_send` hook if their serialized size is at least `threshold` bytes, and a
+/// one-byte flag is added to every message so the receiver knows whether to decompress it. Small messages are sent
+/// uncompressed, since compression overhead dominates for tiny payloads.
+///
+/// Clients and servers must use matching codecs. Compression is applied before payload encryption (see
+/// [`PayloadCipher`]), and JSON debug frames are never compressed.
+#[derive(Debug, Clone)]
+pub struct PayloadCompression
+{
+    /// The compression codec.
+    pub codec: Arc<dyn PayloadCodec>,
+    /// Messages at least this large (bytes) are compressed.
+    pub threshold: usize,
+}
+
+/// Flag appended to uncompressed payloads.
+const UNCOMPRESSED_FLAG: u8 = 0u8;
+/// Flag appended to compressed payloads.
+const COMPRESSED_FLAG: u8 = 1u8;
+
+/// Combine an optional `on_send` hook with optional payload compression, so the hook's output is compressed.
+pub(crate) fn with_payload_compression(
+    on_send     : Option<Arc<dyn BytesHookFn>>,
+    compression : &Option<PayloadCompression>,
+) -> Option<Arc<dyn BytesHookFn>>
+{
+    let Some(compression) = compression.clone() else { return on_send; };
+
+    Some(Arc::new(
+            move |bytes: &mut Vec<u8>|
+            {
+                apply_bytes_hook(&on_send, bytes);
+                if bytes.len() < compression.threshold { bytes.push(UNCOMPRESSED_FLAG); return; }
+                *bytes = compression.codec.compress(bytes);
+                bytes.push(COMPRESSED_FLAG);
+            }
+        ))
+}
+
+/// Decompress a received payload with optional payload compression.
+/// - Fails if the decompressed payload would be larger than `max_size` (see [`PayloadCodec::decompress()`]).
+pub(crate) fn decompress_payload(
+    compression : &Option<PayloadCompression>,
+    bytes       : &mut Vec<u8>,
+    max_size    : usize,
+) -> Result<(), ()>
+{
+    let Some(compression) = compression else { return Ok(()); };
+
+    match bytes.pop()
+    {
+        Some(UNCOMPRESSED_FLAG) => Ok(()),
+        Some(COMPRESSED_FLAG) => { *bytes = compression.codec.decompress(bytes, max_size)?; Ok(()) }
+        _ => Err(()),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reason for a server closing a session.
+///
+/// Close reasons are encoded as application close codes (4000-4999) in the session's close frame, so they can be
+/// interpreted by any websocket client:
+/// - `4000`: [`CloseReason::Kicked`]
+/// - `4001`: [`CloseReason::RateLimited`]
+/// - `4002`: [`CloseReason::MsgSizeViolation`]
+/// - `4003`: [`CloseReason::ProtocolViolation`]
+/// - `4004`: [`CloseReason::ServerError`]
+/// - `4005`: [`CloseReason::Draining`]
+/// - `4006`: [`CloseReason::Banned`]
+/// - `4007`: [`CloseReason::AuthFailed`]
+/// - `4008`: [`CloseReason::VersionMismatch`]
+/// - `4009`: [`CloseReason::Unresponsive`]
+///
+/// Clients can decode the close frame in [`ClientReport::ClosedByServer`] with [`CloseReason::from_close_frame()`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CloseReason
+{
+    /// The server's owner closed the session (e.g. with [`Server::close_session()`]).
+    Kicked,
+    /// The session exceeded the server's rate limit.
+    RateLimited,
+    /// The session sent a message that was too large.
+    MsgSizeViolation,
+    /// The session violated the client/server protocol.
+    ProtocolViolation,
+    /// The server encountered an internal error.
+    ServerError,
+    /// The server closed the session at the end of a lame-duck grace period (see [`Server::begin_lame_duck()`]), or
+    /// because it shut down (see [`ServerConfig::dedicated_handler_thread`]).
+    Draining,
+    /// The server banned the session's client (see [`Server::ban_client()`]).
+    Banned,
+    /// The server rejected the client's connection request because its authentication was invalid.
+    AuthFailed,
+    /// The server rejected the client's connection request because the client uses a different protocol version.
+    VersionMismatch,
+    /// The session's client missed too many heartbeat pongs (see [`ServerConfig::max_missed_pongs`]).
+    Unresponsive,
+}
+
+impl CloseReason
+{
+    /// Get the close code of this reason.
+    pub fn code(&self) -> u16
+    {
+        match self
+        {
+            CloseReason::Kicked            => 4000,
+            CloseReason::RateLimited       => 4001,
+            CloseReason::MsgSizeViolation  => 4002,
+            CloseReason::ProtocolViolation => 4003,
+            CloseReason::ServerError       => 4004,
+            CloseReason::Draining          => 4005,
+            CloseReason::Banned            => 4006,
+            CloseReason::AuthFailed        => 4007,
+            CloseReason::VersionMismatch   => 4008,
+            CloseReason::Unresponsive      => 4009,
+        }
+    }
+
+    /// Get the close reason of a close code.
+    pub fn from_code(code: u16) -> Option<CloseReason>
+    {
+        match code
+        {
+            4000 => Some(CloseReason::Kicked),
+            4001 => Some(CloseReason::RateLimited),
+            4002 => Some(CloseReason::MsgSizeViolation),
+            4003 => Some(CloseReason::ProtocolViolation),
+            4004 => Some(CloseReason::ServerError),
+            4005 => Some(CloseReason::Draining),
+            4006 => Some(CloseReason::Banned),
+            4007 => Some(CloseReason::AuthFailed),
+            4008 => Some(CloseReason::VersionMismatch),
+            4009 => Some(CloseReason::Unresponsive),
+            _    => None,
+        }
+    }
+
+    /// Get the close reason of a close frame.
+    ///
+    /// Returns `None` if the close frame's code is not a known close reason.
+    pub fn from_close_frame(close_frame: &ezsockets::CloseFrame) -> Option<CloseReason>
+    {
+        Self::from_code(u16::from(close_frame.code.clone()))
+    }
+
+    /// Make a close frame for this reason.
+    pub fn close_frame(&self, reason: &str) -> ezsockets::CloseFrame
+    {
+        ezsockets::CloseFrame{
+            code   : ezsockets::CloseCode::from(self.code()),
+            reason : String::from(reason),
+        }
+    }
+
+    /// Make a close frame for this reason with a structured payload, serialized as JSON into the frame's reason.
+    ///
+    /// Returns `Err` if the payload fails to serialize or if the serialized payload is longer than
+    /// [`MAX_CLOSE_REASON_LEN`]. Clients can decode the payload with [`CloseReason::payload_from_close_frame()`].
+    pub fn close_frame_with_payload<P: Serialize>(&self, payload: &P) -> Result<ezsockets::CloseFrame, ()>
+    {
+        let Ok(reason) = serde_json::to_string(payload)
+        else { tracing::error!("failed to serialize close payload"); return Err(()); };
+        if reason.len() > MAX_CLOSE_REASON_LEN
+        {
+            tracing::error!(len = reason.len(), "close payload is too long for a close frame");
+            return Err(());
+        }
+
+        Ok(self.close_frame(&reason))
+    }
+
+    /// Decode a structured payload from a close frame made with [`CloseReason::close_frame_with_payload()`].
+    ///
+    /// Returns `None` if the close frame's reason is not a valid payload.
+    pub fn payload_from_close_frame<P: DeserializeOwned>(close_frame: &ezsockets::CloseFrame) -> Option<P>
+    {
+        serde_json::from_str(&close_frame.reason).ok()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The reason a connection between a client and server ended.
+///
+/// Reported by servers in [`ServerReport::Disconnected`] and by clients in [`ClientReport::Disconnected`].
+#[derive(Debug, Clone)]
+pub enum DisconnectReason
+{
+    /// The client closed the connection.
+    ///
+    /// Contains the close frame sent by the client, if there was one. Only reported by servers.
+    ClientClosed(Option<ezsockets::CloseFrame>),
+    /// The server closed the session.
+    ///
+    /// This includes [`Server::close_session()`] and sessions closed for violating server policies (e.g. the rate
+    /// limit). Only reported by servers (clients report [`ClientReport::ClosedByServer`] instead).
+    ServerClosed,
+    /// The connection ended without a close frame after the other end was silent for at least the keepalive timeout.
+    ///
+    /// Native heartbeat pongs are handled by the transport and aren't observed, so a connection to a native peer that
+    /// is cut abruptly after that peer sent nothing for the keepalive timeout is also reported as timed out. Clients
+    /// also report connections dropped after the server missed too many pongs (see `ClientConfig::max_missed_pongs`).
+    TimedOut,
+    /// The connection ended without a close frame before the keepalive timeout could elapse (e.g. the other end's
+    /// process was killed or its network dropped).
+    ConnectionLost,
+    /// The session failed with an error. Only reported by servers.
+    ///
+    /// Websocket protocol violations by the peer end the connection without surfacing an error in `ezsockets`, so
+    /// they are reported as [`DisconnectReason::ConnectionLost`].
+    TransportError,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Make a tracing span for a server or client instance.
+/// - Returns a disabled span if there is no log id.
+pub(crate) fn log_span(log_id: &Option<String>) -> tracing::Span
+{
+    let Some(log_id) = log_id else { return tracing::Span::none(); };
+    tracing::info_span!("simplenet", log_id = log_id.as_str())
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -2,6 +2,9 @@
 use crate::*;
 
 //third-party shortcuts
+#[cfg(any(feature = "client", feature = "server"))]
+use base64::Engine;
+use bincode::Options;
 use serde::{Serialize, Deserialize};
 
 //standard shortcuts
@@ -13,6 +16,58 @@ pub(crate) const VERSION_MSG_KEY : &'static str = "v";
 pub(crate) const TYPE_MSG_KEY    : &'static str = "t";
 pub(crate) const AUTH_MSG_KEY    : &'static str = "a";
 pub(crate) const CONNECT_MSG_KEY : &'static str = "c";
+pub(crate) const FORMAT_MSG_KEY  : &'static str = "f";
+
+/// Value of the message format query element that selects the JSON debug format.
+#[cfg(all(feature = "server", feature = "debug-json"))]
+pub(crate) const JSON_MSG_FORMAT : &'static str = "json";
+/// Value of the message format query element that selects base64-encoded text frames.
+pub(crate) const BASE64_MSG_FORMAT : &'static str = "b64";
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wire format of a session's messages, negotiated in the connection request.
+#[cfg(feature = "server")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum MsgFormat
+{
+    /// bincode in binary frames
+    Binary,
+    /// bincode in base64-encoded text frames (for networks that mishandle binary frames)
+    Base64,
+    /// JSON in text frames (debug format)
+    Json,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Encode a serialized message as a base64 text frame.
+#[cfg(any(feature = "client", feature = "server"))]
+pub(crate) fn encode_text_frame(bytes: &[u8]) -> String
+{
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Decode a base64 text frame into a serialized message.
+#[cfg(any(feature = "client", feature = "server"))]
+pub(crate) fn decode_text_frame(text: &str) -> Result<Vec<u8>, ()>
+{
+    base64::engine::general_purpose::STANDARD.decode(text).map_err(|_| ())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A chunk of a streamed message.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct StreamChunk
+{
+    pub(crate) stream_id : u64,
+    pub(crate) index     : u32,
+    pub(crate) total     : u32,
+    pub(crate) bytes     : Vec<u8>,
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -24,6 +79,22 @@ pub(crate) enum ClientMetaEvent<ServerMsg, ServerResponse>
     Response(ServerResponse, u64),
     Ack(u64),
     Reject(u64),
+    Chunk(StreamChunk),
+    /// Informs the client that its session is registered and its connection was reported.
+    /// - Includes the server's max message size.
+    Ready(u32),
+    /// A one-shot message with a sequence number (used when the server resends messages after reconnects).
+    SeqMsg(ServerMsg, u64),
+    /// Asks the client to send the last sequence number it received.
+    ResumeRequest,
+    /// Informs the client of the sequence number preceding the messages that will be sent.
+    Resumed(u64),
+    /// Informs the client that the server is draining connections.
+    Draining,
+    /// Answers a client's ping (on-demand or heartbeat).
+    Pong(u64),
+    /// A heartbeat ping that the client should answer with a pong (see `ServerConfig::max_missed_pongs`).
+    Ping(u64),
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -37,10 +108,20 @@ pub(crate) type ClientMetaEventFrom<Channel> = ClientMetaEvent<
 
 /// A meta event that may be received by a server.
 #[derive(Debug, Serialize, Deserialize, Clone)]
+/// Note: the variant order is relied on by [`server_meta_event_size_limit()`].
 pub(crate) enum ServerMetaEvent<ClientMsg, ClientRequest>
 {
     Msg(ClientMsg),
     Request(ClientRequest, u64),
+    Subscribe(String),
+    Unsubscribe(String),
+    Chunk(StreamChunk),
+    /// The last sequence number received by the client (`None` if it has not received any).
+    Resume(Option<u64>),
+    /// An on-demand or heartbeat ping that the server should answer with a pong.
+    Ping(u64),
+    /// Answers a server's heartbeat ping.
+    Pong(u64),
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -51,3 +132,20 @@ pub(crate) type ServerMetaEventFrom<Channel> = ServerMetaEvent<
 >;
 
 //-------------------------------------------------------------------------------------------------------------------
+
+/// Get the type-specific size limit of a serialized [`ServerMetaEvent`] (see [`ChannelPack::MAX_CLIENT_MSG_SIZE`]).
+/// - Only the variant tag is deserialized, so this is cheap to call before deserializing the full message.
+/// - Returns `None` if the message's variant has no size limit.
+pub(crate) fn server_meta_event_size_limit<Channel: ChannelPack>(bytes: &[u8]) -> Option<u32>
+{
+    // bincode encodes enum variants with their index as the leading element
+    let variant: u32 = bincode::DefaultOptions::new().allow_trailing_bytes().deserialize(bytes).ok()?;
+    match variant
+    {
+        0 => Channel::MAX_CLIENT_MSG_SIZE,      //ServerMetaEvent::Msg
+        1 => Channel::MAX_CLIENT_REQUEST_SIZE,  //ServerMetaEvent::Request
+        _ => None,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
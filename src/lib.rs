@@ -8,8 +8,13 @@
 mod authentication;
 mod common;
 mod common_internal;
+mod observer;
+mod pong_tracker;
 mod rate_limiter;
+mod recv_activity;
+mod stream_reassembler;
 mod text_ping_pong;
+mod wire_format;
 
 #[cfg(feature = "client")]
 mod client;
@@ -20,8 +25,13 @@ mod server;
 pub use crate::authentication::*;
 pub use crate::common::*;
 pub(crate) use crate::common_internal::*;
+pub use crate::observer::*;
+pub(crate) use crate::pong_tracker::*;
 pub use crate::rate_limiter::*;
+pub(crate) use crate::recv_activity::*;
+pub(crate) use crate::stream_reassembler::*;
 pub(crate) use crate::text_ping_pong::*;
+pub use crate::wire_format::*;
 
 #[cfg(feature = "client")]
 pub use crate::client::*;
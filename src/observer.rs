@@ -0,0 +1,49 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Observer for lifecycle events, for forwarding structured telemetry to an external sink (e.g. StatsD or
+/// OpenTelemetry).
+///
+/// Set an observer with [`ServerFactory::with_observer()`] or [`ClientFactory::with_observer()`]. All methods default
+/// to no-ops. On servers `id` is the session id, and on clients it is the client's own id.
+///
+/// Methods are called from internal IO tasks, so they should be cheap and must not block.
+pub trait Observer: Send + Sync + 'static
+{
+    /// A session connected (server) or the client connected (client).
+    fn on_connect(&self, _id: SessionID) {}
+    /// A session disconnected (server) or the client disconnected (client).
+    fn on_disconnect(&self, _id: SessionID) {}
+    /// A message frame was received (`size` is the frame's size in bytes).
+    fn on_msg_in(&self, _id: SessionID, _size: usize) {}
+    /// A message frame was sent (`size` is the frame's size in bytes).
+    ///
+    /// This includes responses and internal control messages.
+    fn on_msg_out(&self, _id: SessionID, _size: usize) {}
+    /// A message from a client was dropped by the server's rate limiter (server only).
+    fn on_rate_limit_drop(&self, _id: SessionID) {}
+    /// A request was rejected.
+    fn on_rejection(&self, _id: SessionID, _request_id: u64) {}
+}
+
+impl Debug for dyn Observer
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "Observer") }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Observer that ignores all events (the default).
+#[derive(Debug, Default)]
+pub(crate) struct NoopObserver;
+
+impl Observer for NoopObserver {}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,49 @@
+//local shortcuts
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks heartbeat pings that are waiting for pongs, in order to detect unresponsive connections.
+///
+/// Used to fail half-open connections faster than the keepalive timeout: pings to a dead peer may sit in the socket's
+/// send buffer without failing, but their pongs will never arrive.
+#[derive(Debug)]
+pub(crate) struct PongTracker
+{
+    /// max number of consecutive heartbeat pings that may go unanswered
+    max_missed_pongs: u32,
+    /// number of heartbeat pings sent since the last pong
+    unanswered_pings: u32,
+}
+
+impl PongTracker
+{
+    pub(crate) fn new(max_missed_pongs: u32) -> Self
+    {
+        Self{ max_missed_pongs, unanswered_pings: 0 }
+    }
+
+    /// Record that a heartbeat ping is due.
+    /// - Returns `false` if too many pongs were missed, in which case the connection should be considered dead.
+    ///   Otherwise the caller should send a ping.
+    pub(crate) fn ping_due(&mut self) -> bool
+    {
+        if self.unanswered_pings >= self.max_missed_pongs { return false; }
+        self.unanswered_pings += 1;
+
+        true
+    }
+
+    /// Record that a pong was received.
+    /// - Any pong shows the connection is alive, so the count of missed pongs is reset.
+    pub(crate) fn on_pong(&mut self)
+    {
+        self.unanswered_pings = 0;
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
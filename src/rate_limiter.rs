@@ -3,6 +3,9 @@
 //third-party shortcuts
 
 //standard shortcuts
+use core::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
 #[cfg(not(target_family = "wasm"))]
@@ -13,6 +16,60 @@ use wasm_timer::Instant;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A source of time for [`RateLimitTracker`].
+pub trait Clock: Debug + Send + Sync + 'static
+{
+    /// Time elapsed since an arbitrary fixed point. Must never decrease.
+    fn now(&self) -> Duration;
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A [`Clock`] that reads the system's monotonic time.
+#[derive(Debug, Clone)]
+pub struct SystemClock
+{
+    start: Instant,
+}
+
+impl Default for SystemClock
+{
+    fn default() -> Self { Self{ start: Instant::now() } }
+}
+
+impl Clock for SystemClock
+{
+    fn now(&self) -> Duration { self.start.elapsed() }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A [`Clock`] that only advances when told to. Useful for deterministic tests.
+///
+/// Clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock
+{
+    /// current time in nanoseconds
+    nanos: Arc<AtomicU64>,
+}
+
+impl ManualClock
+{
+    /// Advance the clock.
+    pub fn advance(&self, duration: Duration)
+    {
+        self.nanos.fetch_add(duration.as_nanos() as u64, Ordering::Release);
+    }
+}
+
+impl Clock for ManualClock
+{
+    fn now(&self) -> Duration { Duration::from_nanos(self.nanos.load(Ordering::Acquire)) }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Configuration for rate limiter. Defaults to 10 messages per 100 millisconds.
 #[derive(Debug, Copy, Clone)]
 pub struct RateLimitConfig
@@ -20,7 +77,11 @@ pub struct RateLimitConfig
     /// Length of time to count messages. Defaults to 100 milliseconds.
     pub period: Duration,
     /// Max number of messages that may appear in a collection period. Defaults to 10 messages.
-    pub max_count: u32
+    pub max_count: u32,
+    /// Max number of message bytes that may appear in a collection period. Defaults to unlimited.
+    ///
+    /// Use this to limit bandwidth when clients may send few but large messages.
+    pub max_bytes: u64,
 }
 
 impl Default for RateLimitConfig
@@ -30,6 +91,7 @@ impl Default for RateLimitConfig
         RateLimitConfig{
                 period    : Duration::from_millis(100u64),
                 max_count : 10u32,
+                max_bytes : u64::MAX,
             }
     }
 }
@@ -39,53 +101,105 @@ impl Default for RateLimitConfig
 /// Tracks and limits the rate that messages are accepted.
 /// - If messages appear, on average, more frequently than count/period, then [`RateLimitTracker::try_count_msg()`]
 ///   will fail.
+/// - If message bytes appear, on average, more frequently than bytes/period, then
+///   [`RateLimitTracker::try_count_msg_bytes()`] will fail.
 #[derive(Debug)]
 pub struct RateLimitTracker
 {
     /// rate limit configuration
     config: RateLimitConfig,
-    /// timer
-    timer: Instant,
+    /// clock
+    clock: Arc<dyn Clock>,
+    /// time when the tracker was created
+    start_time: Duration,
 
     /// time of last checkpoint message (first message that appeared after end of last tracking period)
     next_checkpoint_time: Duration,
     /// number of messages received in this tracking period
-    count: u64
+    count: u64,
+    /// number of message bytes received in this tracking period
+    bytes: u64,
 }
 
 impl RateLimitTracker
 {
     /// Make a new rate limit tracker.
     pub fn new(config: RateLimitConfig) -> RateLimitTracker
+    {
+        Self::new_with_clock(config, Arc::new(SystemClock::default()))
+    }
+
+    /// Make a new rate limit tracker with a custom clock.
+    pub fn new_with_clock(config: RateLimitConfig, clock: Arc<dyn Clock>) -> RateLimitTracker
     {
         let next_checkpoint_time = config.period;
+        let start_time = clock.now();
         RateLimitTracker{
                 config,
-                timer: Instant::now(),
+                clock,
+                start_time,
                 next_checkpoint_time,
-                count: 1u64
+                count: 1u64,
+                bytes: 0u64,
             }
     }
 
+    /// Get the time remaining until the current tracking period ends.
+    ///
+    /// Returns zero if the period already ended (the next message will start a new period).
+    pub fn time_until_next_period(&self) -> Duration
+    {
+        let now = self.clock.now().saturating_sub(self.start_time);
+        self.next_checkpoint_time.saturating_sub(now)
+    }
+
+    /// Get the number of messages counted in the current tracking period.
+    ///
+    /// Returns zero if the period already ended.
+    pub fn period_count(&self) -> u64
+    {
+        if self.time_until_next_period().is_zero() { return 0; }
+        self.count
+    }
+
+    /// Get the number of message bytes counted in the current tracking period.
+    ///
+    /// Returns zero if the period already ended.
+    pub fn period_bytes(&self) -> u64
+    {
+        if self.time_until_next_period().is_zero() { return 0; }
+        self.bytes
+    }
+
     /// Try to add a message to the tracker.
     /// - Fails if adding the message violates the rate limit.
     pub fn try_count_msg(&mut self) -> bool
+    {
+        self.try_count_msg_bytes(0u64)
+    }
+
+    /// Try to add a message with a given size (in bytes) to the tracker.
+    /// - Fails if adding the message violates the message count or byte rate limits.
+    pub fn try_count_msg_bytes(&mut self, num_bytes: u64) -> bool
     {
         // check if we are in a new period
-        let msg_time = self.timer.elapsed();
+        let msg_time = self.clock.now().saturating_sub(self.start_time);
 
         if msg_time >= self.next_checkpoint_time
         {
             // reset state for new tracking period
             self.next_checkpoint_time = msg_time.saturating_add(self.config.period);
             self.count = 0;
+            self.bytes = 0;
         }
 
         // increment count
         self.count += 1;
+        self.bytes = self.bytes.saturating_add(num_bytes);
 
         // check if we have exceeded the rate limit
         if self.count > self.config.max_count as u64 { return false; }
+        if self.bytes > self.config.max_bytes { return false; }
 
         true
     }
@@ -0,0 +1,64 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+
+#[cfg(target_family = "wasm")]
+use wasm_timer::Instant;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks when a connection last received a frame.
+///
+/// Used to classify connections that end without a close frame: the keepalive timeout can only elapse after the
+/// connection was silent for at least the timeout.
+#[derive(Debug, Clone)]
+pub(crate) struct RecvActivity
+{
+    last_recv: Arc<Mutex<Instant>>,
+}
+
+impl RecvActivity
+{
+    pub(crate) fn new() -> Self
+    {
+        Self{ last_recv: Arc::new(Mutex::new(Instant::now())) }
+    }
+
+    /// Record that a frame was received.
+    pub(crate) fn touch(&self)
+    {
+        match self.last_recv.lock()
+        {
+            Ok(mut last_recv) => *last_recv = Instant::now(),
+            Err(_) => tracing::error!("recv activity poisoned"),
+        }
+    }
+
+    /// Classify a connection that ended without a close frame.
+    /// - The connection timed out if it was silent for at least the keepalive timeout, otherwise it was lost.
+    pub(crate) fn lost_connection_reason(&self, keepalive_timeout: Duration) -> DisconnectReason
+    {
+        let silent_for = match self.last_recv.lock()
+        {
+            Ok(last_recv) => last_recv.elapsed(),
+            Err(_) => { tracing::error!("recv activity poisoned"); Duration::default() }
+        };
+
+        match silent_for >= keepalive_timeout
+        {
+            true  => DisconnectReason::TimedOut,
+            false => DisconnectReason::ConnectionLost,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
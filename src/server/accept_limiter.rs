@@ -0,0 +1,164 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use axum::extract::connect_info::Connected;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Hook run by [`RateLimitedAcceptor`] on each accepted connection, once the inner acceptor is done with it (e.g. after
+/// the TLS handshake).
+pub(crate) trait AcceptHook<S>
+{
+    /// Held by the connection's stream until the connection closes.
+    type Guard: Debug + Send + Unpin + 'static;
+
+    /// Inspect an accepted connection.
+    fn on_accept(&self, peer_addr: SocketAddr, stream: &S) -> Self::Guard;
+}
+
+/// Accept hook that does nothing.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NoAcceptHook;
+
+impl<S> AcceptHook<S> for NoAcceptHook
+{
+    type Guard = ();
+
+    fn on_accept(&self, _peer_addr: SocketAddr, _stream: &S) {}
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wraps an accepted connection in order to hold its accept hook's guard until the connection closes.
+#[derive(Debug)]
+pub(crate) struct AcceptedStream<S, G>
+{
+    /// the wrapped stream
+    inner: S,
+    /// the accept hook's guard
+    _guard: G,
+}
+
+impl<S: AsyncRead + Unpin, G: Unpin> AsyncRead for AcceptedStream<S, G>
+{
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>>
+    {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin, G: Unpin> AsyncWrite for AcceptedStream<S, G>
+{
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>>
+    {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self : Pin<&mut Self>,
+        cx       : &mut Context<'_>,
+        bufs     : &[std::io::IoSlice<'_>]
+    ) -> Poll<std::io::Result<usize>>
+    {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool
+    {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>>
+    {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>>
+    {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wraps a connection acceptor in order to limit the rate that new TCP connections are accepted.
+/// - Connections that exceed the rate limit are dropped before reaching the upgrade path.
+/// - Connections that are accepted are passed to an [`AcceptHook`] (e.g. to record TLS handshake details, see
+///   [`TlsInfoRegistry`]).
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimitedAcceptor<A, H = NoAcceptHook>
+{
+    /// the wrapped acceptor
+    inner: A,
+    /// rate limit tracker for accepted connections (shared by all clones of the acceptor)
+    tracker: Option<Arc<Mutex<RateLimitTracker>>>,
+    /// hook for accepted connections
+    hook: H,
+}
+
+impl<A, H> RateLimitedAcceptor<A, H>
+{
+    pub(crate) fn new(inner: A, rate_limit_config: Option<RateLimitConfig>, hook: H) -> Self
+    {
+        let tracker = rate_limit_config.map(|config| Arc::new(Mutex::new(RateLimitTracker::new(config))));
+        Self{ inner, tracker, hook }
+    }
+
+    fn try_count_connection(&self) -> bool
+    {
+        let Some(tracker) = &self.tracker else { return true; };
+        let Ok(mut tracker) = tracker.lock() else { tracing::error!("accept rate limit tracker poisoned"); return false; };
+        tracker.try_count_msg()
+    }
+}
+
+impl<A, H, I, S> axum_server::accept::Accept<I, S> for RateLimitedAcceptor<A, H>
+where
+    A: axum_server::accept::Accept<I, S>,
+    A::Stream: Send + 'static,
+    A::Service: Send + 'static,
+    A::Future: Send + 'static,
+    H: AcceptHook<A::Stream> + Clone + Send + 'static,
+    for<'a> SocketAddr: Connected<&'a I>,
+{
+    type Stream  = AcceptedStream<A::Stream, H::Guard>;
+    type Service = A::Service;
+    type Future  = Pin<Box<dyn Future<Output = std::io::Result<(Self::Stream, A::Service)>> + Send>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future
+    {
+        // drop the connection if it violates the accept rate limit
+        if !self.try_count_connection()
+        {
+            tracing::trace!("accept rate limit exceeded, dropping connection...");
+            return Box::pin(std::future::ready(Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionRefused,
+                    "accept rate limit exceeded"
+                ))));
+        }
+
+        // the peer address is read the same way the router reads it, so hooks can key connections by the address that
+        // sessions report
+        let peer_addr = SocketAddr::connect_info(&stream);
+        let hook = self.hook.clone();
+        let accept = self.inner.accept(stream, service);
+
+        Box::pin(async move {
+                let (stream, service) = accept.await?;
+                let guard = hook.on_accept(peer_addr, &stream);
+                Ok((AcceptedStream{ inner: stream, _guard: guard }, service))
+            })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
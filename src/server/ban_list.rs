@@ -0,0 +1,51 @@
+//local shortcuts
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::collections::HashMap;
+use std::time::Instant;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks client ids that are banned from connecting.
+/// - Shared between the server API and the connection prevalidator.
+#[derive(Debug, Default)]
+pub(crate) struct BanList
+{
+    /// client id : when the ban expires (`None` for permanent bans)
+    bans: HashMap<u128, Option<Instant>>,
+}
+
+impl BanList
+{
+    /// Ban a client id until the given time (or permanently).
+    pub(crate) fn ban(&mut self, client_id: u128, until: Option<Instant>)
+    {
+        self.prune();
+        self.bans.insert(client_id, until);
+    }
+
+    /// Remove a client id's ban.
+    pub(crate) fn unban(&mut self, client_id: u128)
+    {
+        self.bans.remove(&client_id);
+    }
+
+    /// Check if a client id is banned.
+    pub(crate) fn is_banned(&self, client_id: u128) -> bool
+    {
+        let Some(until) = self.bans.get(&client_id) else { return false; };
+        until.map_or(true, |until| Instant::now() < until)
+    }
+
+    /// Remove expired bans.
+    fn prune(&mut self)
+    {
+        let now = Instant::now();
+        self.bans.retain(|_, until| until.map_or(true, |until| now < until));
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,76 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy_ecs::event::{Event, EventWriter, Events};
+use bevy_ecs::system::{Command, Res};
+use bevy_ecs::world::World;
+
+//standard shortcuts
+use std::marker::PhantomData;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Bevy event that carries a [`ServerEvent`] drained from a [`Server`] resource.
+///
+/// Register it with `App::add_event::<ServerEventOf<Channel>>()`, then use [`drain_server_events`] and
+/// [`RemoveServer`] to forward server events into it.
+#[derive(Event, Debug)]
+pub struct ServerEventOf<Channel: ChannelPack>
+{
+    /// the session the event came from
+    pub session_id : SessionID,
+    /// the server event
+    pub event      : ServerEventFrom<Channel>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// System that drains the pending events of a [`Server`] resource into `Events<ServerEventOf<Channel>>`.
+///
+/// Does nothing if the server resource doesn't exist.
+pub fn drain_server_events<Channel: ChannelPack>(
+    server     : Option<Res<Server<Channel>>>,
+    mut events : EventWriter<ServerEventOf<Channel>>,
+){
+    let Some(server) = server else { return; };
+    while let Some((session_id, event)) = server.next() { events.send(ServerEventOf{ session_id, event }); }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command that removes a [`Server`] resource after draining its pending events into
+/// `Events<ServerEventOf<Channel>>`, so they can be read by systems that run after the command is applied.
+///
+/// A warning is logged if the events resource doesn't exist.
+///
+/// Bevy does not notify resources when they are removed, so removing the server resource directly (e.g. with
+/// `Commands::remove_resource()`) still discards its pending events.
+pub struct RemoveServer<Channel: ChannelPack>
+{
+    _phantom : PhantomData<fn() -> Channel>,
+}
+
+impl<Channel: ChannelPack> Default for RemoveServer<Channel>
+{
+    fn default() -> Self { Self{ _phantom: PhantomData::default() } }
+}
+
+impl<Channel: ChannelPack> Command for RemoveServer<Channel>
+{
+    fn apply(self, world: &mut World)
+    {
+        let Some(server) = world.remove_resource::<Server<Channel>>() else { return; };
+
+        // drain the server's events
+        let Some(mut events) = world.get_resource_mut::<Events<ServerEventOf<Channel>>>()
+        else
+        {
+            tracing::warn!("server events were not registered, discarding server events during teardown");
+            return;
+        };
+        while let Some((session_id, event)) = server.next() { events.send(ServerEventOf{ session_id, event }); }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -5,24 +5,222 @@ use crate::*;
 
 //standard shortcuts
 use core::fmt::Debug;
+use std::any::Any;
+use std::sync::Arc;
 use std::time::Duration;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Policy for handling sessions that exceed the server's rate limit.
+///
+/// Rate limit violations are reported with [`ServerEvent::RateLimited`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RateLimitPolicy
+{
+    /// Close the session.
+    CloseSession,
+    /// Drop messages that exceed the rate limit.
+    DropMessages,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wrapper trait for `Fn(&ServerContext) -> bool`.
+///
+/// Used to shed load by rejecting new connections while the server is overloaded (see
+/// [`ServerConfig::load_shed`]). Returns `true` if new connections should be rejected.
+///
+/// The function is invoked for every connection request, so it should be cheap and must not block.
+pub trait LoadShedFn: Fn(&ServerContext) -> bool + Send + Sync + 'static {}
+impl<F> LoadShedFn for F where F: Fn(&ServerContext) -> bool + Send + Sync + 'static {}
+
+impl Debug for dyn LoadShedFn
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "LoadShedFn") }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wrapper trait for `Fn(&ServerContext, &ezsockets::Request) -> Result<Option<Arc<dyn Any + Send + Sync>>, String>`.
+///
+/// Used to inspect a client's full websocket upgrade request (path, headers, query) when it connects (see
+/// [`ServerConfig::on_upgrade`]). Returns `Ok` with optional per-session context to accept the connection, or `Err`
+/// with a reason to reject it.
+///
+/// The hook runs on the task that handles the connection request, before the connection is upgraded, so it should be
+/// cheap and must not block.
+pub trait UpgradeHookFn:
+    Fn(&ServerContext, &ezsockets::Request) -> Result<Option<Arc<dyn Any + Send + Sync>>, String>
+    + Send + Sync + 'static
+{}
+impl<F> UpgradeHookFn for F where
+    F: Fn(&ServerContext, &ezsockets::Request) -> Result<Option<Arc<dyn Any + Send + Sync>>, String>
+    + Send + Sync + 'static
+{}
+
+impl Debug for dyn UpgradeHookFn
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "UpgradeHookFn") }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wrapper trait for `Fn(SessionID, M) -> Option<M>`.
+///
+/// Used to validate or normalize client messages after they are deserialized and before they are emitted as server
+/// events (see [`ServerFactory::with_inbound_middleware()`]). Returns `None` to drop the message.
+///
+/// The middleware runs on each session's IO task for every client message, so it should be cheap and must not block.
+pub trait InboundMiddlewareFn<M>: Fn(SessionID, M) -> Option<M> + Send + Sync + 'static {}
+impl<M, F> InboundMiddlewareFn<M> for F where F: Fn(SessionID, M) -> Option<M> + Send + Sync + 'static {}
+
+impl<M> Debug for dyn InboundMiddlewareFn<M>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "InboundMiddlewareFn") }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Config for the [`Server`].
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct ServerConfig
 {
     /// Max number of concurrent client connections. Defaults to 100K.
     pub max_connections: u32,
+    /// Load signal checked when a client tries to connect. Defaults to `None`.
+    ///
+    /// If the function returns `true`, the connection request is rejected as 'server busy' (HTTP 503). Use this to
+    /// plug in an app-specific load signal (e.g. [`Server::queued_events()`], CPU usage, or tick budget) so the
+    /// server stays responsive to existing clients while overloaded.
+    pub load_shed: Option<Arc<dyn LoadShedFn>>,
     /// Max message size allowed from clients (bytes). Defaults to 1MB.
     pub max_msg_size: u32,
+    /// Max number of bytes buffered for partially-received streams from a client. Defaults to 16MB.
+    ///
+    /// This bounds the size of streamed messages. Each chunk of a stream must also respect
+    /// [`ServerConfig::max_msg_size`].
+    pub max_stream_size: u32,
+    /// Chunk size for streams sent with [`Server::send_stream()`] (bytes). Defaults to 100KB.
+    pub stream_chunk_size: u32,
+    /// Duration after which a partially-received stream is discarded if no new chunks arrive. Defaults to 10 seconds.
+    pub stream_timeout: Duration,
     /// Rate limit for messages received from a session. See [`RateLimitConfig`] for defaults.
     pub rate_limit_config: RateLimitConfig,
+    /// What to do when a session exceeds the rate limit. Defaults to [`RateLimitPolicy::CloseSession`].
+    pub rate_limit_policy: RateLimitPolicy,
+    /// Max number of requests from a session that may be waiting for a response. Defaults to `None` (no limit).
+    ///
+    /// A request is outstanding until its [`RequestToken`] is consumed or dropped. Requests received while a session
+    /// is at the limit are rejected immediately and are not emitted as server events. This prevents a single session
+    /// from amplifying server work by firing many concurrent requests.
+    pub max_concurrent_requests_per_session: Option<u32>,
+    /// Rate limit for accepting new TCP connections. Defaults to `None` (no limit).
+    ///
+    /// Connections that exceed the rate limit are dropped before they reach the connection upgrade path.
+    pub accept_rate_limit: Option<RateLimitConfig>,
+    /// Backlog size for pending TCP connections on the server's listener. Defaults to `None` (the OS default).
+    pub accept_backlog: Option<u32>,
+    /// Max duration between accepting a TCP connection and receiving its complete upgrade request. Defaults to 10
+    /// seconds.
+    ///
+    /// Connections that stall before the server responds to their upgrade request (e.g. slowloris-style clients) are
+    /// dropped when the timeout expires. Includes the TLS handshake.
+    pub upgrade_timeout: Duration,
+    /// Origins that may connect to the server. Defaults to `None` (all origins are allowed).
+    ///
+    /// Connection requests with an `Origin` header that is not in the list are rejected, which protects browser
+    /// deployments from cross-site connections. Requests without an `Origin` header (e.g. from native clients) are
+    /// allowed. Origins are compared exactly (e.g. `"https://example.com"`).
+    pub allowed_origins: Option<Vec<String>>,
+    /// Max number of recent messages retained per client for resending after the client reconnects. Defaults to 0
+    /// (disabled).
+    ///
+    /// When enabled, one-shot messages sent to a client are numbered and logged. When the client reconnects, it reports
+    /// the last message it received and the server resends the gap before sending new messages. Messages sent while
+    /// the client is disconnected are logged and sent when it reconnects. Messages evicted from the log are lost.
+    /// Streams, responses, acks, and rejections are not resent.
+    ///
+    /// Clients discard resent messages at or below the highest sequence number they already received, so a message
+    /// is never emitted twice by [`Client::next()`] even if a reconnect races with delivery.
+    pub resend_buffer_size: usize,
+    /// Duration a disconnected client's resend buffer is retained. Defaults to 30 seconds.
+    pub resend_timeout: Duration,
     /// Duration between socket heartbeat pings if the connection is inactive. Defaults to 5 seconds.
     pub heartbeat_interval: Duration,
     /// Duration after which a socket will shut down if the connection is inactive. Defaults to 10 seconds.
+    ///
+    /// Heartbeat pongs count as activity. See [`ServerConfig::max_missed_pongs`] for detecting unresponsive
+    /// connections sooner.
     pub keepalive_timeout: Duration,
+    /// Heartbeat interval for WASM clients. Defaults to `None` (use [`ServerConfig::heartbeat_interval`]).
+    pub wasm_heartbeat_interval: Option<Duration>,
+    /// Keepalive timeout for WASM clients. Defaults to `None` (use [`ServerConfig::keepalive_timeout`]).
+    ///
+    /// Browsers throttle timers in backgrounded tabs, so WASM clients may need a more lenient timeout than native
+    /// clients to avoid being disconnected while backgrounded.
+    pub wasm_keepalive_timeout: Option<Duration>,
+    /// Max number of consecutive heartbeat pings a session may leave unanswered before it is closed. Defaults to
+    /// `None` (unresponsive sessions are only closed by [`ServerConfig::keepalive_timeout`]).
+    ///
+    /// When set, the server pings each session at its heartbeat interval and closes sessions that miss this many pongs
+    /// in a row with [`CloseReason::Unresponsive`]. This detects half-open connections sooner than the keepalive
+    /// timeout, since pings to a dead client can sit in the socket's send buffer without failing. Pongs count against
+    /// the session's [`ServerConfig::rate_limit_config`]. Sessions that use the JSON debug message format are not
+    /// pinged.
+    pub max_missed_pongs: Option<u32>,
+    /// Drain the server when the process receives a termination signal (SIGINT, or SIGTERM on unix targets).
+    /// Defaults to `false`.
+    ///
+    /// When a signal arrives, the server runs [`Server::begin_lame_duck()`] with
+    /// [`ServerConfig::shutdown_grace_period`]. Enabling this replaces the default signal behavior, so the process
+    /// will not exit on its own; poll [`Server::shutdown_signal()`] and exit once it is done.
+    pub shutdown_on_signal: bool,
+    /// Grace period for draining connections after a termination signal. Defaults to 10 seconds.
+    pub shutdown_grace_period: Duration,
+    /// Run the connection handler on a dedicated thread with its own single-threaded runtime. Defaults to `false`.
+    ///
+    /// By default the connection handler shares the server's runtime with socket IO. A dedicated thread isolates the
+    /// handler's loop (session bookkeeping, client messages, and server API commands) from IO load, which can reduce
+    /// tail latency on busy servers. When the server is dropped, its sessions are closed with
+    /// [`CloseReason::Draining`], then the thread is stopped and joined.
+    pub dedicated_handler_thread: bool,
+    /// Coalesce server events into batches when the server's handlers send them to the [`Server`]. Defaults to
+    /// `false`.
+    ///
+    /// Each event normally crosses the server's internal event channel on its own. When coalescing, events sent close
+    /// together (e.g. by many sessions at once) cross as one batch after a short yield on the server's runtime, which
+    /// amortizes synchronization costs on high-throughput servers at the cost of slightly delaying each event. Events
+    /// are consumed one at a time or in bulk as usual (see [`Server::next_batch()`]).
+    pub coalesce_events: bool,
+    /// Hook that inspects each connection's websocket upgrade request after it passes the server's built-in
+    /// validation. Defaults to `None`.
+    ///
+    /// Rejected requests are answered with HTTP 403 (Forbidden) and the hook's reason, without upgrading the
+    /// connection. Context returned by the hook can be accessed with [`Server::session_context()`].
+    pub on_upgrade: Option<Arc<dyn UpgradeHookFn>>,
+    /// Identifier attached to the server's internal logs as the `log_id` field of a tracing span. Defaults to `None`.
+    ///
+    /// Use this to filter logs per-instance when running multiple servers in one process.
+    pub log_id: Option<String>,
+    /// Hook applied to serialized messages immediately before they are sent to a session. Defaults to `None`.
+    ///
+    /// The hook runs on the server's internal connection handler, so it should be cheap and must not block.
+    pub on_send: Option<Arc<dyn BytesHookFn>>,
+    /// Hook applied to messages immediately after they are received from a session. Defaults to `None`.
+    ///
+    /// The hook runs on the session's IO task after the message size is validated, so it should be cheap and must not
+    /// block.
+    pub on_recv: Option<Arc<dyn BytesHookFn>>,
+    /// Cipher for application-layer encryption of message payloads. Defaults to `None`.
+    ///
+    /// This is independent of [`AcceptorConfig`] TLS. Clients must use a matching
+    /// `ClientConfig::payload_encryption`.
+    pub payload_encryption: Option<Arc<dyn PayloadCipher>>,
+    /// Per-message compression of message payloads. Defaults to `None`.
+    ///
+    /// Clients must use matching `ClientConfig::payload_compression`. Received messages that exceed
+    /// [`ServerConfig::max_msg_size`] after decompression are treated as message size violations.
+    pub payload_compression: Option<PayloadCompression>,
 }
 
 impl Default for ServerConfig
@@ -30,11 +228,36 @@ impl Default for ServerConfig
     fn default() -> ServerConfig
     {
         ServerConfig{
-                max_connections    : 100_000u32,
-                max_msg_size       : 1_000_000u32,
-                rate_limit_config  : RateLimitConfig::default(),
-                heartbeat_interval : Duration::from_secs(5),
-                keepalive_timeout  : Duration::from_secs(10),
+                max_connections                     : 100_000u32,
+                load_shed                           : None,
+                max_msg_size                        : 1_000_000u32,
+                max_stream_size                     : 16_000_000u32,
+                stream_chunk_size                   : 100_000u32,
+                stream_timeout                      : Duration::from_secs(10),
+                rate_limit_config                   : RateLimitConfig::default(),
+                rate_limit_policy                   : RateLimitPolicy::CloseSession,
+                max_concurrent_requests_per_session : None,
+                accept_rate_limit                   : None,
+                accept_backlog                      : None,
+                upgrade_timeout                     : Duration::from_secs(10),
+                allowed_origins                     : None,
+                resend_buffer_size                  : 0usize,
+                resend_timeout                      : Duration::from_secs(30),
+                heartbeat_interval                  : Duration::from_secs(5),
+                keepalive_timeout                   : Duration::from_secs(10),
+                wasm_heartbeat_interval             : None,
+                wasm_keepalive_timeout              : None,
+                max_missed_pongs                    : None,
+                shutdown_on_signal                  : false,
+                shutdown_grace_period               : Duration::from_secs(10),
+                dedicated_handler_thread            : false,
+                coalesce_events                     : false,
+                on_upgrade                          : None,
+                log_id                              : None,
+                on_send                             : None,
+                on_recv                             : None,
+                payload_encryption                  : None,
+                payload_compression                 : None,
             }
     }
 }
@@ -6,30 +6,252 @@ use bincode::Options;
 
 //standard shortcuts
 use core::fmt::Debug;
-use std::sync::Arc;
-use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::collections::HashMap;
+use std::time::Duration;
 
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
-fn reject_client_request<Channel: ChannelPack>(
-    session    : &ezsockets::Session<SessionID, ()>,
-    session_id : SessionID,
-    request_id : u64
-){
-    // pack the message
-    let packed_msg = ClientMetaEventFrom::<Channel>::Reject(request_id);
+/// Returns the message's signal, or `None` if the message could not be sent.
+fn send_client_meta_event<Channel: ChannelPack>(
+    session        : &ezsockets::Session<SessionID, SessionCall>,
+    outbound_queue : &Mutex<OutboundQueue>,
+    on_send        : &Option<Arc<dyn BytesHookFn>>,
+    observer       : &Arc<dyn Observer>,
+    msg_format     : MsgFormat,
+    session_id     : SessionID,
+    msg            : &ClientMetaEventFrom<Channel>
+) -> Option<ezsockets::MessageSignal>
+{
+    // send as JSON text if the session uses the JSON debug format
+    // - bytes hooks are not applied
+    if msg_format == MsgFormat::Json
+    {
+        let Ok(text) = serde_json::to_string(msg)
+        else { tracing::error!(session_id, "serializing message failed"); return None; };
+        let size = text.len();
+
+        return match session.text(text)
+        {
+            Ok(signal) =>
+            {
+                track_outbound(outbound_queue, &signal);
+                observer.on_msg_out(session_id, size);
+                Some(signal)
+            }
+            Err(_) => { tracing::debug!(session_id, "dropping message sent to broken session"); None }
+        };
+    }
 
     // serialize message
-    tracing::trace!(session_id, "sending request rejection to session");
-    let Ok(ser_msg) = bincode::DefaultOptions::new().serialize(&packed_msg)
-    else { tracing::error!(session_id, "serializing rejection failed"); return; };
+    let Ok(mut ser_msg) = bincode::DefaultOptions::new().serialize(msg)
+    else { tracing::error!(session_id, "serializing message failed"); return None; };
+    apply_bytes_hook(on_send, &mut ser_msg);
 
     // forward server message to target session
     // - this may fail if the session is disconnected
-    if let Err(_) = session.binary(ser_msg)
-    { tracing::debug!(session_id, "dropping request rejection sent to broken session"); }
+    let size = ser_msg.len();
+    match send_session_frame(session, outbound_queue, msg_format, ser_msg)
+    {
+        Ok(signal) => { observer.on_msg_out(session_id, size); Some(signal) }
+        Err(())    => { tracing::debug!(session_id, "dropping message sent to broken session"); None }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn reject_client_request<Channel: ChannelPack>(
+    session        : &ezsockets::Session<SessionID, SessionCall>,
+    outbound_queue : &Mutex<OutboundQueue>,
+    on_send        : &Option<Arc<dyn BytesHookFn>>,
+    observer       : &Arc<dyn Observer>,
+    msg_format     : MsgFormat,
+    session_id     : SessionID,
+    request_id     : u64
+){
+    tracing::trace!(session_id, "sending request rejection to session");
+    observer.on_rejection(session_id, request_id);
+    send_client_meta_event::<Channel>(
+            session,
+            outbound_queue,
+            on_send,
+            observer,
+            msg_format,
+            session_id,
+            &ClientMetaEventFrom::<Channel>::Reject(request_id)
+        );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Report the delivery receipt of a tracked message.
+/// - The message was delivered if its signal reports it was sent.
+fn report_delivery_receipt<Channel: ChannelPack>(
+    server_event_sender : &ServerEventSender<Channel>,
+    session_id          : SessionID,
+    generation          : u64,
+    signal_id           : MessageSignalId,
+    signal              : Option<ezsockets::MessageSignal>
+){
+    let delivered = signal.map_or(false, |signal| signal.status() == ezsockets::MessageStatus::Sent);
+    tracing::trace!(session_id, signal_id, delivered, "reporting delivery receipt");
+
+    if let Err(err) = server_event_sender.send(
+            SessionSourceMsg::new(session_id, generation, ServerEventFrom::<Channel>::DeliveryReceipt{ signal_id, delivered })
+        )
+    {
+        tracing::debug!(?err, "failed forwarding delivery receipt");
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Max duration between checks of tracked messages that are still being written to their sessions' sockets.
+/// - The watcher is normally woken when a connection finishes flushing. This bounds the delay for messages that fail
+///   without a flush (e.g. when the session's socket breaks).
+const DELIVERY_RECHECK_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// A tracked message that is being written to its session's socket.
+#[derive(Debug)]
+struct PendingDelivery
+{
+    session_id : SessionID,
+    generation : u64,
+    signal_id  : MessageSignalId,
+    signal     : ezsockets::MessageSignal,
+}
+
+/// Tracked messages that are being written to their sessions' sockets.
+#[derive(Debug, Default)]
+struct PendingDeliveries
+{
+    deliveries : Vec<PendingDelivery>,
+    /// indicates the watcher task is running
+    watching   : bool,
+}
+
+/// Reports delivery receipts for tracked messages once they are written to their sessions' sockets.
+/// - Message signals don't notify on status changes, so one watcher task checks all pending messages whenever a
+///   connection finishes flushing (see [`FlushNotifyAcceptor`]). The watcher only runs while messages are pending.
+#[derive(Debug)]
+pub(crate) struct DeliveryReceiptWatcher<Channel: ChannelPack>
+{
+    server_event_sender : ServerEventSender<Channel>,
+    pending             : Mutex<PendingDeliveries>,
+    /// notified when a connection finishes flushing
+    flushed             : Arc<tokio::sync::Notify>,
+}
+
+impl<Channel: ChannelPack> DeliveryReceiptWatcher<Channel>
+{
+    pub(crate) fn new(
+        server_event_sender : ServerEventSender<Channel>,
+        flushed             : Arc<tokio::sync::Notify>
+    ) -> Arc<Self>
+    {
+        Arc::new(Self{ server_event_sender, pending: Mutex::new(PendingDeliveries::default()), flushed })
+    }
+
+    /// Watch a tracked message, then report its delivery receipt once it finishes sending.
+    /// - Must be called within a tokio runtime.
+    fn watch(
+        self       : &Arc<Self>,
+        session_id : SessionID,
+        generation : u64,
+        signal_id  : MessageSignalId,
+        signal     : ezsockets::MessageSignal
+    ){
+        let Ok(mut pending) = self.pending.lock()
+        else
+        {
+            tracing::error!("pending deliveries poisoned");
+            report_delivery_receipt::<Channel>(&self.server_event_sender, session_id, generation, signal_id, None);
+            return;
+        };
+        pending.deliveries.push(PendingDelivery{ session_id, generation, signal_id, signal });
+
+        // start the watcher if it isn't running
+        if pending.watching { return; }
+        pending.watching = true;
+        tokio::spawn(self.clone().run());
+    }
+
+    /// Report delivery receipts as tracked messages finish sending, until no messages are pending.
+    async fn run(self: Arc<Self>)
+    {
+        loop
+        {
+            // wait for a connection to finish flushing
+            // - the flush is reported before the socket marks the message as sent, so yield to let the socket finish
+            let _ = tokio::time::timeout(DELIVERY_RECHECK_TIMEOUT, self.flushed.notified()).await;
+            tokio::task::yield_now().await;
+
+            // collect messages that finished sending
+            // - the watching flag is cleared while holding the lock so a message added concurrently will start a
+            //   new watcher
+            let (finished, done) = {
+                let Ok(mut pending) = self.pending.lock()
+                else { tracing::error!("pending deliveries poisoned"); return; };
+                let (finished, still_sending) = std::mem::take(&mut pending.deliveries)
+                    .into_iter()
+                    .partition::<Vec<_>, _>(|delivery| delivery.signal.status() != ezsockets::MessageStatus::Sending);
+                pending.deliveries = still_sending;
+                pending.watching = !pending.deliveries.is_empty();
+                (finished, !pending.watching)
+            };
+
+            for PendingDelivery{ session_id, generation, signal_id, signal } in finished
+            {
+                report_delivery_receipt::<Channel>(&self.server_event_sender, session_id, generation, signal_id, Some(signal));
+            }
+
+            if done { return; }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn disconnect_reason(
+    closed_by_server  : bool,
+    recv_activity     : Option<&RecvActivity>,
+    keepalive_timeout : Duration,
+    reason            : &Result<Option<ezsockets::CloseFrame>, ezsockets::Error>
+) -> DisconnectReason
+{
+    if closed_by_server { return DisconnectReason::ServerClosed; }
+
+    // the keepalive timeout elapsing stops the socket without a close frame, but so does the connection being cut
+    // - ezsockets reports both the same way, so they are distinguished by how long the client was silent
+    let lost_connection_reason = || match recv_activity
+    {
+        Some(recv_activity) => recv_activity.lost_connection_reason(keepalive_timeout),
+        None => DisconnectReason::ConnectionLost,
+    };
+
+    match reason
+    {
+        // the client sent a close frame
+        Ok(Some(close_frame)) => DisconnectReason::ClientClosed(Some(close_frame.clone())),
+        // the socket stopped without a close frame
+        Ok(None) => lost_connection_reason(),
+        // the connection broke
+        // - a keepalive timeout can surface as an error if the socket is torn down mid-read
+        Err(err) =>
+        {
+            tracing::debug!(?err, "session disconnected with transport error");
+            match lost_connection_reason()
+            {
+                DisconnectReason::TimedOut => DisconnectReason::TimedOut,
+                _ => DisconnectReason::TransportError,
+            }
+        }
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -41,31 +263,61 @@ pub(crate) struct ConnectionHandler<Channel: ChannelPack>
 {
     /// config: maximum message size (bytes)
     pub(crate) config: ServerConfig,
+    /// tracing span for the server's logs (shared with sessions)
+    pub(crate) span: tracing::Span,
     /// counter for number of connections
     pub(crate) connection_counter: ConnectionCounter,
 
     /// registered sessions
-    pub(crate) session_registry: HashMap<SessionID, ezsockets::Session<SessionID, ()>>,
+    pub(crate) session_registry: HashMap<SessionID, SessionEntry>,
+    /// metadata for connected sessions (shared with the server)
+    pub(crate) session_info: SessionInfoRegistry,
+    /// topic subscriptions (shared with the server and sessions)
+    pub(crate) topic_registry: Arc<Mutex<TopicRegistry>>,
+    /// resend logs of connected and recently-disconnected clients (shared with sessions)
+    pub(crate) resend_logs: HashMap<SessionID, Arc<Mutex<ResendLog>>>,
+    /// generation number of the most recent session
+    /// - a single counter is shared by all clients, so each client's generations are strictly increasing
+    pub(crate) last_generation: u64,
+    /// when the connection handler was created
+    pub(crate) started_at: std::time::Instant,
+    /// the server's protocol version
+    pub(crate) protocol_version: &'static str,
+    /// the current authenticator (shared with the server and prevalidator)
+    pub(crate) authenticator: Arc<RwLock<Authenticator>>,
+    /// session contexts returned by the upgrade hook (shared with the prevalidator)
+    pub(crate) upgrade_contexts: UpgradeContexts,
+    /// TLS handshake details of open connections (shared with the acceptor)
+    pub(crate) tls_infos: TlsInfoRegistry,
+    /// observer for lifecycle events (shared with sessions)
+    pub(crate) observer: Arc<dyn Observer>,
+    /// middleware applied to client messages (shared with sessions)
+    pub(crate) inbound_middleware: Option<Arc<dyn InboundMiddlewareFn<Channel::ClientMsg>>>,
 
     /// cached sender endpoint for constructing new sessions
     /// - receiver is in server owner
-    pub(crate) server_event_sender: crossbeam::channel::Sender<SessionSourceMsg<SessionID, ServerEventFrom<Channel>>>,
+    pub(crate) server_event_sender: ServerEventSender<Channel>,
+    /// reports delivery receipts for tracked messages
+    pub(crate) delivery_receipts: Arc<DeliveryReceiptWatcher<Channel>>,
 }
 
 #[async_trait::async_trait]
 impl<Channel: ChannelPack> ezsockets::ServerExt for ConnectionHandler<Channel>
 {
     type Session = SessionHandler<Channel>;  //Self::Session, not ezsockets::Session
-    type Call    = SessionTargetMsg<SessionID, SessionCommand<Channel>>;
+    type Call    = HandlerCommand<Channel>;
 
     /// Produces server sessions for new connections.
     async fn on_connect(
         &mut self,
         socket   : ezsockets::Socket,
         request  : ezsockets::Request,
-        _address : std::net::SocketAddr,
-    ) -> Result<ezsockets::Session<SessionID, ()>, Option<ezsockets::CloseFrame>>
+        address  : std::net::SocketAddr,
+    ) -> Result<ezsockets::Session<SessionID, SessionCall>, Option<ezsockets::CloseFrame>>
     {
+        let span = self.span.clone();
+        let _entered = span.enter();
+
         // reject connection if max connections reached
         if self.session_registry.len() >= self.config.max_connections as usize
         {
@@ -77,59 +329,231 @@ impl<Channel: ChannelPack> ezsockets::ServerExt for ConnectionHandler<Channel>
         }
 
         // extract info from the request
-        let info = extract_connection_info(&request, &self.session_registry)?;
+        let info = extract_connection_info(
+                &request,
+                self.protocol_version,
+                &self.authenticator,
+                &self.session_registry
+            )?;
 
-        // report the new connection
-        let report = ServerReport::<Channel::ConnectMsg>::Connected(info.client_env_type, info.connect_msg);
-        if let Err(err) = self.server_event_sender.send(
-                SessionSourceMsg::new(info.id, ServerEventFrom::<Channel>::Report(report))
-            )
+        // take the session context recorded by the upgrade hook
+        let context = match self.upgrade_contexts.lock()
         {
-            tracing::error!(?err, "forwarding connection report failed");
-            return Err(Some(ezsockets::CloseFrame{
-                    code   : ezsockets::CloseCode::Error,
-                    reason : String::from("Server internal error.")
-                }));
+            Ok(mut upgrade_contexts) => upgrade_contexts.remove(&info.id).flatten(),
+            Err(_) => { tracing::error!("upgrade contexts poisoned"); None }
         };
 
+        // assign the session's generation
+        self.last_generation += 1;
+        let generation = self.last_generation;
+
+        // collect the session's connection details
+        let connection_details = ConnectionDetails{
+                client_id        : info.id,
+                env_type         : info.client_env_type,
+                peer_addr        : address,
+                tls_info         : self.tls_infos.get(&address),
+                connected_at     : std::time::SystemTime::now(),
+                protocol_version : self.protocol_version,
+            };
+        let connected_at = connection_details.connected_at;
+        let tls_info = connection_details.tls_info.clone();
+
+        // report the new connection
+        // - if the connect message will arrive in the first binary frame, the session handler reports the connection
+        let connect_reported = Arc::new(AtomicBool::new(false));
+        if let Some(connect_msg) = info.connect_msg
+        {
+            let report = ServerReport::<Channel::ConnectMsg>::Connected(connection_details.clone(), connect_msg);
+            if let Err(err) = self.server_event_sender.send(
+                    SessionSourceMsg::new(info.id, generation, ServerEventFrom::<Channel>::Report(report))
+                )
+            {
+                tracing::error!(?err, "forwarding connection report failed");
+                return Err(Some(ezsockets::CloseFrame{
+                        code   : ezsockets::CloseCode::Error,
+                        reason : String::from("Server internal error.")
+                    }));
+            };
+            connect_reported.store(true, Ordering::Release);
+        }
+        let session_ready = connect_reported.load(Ordering::Acquire);
+
         // increment the connection counter now so the updated value is available asap
         self.connection_counter.increment();
+        self.observer.on_connect(info.id);
+
+        // prepare the client's resend log
+        // - messages are held until the client reports the last message it received
+        let resend_log = self.prepare_resend_log(info.id, info.msg_format);
 
         // make a session
         let session_id        = info.id;
         let server_event_sender = self.server_event_sender.clone();
         let max_msg_size      = self.config.max_msg_size;
+        let max_stream_size   = self.config.max_stream_size;
+        let stream_timeout    = self.config.stream_timeout;
         let rate_limit_config = self.config.rate_limit_config.clone();
+        let rate_limit_policy = self.config.rate_limit_policy;
+        let max_concurrent_requests = self.config.max_concurrent_requests_per_session;
+        let on_send           = self.config.on_send.clone();
+        let on_send_clone     = on_send.clone();
+        let on_recv           = self.config.on_recv.clone();
+        let payload_cipher    = self.config.payload_encryption.clone();
+        let payload_compression = self.config.payload_compression.clone();
+        let topic_registry    = self.topic_registry.clone();
+        let closed_by_server  = Arc::new(AtomicBool::new(false));
+        let closed_by_server_clone = closed_by_server.clone();
+        let recv_activity     = RecvActivity::new();
+        let recv_activity_clone = recv_activity.clone();
+        let keepalive_timeout = match info.client_env_type
+        {
+            EnvType::Native => self.config.keepalive_timeout,
+            EnvType::Wasm   => self.config.wasm_keepalive_timeout.unwrap_or(self.config.keepalive_timeout),
+        };
+        let heartbeat_interval = match info.client_env_type
+        {
+            EnvType::Native => self.config.heartbeat_interval,
+            EnvType::Wasm   => self.config.wasm_heartbeat_interval.unwrap_or(self.config.heartbeat_interval),
+        };
+        let pong_tracker      = match info.msg_format
+        {
+            MsgFormat::Json => None,
+            _               => self.config.max_missed_pongs.map(PongTracker::new),
+        };
+        let pings_client      = pong_tracker.is_some();
+        let connect_reported_clone = connect_reported.clone();
+        let session_span      = self.span.clone();
+        let msg_format        = info.msg_format;
+        let observer          = self.observer.clone();
+        let observer_clone    = observer.clone();
+        let inbound_middleware = self.inbound_middleware.clone();
+        let outbound_queue    = Arc::new(Mutex::new(OutboundQueue::default()));
+        let outbound_queue_clone = outbound_queue.clone();
+        let outstanding_requests = Arc::new(AtomicU32::new(0));
+        let outstanding_requests_clone = outstanding_requests.clone();
+        let rate_limit_tracker = Arc::new(Mutex::new(RateLimitTracker::new(rate_limit_config)));
+        let rate_limit_tracker_clone = rate_limit_tracker.clone();
 
         let session = ezsockets::Session::create(
                 move |session|
                 {
                     // prep client request rejector
                     let session_clone = session.clone();
+                    let outbound_queue = outbound_queue_clone.clone();
                     let request_rejector =
                         move |request_id: u64|
                         {
-                            reject_client_request::<Channel>(&session_clone, session_id, request_id);
+                            reject_client_request::<Channel>(
+                                    &session_clone,
+                                    &outbound_queue,
+                                    &on_send,
+                                    &observer,
+                                    msg_format,
+                                    session_id,
+                                    request_id
+                                );
                         };
 
                     // make session handler
                     SessionHandler::<Channel>{
                             id: session_id,
+                            generation,
+                            span: session_span,
                             session,
                             server_event_sender,
                             max_msg_size,
+                            on_send: on_send_clone,
+                            on_recv,
+                            payload_cipher,
+                            payload_compression,
+                            observer: observer_clone,
+                            inbound_middleware,
                             client_env_type: info.client_env_type,
-                            rate_limit_tracker: RateLimitTracker::new(rate_limit_config),
+                            connection_details,
+                            msg_format,
+                            rate_limit_tracker: rate_limit_tracker_clone,
+                            rate_limit_policy,
+                            rate_limit_dropped: 0,
+                            rate_limit_report_scheduled: false,
+                            last_request_id: None,
+                            max_concurrent_requests,
+                            outstanding_requests: outstanding_requests_clone,
+                            outbound_queue: outbound_queue_clone,
+                            resend_log,
+                            stream_reassembler: StreamReassembler::new(max_stream_size, stream_timeout),
+                            topic_registry,
                             request_rejector: Arc::new(request_rejector),
                             death_signal: Arc::new(AtomicBool::new(false)),
+                            closed_by_server: closed_by_server_clone,
+                            connect_reported: connect_reported_clone,
+                            recv_activity: recv_activity_clone,
+                            pong_tracker,
+                            next_ping_id: 0u64,
                         }
                 },
                 session_id,
                 socket
             );
 
+        // ping the client at each heartbeat to detect missed pongs
+        if pings_client { spawn_session_heartbeat(session.clone(), heartbeat_interval); }
+
         // register the session
-        self.session_registry.insert(info.id, session.clone());
+        self.session_registry.insert(
+                info.id,
+                SessionEntry{
+                    session: session.clone(),
+                    closed_by_server,
+                    connect_reported,
+                    msg_format,
+                    generation,
+                    recv_activity,
+                    keepalive_timeout,
+                    outbound_queue: outbound_queue.clone(),
+                    outstanding_requests,
+                    rate_limit_tracker,
+                }
+            );
+        match self.session_info.write()
+        {
+            Ok(mut session_info) =>
+            {
+                session_info.insert(
+                        info.id,
+                        SessionInfo{
+                            connected_at : connected_at,
+                            auth_request : info.auth_request,
+                            peer_addr    : address,
+                            tls_info     : tls_info,
+                            env_type     : info.client_env_type,
+                            generation   : generation,
+                            context      : context,
+                            tag          : None,
+                        }
+                    );
+            }
+            Err(_) => tracing::error!(info.id, "session info registry poisoned"),
+        }
+
+        // tell the client its session is ready
+        // - if the connect message will arrive in the first binary frame, the session handler does this
+        if session_ready
+        {
+            self.send_to_session(
+                    &session,
+                    &outbound_queue,
+                    info.id,
+                    msg_format,
+                    &ClientMetaEventFrom::<Channel>::Ready(self.config.max_msg_size)
+                );
+        }
+
+        // ask the client to resume its message sequence
+        if self.resend_logs.contains_key(&info.id)
+        {
+            self.send_to_session(&session, &outbound_queue, info.id, msg_format, &ClientMetaEventFrom::<Channel>::ResumeRequest);
+        }
 
         Ok(session)
     }
@@ -137,19 +561,60 @@ impl<Channel: ChannelPack> ezsockets::ServerExt for ConnectionHandler<Channel>
     /// Responds to session disconnects.
     async fn on_disconnect(
         &mut self,
-        id      : SessionID,
-        _reason : Result<Option<ezsockets::CloseFrame>, ezsockets::Error>
+        id     : SessionID,
+        reason : Result<Option<ezsockets::CloseFrame>, ezsockets::Error>
     ) -> Result<(), ezsockets::Error>
     {
+        let span = self.span.clone();
+        let _entered = span.enter();
+
         // unregister session
         tracing::info!(id, "unregistering session");
         self.connection_counter.decrement();
-        self.session_registry.remove(&id);
+        self.observer.on_disconnect(id);
+        let (closed_by_server, connect_reported, generation, recv_activity, keepalive_timeout) = self.session_registry
+            .remove(&id)
+            .map(|entry| (
+                    entry.closed_by_server.load(Ordering::Acquire),
+                    entry.connect_reported.load(Ordering::Acquire),
+                    entry.generation,
+                    Some(entry.recv_activity),
+                    entry.keepalive_timeout,
+                ))
+            .unwrap_or_default();
+
+        // clean up session metadata
+        match self.session_info.write()
+        {
+            Ok(mut session_info) => { session_info.remove(&id); }
+            Err(_) => tracing::error!(id, "session info registry poisoned"),
+        }
+
+        // clean up topic subscriptions
+        match self.topic_registry.lock()
+        {
+            Ok(mut topic_registry) => topic_registry.remove_session(id),
+            Err(_) => tracing::error!(id, "topic registry poisoned"),
+        }
+
+        // retain the client's resend log in case it reconnects
+        if let Some(resend_log) = self.resend_logs.get(&id)
+        {
+            match resend_log.lock()
+            {
+                Ok(mut resend_log) => resend_log.end_session(),
+                Err(_) => tracing::error!(id, "resend log poisoned"),
+            }
+        }
+        self.prune_resend_logs();
 
         // send disconnect report
-        let report = ServerReport::<Channel::ConnectMsg>::Disconnected;
+        // - skip it if the session disconnected before its connection was reported
+        if !connect_reported { tracing::debug!(id, "session disconnected before sending its connect message"); return Ok(()); }
+        let reason = disconnect_reason(closed_by_server, recv_activity.as_ref(), keepalive_timeout, &reason);
+        let report = ServerReport::<Channel::ConnectMsg>::Disconnected(reason);
         if let Err(err) = self.server_event_sender.send(
-                SessionSourceMsg::new(id, ServerEventFrom::<Channel>::Report(report))
+                SessionSourceMsg::new(id, generation, ServerEventFrom::<Channel>::Report(report))
             )
         {
             tracing::error!(?err, "forwarding disconnect report failed");
@@ -162,14 +627,57 @@ impl<Channel: ChannelPack> ezsockets::ServerExt for ConnectionHandler<Channel>
     /// Responds to calls to the server connected to this handler (i.e. ezsockets::Server::call()).
     async fn on_call(
         &mut self,
-        session_msg: SessionTargetMsg<SessionID, SessionCommand<Channel>>
+        command: HandlerCommand<Channel>
     ) -> Result<(), ezsockets::Error>
     {
+        let span = self.span.clone();
+        let _entered = span.enter();
+
+        // handle commands for the connection handler itself
+        let session_msg = match command
+        {
+            HandlerCommand::<Channel>::Session(session_msg) => session_msg,
+            HandlerCommand::<Channel>::Control(ControlCommand::<Channel>::SendResponses(responses)) =>
+            {
+                self.send_responses(responses);
+                return Ok(());
+            }
+            HandlerCommand::<Channel>::Control(ControlCommand::<Channel>::DebugSnapshot(snapshot_sender)) =>
+            {
+                if let Err(_) = snapshot_sender.send(self.debug_snapshot())
+                { tracing::debug!("dropping debug snapshot, the requester is gone"); }
+                return Ok(());
+            }
+        };
+
+        // send one-shot messages through the target client's resend log (if it has one)
+        // - this happens even if the client is disconnected, so the message can be resent when it reconnects
+        let session_msg = match session_msg
+        {
+            SessionTargetMsg{ id, msg: SessionCommand::<Channel>::Send(ClientMetaEventFrom::<Channel>::Msg(msg), _) }
+                if self.resend_logs.contains_key(&id) =>
+            {
+                self.send_sequenced(id, msg);
+                return Ok(());
+            }
+            SessionTargetMsg{ id, msg: SessionCommand::<Channel>::BroadcastExcept(msg) } =>
+            {
+                self.broadcast_except(id, msg);
+                return Ok(());
+            }
+            session_msg => session_msg,
+        };
+
         // try to get targeted session (ignore if missing)
-        let Some(session) = self.session_registry.get(&session_msg.id)
+        let Some(SessionEntry{ session, closed_by_server, msg_format, generation, outbound_queue, .. }) =
+            self.session_registry.get(&session_msg.id)
         else
         {
             tracing::debug!(session_msg.id, "dropping message sent to unknown session");
+            if let SessionCommand::<Channel>::SendTracked(_, signal_id) = session_msg.msg
+            {
+                report_delivery_receipt::<Channel>(&self.server_event_sender, session_msg.id, 0u64, signal_id, None);
+            }
             return Ok(());
         };
 
@@ -189,28 +697,279 @@ impl<Channel: ChannelPack> ezsockets::ServerExt for ConnectionHandler<Channel>
                     { tracing::debug!("dropping response targeted at dead session"); return Ok(()); }
                 }
 
-                // serialize message
+                // forward server message to target session
                 tracing::trace!(session_msg.id, "sending message to session");
-                let Ok(ser_msg) = bincode::DefaultOptions::new().serialize(&msg_to_send)
-                else { tracing::error!(session_msg.id, "serializing message failed"); return Ok(()); };
+                send_client_meta_event::<Channel>(
+                        session,
+                        outbound_queue,
+                        &self.config.on_send,
+                        &self.observer,
+                        *msg_format,
+                        session_msg.id,
+                        &msg_to_send
+                    );
+            }
+            SessionCommand::<Channel>::SendExpiring(msg_to_send, deadline) =>
+            {
+                // drop the message if it is stale
+                if std::time::Instant::now() > deadline
+                { tracing::debug!(session_msg.id, "dropping expired message"); return Ok(()); }
 
                 // forward server message to target session
-                // - this may fail if the session is disconnected
-                if let Err(_) = session.binary(ser_msg)
-                { tracing::debug!(session_msg.id, "dropping message sent to broken session"); }
+                tracing::trace!(session_msg.id, "sending expiring message to session");
+                send_client_meta_event::<Channel>(
+                        session,
+                        outbound_queue,
+                        &self.config.on_send,
+                        &self.observer,
+                        *msg_format,
+                        session_msg.id,
+                        &msg_to_send
+                    );
+            }
+            SessionCommand::<Channel>::SendTracked(msg_to_send, signal_id) =>
+            {
+                // forward server message to target session
+                tracing::trace!(session_msg.id, signal_id, "sending tracked message to session");
+                let signal = send_client_meta_event::<Channel>(
+                        session,
+                        outbound_queue,
+                        &self.config.on_send,
+                        &self.observer,
+                        *msg_format,
+                        session_msg.id,
+                        &msg_to_send
+                    );
+
+                // report the delivery receipt once the message is written to the session's socket
+                let (id, generation) = (session_msg.id, *generation);
+                match signal
+                {
+                    Some(signal) if signal.status() == ezsockets::MessageStatus::Sending =>
+                    {
+                        self.delivery_receipts.watch(id, generation, signal_id, signal);
+                    }
+                    signal => report_delivery_receipt::<Channel>(&self.server_event_sender, id, generation, signal_id, signal),
+                }
             }
             SessionCommand::<Channel>::Close(close_frame) =>
             {
                 // command the target session to close
                 // - this may fail if the session is disconnected
                 tracing::info!(session_msg.id, "closing session");
+                closed_by_server.store(true, Ordering::Release);
                 if let Err(_) = session.close(Some(close_frame))
                 { tracing::debug!(session_msg.id, "failed closing session"); }
             }
+            SessionCommand::<Channel>::BroadcastExcept(_) => (),  //handled above
         }
 
         Ok(())
     }
 }
 
+impl<Channel: ChannelPack> ConnectionHandler<Channel>
+{
+    /// Get or make the resend log for a new session.
+    /// - Returns `None` if resends are disabled or the session uses the JSON debug format.
+    fn prepare_resend_log(&mut self, id: SessionID, msg_format: MsgFormat) -> Option<Arc<Mutex<ResendLog>>>
+    {
+        if self.config.resend_buffer_size == 0 { return None; }
+        if msg_format == MsgFormat::Json { self.resend_logs.remove(&id); return None; }
+        self.prune_resend_logs();
+
+        let resend_buffer_size = self.config.resend_buffer_size;
+        let resend_log = self.resend_logs
+            .entry(id)
+            .or_insert_with(|| Arc::new(Mutex::new(ResendLog::new(resend_buffer_size))))
+            .clone();
+
+        match resend_log.lock()
+        {
+            Ok(mut resend_log) => resend_log.start_session(),
+            Err(_) => tracing::error!(id, "resend log poisoned"),
+        }
+
+        Some(resend_log)
+    }
+
+    /// Discard the resend logs of clients that have been disconnected for too long.
+    fn prune_resend_logs(&mut self)
+    {
+        let resend_timeout = self.config.resend_timeout;
+        self.resend_logs.retain(
+                |_, resend_log|
+                {
+                    let Ok(resend_log) = resend_log.lock() else { return false; };
+                    !resend_log.is_expired(resend_timeout)
+                }
+            );
+    }
+
+    /// Send a one-shot message through a client's resend log.
+    fn send_sequenced(&self, id: SessionID, msg: Channel::ServerMsg)
+    {
+        let Some(resend_log) = self.resend_logs.get(&id) else { return; };
+        let Ok(mut resend_log) = resend_log.lock() else { tracing::error!(id, "resend log poisoned"); return; };
+
+        // serialize message
+        tracing::trace!(id, "sending sequenced message to session");
+        let seq = resend_log.reserve_seq();
+        let Ok(mut ser_msg) = bincode::DefaultOptions::new().serialize(&ClientMetaEventFrom::<Channel>::SeqMsg(msg, seq))
+        else { tracing::error!(id, "serializing message failed"); return; };
+        apply_bytes_hook(&self.config.on_send, &mut ser_msg);
+
+        // log the message, then forward it to the target session if the client is ready to receive it
+        // - this is done inside the resend log lock so messages stay ordered with resent messages
+        if !resend_log.push(seq, ser_msg.clone()) { return; }
+        let Some(SessionEntry{ session, msg_format, outbound_queue, .. }) = self.session_registry.get(&id)
+        else { return; };
+        let size = ser_msg.len();
+        match send_session_frame(session, outbound_queue, *msg_format, ser_msg)
+        {
+            Ok(_)   => self.observer.on_msg_out(id, size),
+            Err(()) => tracing::debug!(id, "dropping message sent to broken session"),
+        }
+    }
+
+    /// Send a one-shot message to all connected sessions except one.
+    /// - The message is serialized once for all sessions that don't need their own copy (sessions with resend logs
+    ///   and sessions that use the JSON debug format).
+    fn broadcast_except(&self, exclude: SessionID, msg: Channel::ServerMsg)
+    {
+        let meta_msg = ClientMetaEventFrom::<Channel>::Msg(msg.clone());
+        let mut ser_msg: Option<Vec<u8>> = None;
+        tracing::trace!(exclude, "broadcasting message to sessions");
+
+        for (id, SessionEntry{ session, msg_format, outbound_queue, .. }) in self.session_registry.iter()
+        {
+            if *id == exclude { continue; }
+
+            // handle sessions that need their own copy of the message
+            if self.resend_logs.contains_key(id)
+            {
+                self.send_sequenced(*id, msg.clone());
+                continue;
+            }
+            if *msg_format == MsgFormat::Json
+            {
+                self.send_to_session(session, outbound_queue, *id, *msg_format, &meta_msg);
+                continue;
+            }
+
+            // serialize the message on first use
+            let bytes = match &ser_msg
+            {
+                Some(bytes) => bytes.clone(),
+                None =>
+                {
+                    let Ok(mut bytes) = bincode::DefaultOptions::new().serialize(&meta_msg)
+                    else { tracing::error!("serializing broadcast message failed"); return; };
+                    apply_bytes_hook(&self.config.on_send, &mut bytes);
+                    ser_msg = Some(bytes.clone());
+                    bytes
+                }
+            };
+
+            // forward the message to the session
+            // - this may fail if the session is disconnected
+            let size = bytes.len();
+            match send_session_frame(session, outbound_queue, *msg_format, bytes)
+            {
+                Ok(_)   => self.observer.on_msg_out(*id, size),
+                Err(()) => tracing::debug!(id, "dropping message sent to broken session"),
+            }
+        }
+    }
+
+    /// Send a batch of responses to their sessions.
+    /// - Responses targeted at dead sessions are dropped (see [`SessionCommand::Send`]).
+    fn send_responses(&self, responses: Vec<(SessionID, ClientMetaEventFrom<Channel>, SessionDeathSignal)>)
+    {
+        for (id, response, death_signal) in responses
+        {
+            if death_signal.is_dead()
+            { tracing::debug!(id, "dropping response targeted at dead session"); continue; }
+
+            let Some(SessionEntry{ session, msg_format, outbound_queue, .. }) = self.session_registry.get(&id)
+            else { tracing::debug!(id, "dropping response sent to unknown session"); continue; };
+
+            tracing::trace!(id, "sending response to session");
+            self.send_to_session(session, outbound_queue, id, *msg_format, &response);
+        }
+    }
+
+    /// Take a snapshot of the handler's state.
+    fn debug_snapshot(&self) -> ServerDebugSnapshot
+    {
+        let session_info = match self.session_info.read()
+        {
+            Ok(session_info) => Some(session_info),
+            Err(_) => { tracing::error!("session info registry poisoned"); None }
+        };
+        let topic_registry = match self.topic_registry.lock()
+        {
+            Ok(topic_registry) => Some(topic_registry),
+            Err(_) => { tracing::error!("topic registry poisoned"); None }
+        };
+
+        let mut sessions: Vec<SessionDebugSnapshot> = self.session_registry
+            .iter()
+            .map(
+                |(id, entry)|
+                {
+                    let (resend_buffered, resend_awaiting_resume) = self.resend_logs
+                        .get(id)
+                        .and_then(|resend_log| resend_log.lock().ok())
+                        .map_or((0, false), |resend_log| (resend_log.len(), resend_log.is_awaiting_resume()));
+                    let (rate_limit_count, rate_limit_bytes) = entry.rate_limit_tracker
+                        .lock()
+                        .map_or((0, 0), |tracker| (tracker.period_count(), tracker.period_bytes()));
+                    let outbound_queued = entry.outbound_queue
+                        .lock()
+                        .map_or(0, |mut outbound_queue| outbound_queue.len());
+
+                    SessionDebugSnapshot{
+                        id                     : *id,
+                        generation             : entry.generation,
+                        env_type               : session_info.as_ref().and_then(|info| info.get(id)).map(|info| info.env_type),
+                        json_format            : entry.msg_format == MsgFormat::Json,
+                        text_frames            : entry.msg_format == MsgFormat::Base64,
+                        connect_reported       : entry.connect_reported.load(Ordering::Acquire),
+                        closed_by_server       : entry.closed_by_server.load(Ordering::Acquire),
+                        num_topics             : topic_registry.as_ref().map_or(0, |topics| topics.num_topics(*id)),
+                        resend_buffered,
+                        resend_awaiting_resume,
+                        pending_requests       : entry.outstanding_requests.load(Ordering::Acquire),
+                        rate_limit_count,
+                        rate_limit_bytes,
+                        outbound_queued,
+                    }
+                }
+            )
+            .collect();
+        sessions.sort_unstable_by_key(|session| session.id);
+
+        ServerDebugSnapshot{
+            uptime          : self.started_at.elapsed(),
+            num_connections : self.connection_counter.load(),
+            queued_events   : self.server_event_sender.len(),
+            num_resend_logs : self.resend_logs.len(),
+            sessions,
+        }
+    }
+
+    /// Send a client meta event to a session.
+    fn send_to_session(
+        &self,
+        session        : &ezsockets::Session<SessionID, SessionCall>,
+        outbound_queue : &Mutex<OutboundQueue>,
+        id             : SessionID,
+        msg_format     : MsgFormat,
+        msg            : &ClientMetaEventFrom<Channel>
+    ){
+        send_client_meta_event::<Channel>(session, outbound_queue, &self.config.on_send, &self.observer, msg_format, id, msg);
+    }
+}
+
 //-------------------------------------------------------------------------------------------------------------------
@@ -8,17 +8,16 @@ use serde::Deserialize;
 use core::fmt::Debug;
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
-fn check_protocol_version<'a>(
-    query_element    : Option<(Cow<str>, Cow<str>)>,
-    protocol_version : &'static str
-) -> Result<(), &'static str>
+fn extract_protocol_version<'a>(
+    query_element: Option<(Cow<'a, str>, Cow<'a, str>)>,
+) -> Result<Cow<'a, str>, &'static str>
 {
     // get query element
     let Some((key, value)) = query_element
@@ -32,11 +31,18 @@ fn check_protocol_version<'a>(
     if value.len() > 20
     { tracing::trace!("version too big"); return Err("Version oversized."); };
 
-    // check protocol version
+    Ok(value)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn protocol_version_matches(value: &str, protocol_version: &'static str) -> bool
+{
     if value != protocol_version
-    { tracing::trace!(?value, protocol_version, "version mismatch"); return Err("Version mismatch."); };
+    { tracing::trace!(?value, protocol_version, "version mismatch"); return false; };
 
-    Ok(())
+    true
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -86,30 +92,25 @@ fn deserialize_authentication<'a>(
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
-fn validate_authentication<'a>(
-    query_element : Option<(Cow<str>, Cow<str>)>,
-    authenticator : &Authenticator
-) -> Result<(), &'static str>
+fn authentication_is_valid(auth_request: &AuthRequest, authenticator: &RwLock<Authenticator>) -> Result<bool, ()>
 {
-    // deserialize
-    let auth_request = deserialize_authentication(&query_element)?;
+    let Ok(authenticator) = authenticator.read()
+    else { tracing::error!("authenticator poisoned"); return Err(()); };
 
-    // validate
-    if !authenticate(&auth_request, authenticator)
-    { tracing::trace!("invalid auth message (verification)"); return Err("Auth message invalid."); };
+    if !authenticate(auth_request, &authenticator)
+    { tracing::trace!("invalid auth message (verification)"); return Ok(false); };
 
-    Ok(())
+    Ok(true)
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
-fn try_extract_client_id<'a>(
+fn try_extract_auth_request<'a>(
     query_element: Option<(Cow<str>, Cow<str>)>,
-) -> Result<u128, &'static str>
+) -> Result<AuthRequest, &'static str>
 {
-    let auth_request = deserialize_authentication(&query_element)?;
-    Ok(auth_request.client_id())
+    deserialize_authentication(&query_element)
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -165,15 +166,47 @@ where
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Returns the message format selected by the query element.
+/// - The JSON debug format is only available with the `debug-json` feature.
+fn try_extract_msg_format<'a>(
+    query_element : Option<(Cow<str>, Cow<str>)>,
+) -> Result<MsgFormat, &'static str>
+{
+    // extract message format (optional)
+    let Some((key, value)) = query_element else { return Ok(MsgFormat::Binary); };
+
+    // check key
+    if key != FORMAT_MSG_KEY
+    { tracing::trace!("invalid message format (unknown key)"); return Err("Excess query elements."); };
+
+    // get value
+    match value.as_ref()
+    {
+        BASE64_MSG_FORMAT => Ok(MsgFormat::Base64),
+        #[cfg(feature = "debug-json")]
+        JSON_MSG_FORMAT => Ok(MsgFormat::Json),
+        _ => { tracing::trace!("invalid message format (unknown)"); Err("Unknown message format.") }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks the number of connections to a server.
+///
+/// The counter is shared, so clones observe the same count. See [`Server::connection_counter_handle()`].
 #[derive(Debug, Clone)]
-pub(crate) struct ConnectionCounter
+pub struct ConnectionCounter
 {
     counter: Arc<AtomicU64>,
+    /// notified whenever a connection closes
+    closed: Arc<tokio::sync::Notify>,
 }
 
 impl ConnectionCounter
 {
-    pub(crate) fn load(&self) -> u64
+    /// Get the current number of connections.
+    pub fn load(&self) -> u64
     {
         self.counter.load(Ordering::Relaxed)
     }
@@ -190,22 +223,72 @@ impl ConnectionCounter
         {
             self.increment();
         }
+        self.closed.notify_waiters();
+    }
+
+    /// Wait until there are no connections.
+    pub(crate) async fn wait_for_zero(&self)
+    {
+        loop
+        {
+            // register for notifications before checking the count so a concurrent decrement can't be missed
+            let closed = self.closed.notified();
+            tokio::pin!(closed);
+            closed.as_mut().enable();
+            if self.load() == 0 { return; }
+            closed.await;
+        }
     }
 }
 
-impl Default for ConnectionCounter { fn default() -> Self { Self{ counter: Arc::new(AtomicU64::new(0u64)) } } }
+impl Default for ConnectionCounter
+{
+    fn default() -> Self
+    {
+        Self{ counter: Arc::new(AtomicU64::new(0u64)), closed: Arc::new(tokio::sync::Notify::new()) }
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
 #[derive(Debug)]
 pub(crate) struct ConnectionPrevalidator
 {
-    pub(crate) protocol_version   : &'static str,
-    pub(crate) authenticator      : Authenticator,
-    pub(crate) max_connections    : u32,
-    pub(crate) max_msg_size       : u32,
-    pub(crate) heartbeat_interval : Duration,
-    pub(crate) keepalive_timeout  : Duration,
+    pub(crate) protocol_version        : &'static str,
+    /// the current authenticator (shared with the server so it can be replaced)
+    pub(crate) authenticator           : Arc<RwLock<Authenticator>>,
+    pub(crate) max_connections         : u32,
+    pub(crate) load_shed               : Option<Arc<dyn LoadShedFn>>,
+    pub(crate) max_msg_size            : u32,
+    pub(crate) heartbeat_interval      : Duration,
+    pub(crate) keepalive_timeout       : Duration,
+    pub(crate) wasm_heartbeat_interval : Duration,
+    pub(crate) wasm_keepalive_timeout  : Duration,
+    /// indicates the server is accepting new connections (shared with the server)
+    pub(crate) accepting               : Arc<AtomicBool>,
+    /// client ids that may not connect (shared with the server)
+    pub(crate) ban_list                : Arc<RwLock<BanList>>,
+    /// origins that may connect (`None` allows all origins)
+    pub(crate) allowed_origins         : Option<Vec<String>>,
+    /// the server's shared app context
+    pub(crate) context                 : ServerContext,
+    /// hook that inspects connection requests after they pass validation
+    pub(crate) on_upgrade              : Option<Arc<dyn UpgradeHookFn>>,
+    /// session contexts returned by the upgrade hook, waiting for their connections (shared with the connection
+    /// handler)
+    pub(crate) upgrade_contexts        : UpgradeContexts,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A connection request that passed prevalidation.
+#[derive(Debug)]
+pub(crate) struct PrevalidatedRequest
+{
+    /// the client's environment type
+    pub(crate) client_env_type: EnvType,
+    /// the client's id (`None` if the request will be rejected by the connection handler)
+    pub(crate) client_id: Option<u128>,
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -214,8 +297,15 @@ pub(crate) fn prevalidate_connection_request(
     request         : &ezsockets::Request,
     num_connections : &ConnectionCounter,
     prevalidator    : &ConnectionPrevalidator,
-) -> Result<EnvType, (axum::http::StatusCode, &'static str)>
+) -> Result<PrevalidatedRequest, (axum::http::StatusCode, &'static str)>
 {
+    // check if the server is accepting connections
+    if !prevalidator.accepting.load(Ordering::Acquire)
+    {
+        tracing::trace!("server is not accepting connections, dropping request...");
+        return Err((axum::http::StatusCode::SERVICE_UNAVAILABLE, "Maintenance."));
+    }
+
     // check max connection count
     // - this is an approximate test since the counter is updated async
     if num_connections.load() >= prevalidator.max_connections as u64
@@ -224,6 +314,27 @@ pub(crate) fn prevalidate_connection_request(
         return Err((axum::http::StatusCode::SERVICE_UNAVAILABLE, "Max connections reached."));
     };
 
+    // check if the server is overloaded
+    if let Some(load_shed) = &prevalidator.load_shed
+    {
+        if (load_shed)(&prevalidator.context)
+        {
+            tracing::trace!("server is busy, dropping request...");
+            return Err((axum::http::StatusCode::SERVICE_UNAVAILABLE, "Server busy."));
+        }
+    }
+
+    // check the request's origin
+    // - requests without an origin are allowed since only browsers are expected to send one
+    if let (Some(allowed_origins), Some(origin)) = (&prevalidator.allowed_origins, request.headers().get("origin"))
+    {
+        if !allowed_origins.iter().any(|allowed| allowed.as_bytes() == origin.as_bytes())
+        {
+            tracing::trace!(?origin, "origin not allowed, dropping connection request...");
+            return Err((axum::http::StatusCode::FORBIDDEN, "Origin not allowed."));
+        }
+    }
+
     // parse request query
     let Some(query) = request.uri().query()
     else
@@ -233,28 +344,107 @@ pub(crate) fn prevalidate_connection_request(
     };
     let mut query_elements_iterator = form_urlencoded::parse(query.as_bytes());
 
-    // check if there is a protocol version mismatch
-    let _ = check_protocol_version(query_elements_iterator.next(), prevalidator.protocol_version)
+    // get the protocol version
+    let version = extract_protocol_version(query_elements_iterator.next())
         .map_err(|reason| (axum::http::StatusCode::BAD_REQUEST, reason))?;
 
     // check that client env type is present
     let client_env_type = try_extract_client_env(query_elements_iterator.next())
         .map_err(|reason| (axum::http::StatusCode::BAD_REQUEST, reason))?;
 
+    // accept the upgrade if there is a protocol version mismatch
+    // - the connection handler rejects it with a close frame, since clients can't observe HTTP rejections
+    if !protocol_version_matches(&version, prevalidator.protocol_version)
+    {
+        tracing::trace!("version mismatch, deferring rejection to the connection handler...");
+        return Ok(PrevalidatedRequest{ client_env_type, client_id: None });
+    }
+
     // validate authentication
-    validate_authentication(query_elements_iterator.next(), &prevalidator.authenticator)
+    // - invalid authentication is rejected by the connection handler with a close frame (see above)
+    let auth_request = deserialize_authentication(&query_elements_iterator.next())
         .map_err(|reason| (axum::http::StatusCode::BAD_REQUEST, reason))?;
+    match authentication_is_valid(&auth_request, &prevalidator.authenticator)
+    {
+        Ok(true) => (),
+        Ok(false) =>
+        {
+            tracing::trace!("invalid authentication, deferring rejection to the connection handler...");
+            return Ok(PrevalidatedRequest{ client_env_type, client_id: None });
+        }
+        Err(()) => return Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, "Server internal error.")),
+    }
+
+    // check if the client is banned
+    let Ok(ban_list) = prevalidator.ban_list.read()
+    else
+    {
+        tracing::error!("ban list poisoned, dropping connection request...");
+        return Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, "Server internal error."));
+    };
+    if ban_list.is_banned(auth_request.client_id())
+    {
+        tracing::trace!(client_id = auth_request.client_id(), "client is banned, dropping connection request...");
+        return Err((axum::http::StatusCode::FORBIDDEN, "Banned."));
+    }
+    drop(ban_list);
 
     // validate size of connect message
     // - don't check if deserializable (too expensive for valid connections)
-    check_connect_message_size(query_elements_iterator.next(), prevalidator.max_msg_size)
-        .map_err(|reason| (axum::http::StatusCode::BAD_REQUEST, reason))?;
+    // - the connect message is absent if the client will send it in the first binary frame
+    let mut next_element = query_elements_iterator.next();
+    if next_element.as_ref().map_or(false, |(key, _)| key == CONNECT_MSG_KEY)
+    {
+        check_connect_message_size(next_element, prevalidator.max_msg_size)
+            .map_err(|reason| (axum::http::StatusCode::BAD_REQUEST, reason))?;
+        next_element = query_elements_iterator.next();
+    }
+
+    // validate the message format
+    if next_element.is_some()
+    {
+        try_extract_msg_format(next_element)
+            .map_err(|reason| (axum::http::StatusCode::BAD_REQUEST, reason))?;
+        next_element = query_elements_iterator.next();
+    }
 
     // there should be no more query elements
-    let None = query_elements_iterator.next()
+    let None = next_element
     else { return Err((axum::http::StatusCode::PAYLOAD_TOO_LARGE, "Excess query elements.")); };
 
-    Ok(client_env_type)
+    Ok(PrevalidatedRequest{ client_env_type, client_id: Some(auth_request.client_id()) })
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Run the app's upgrade hook on a prevalidated connection request.
+/// - Records the session context returned by the hook so the connection handler can attach it to the session.
+/// - Returns `Err` with the hook's reason if the hook rejects the request.
+pub(crate) fn run_upgrade_hook(
+    request      : &ezsockets::Request,
+    prevalidated : &PrevalidatedRequest,
+    prevalidator : &ConnectionPrevalidator,
+) -> Result<(), String>
+{
+    // requests that will be rejected by the connection handler aren't shown to the app
+    let Some(client_id) = prevalidated.client_id else { return Ok(()); };
+    let Some(on_upgrade) = &prevalidator.on_upgrade else { return Ok(()); };
+
+    // let the app inspect the request
+    let context = (on_upgrade)(&prevalidator.context, request)
+        .map_err(|reason| { tracing::trace!(client_id, ?reason, "upgrade hook rejected connection request"); reason })?;
+
+    // record the session context
+    // - always record it, so context from an abandoned upgrade is never attached to a later session
+    let Ok(mut upgrade_contexts) = prevalidator.upgrade_contexts.lock()
+    else
+    {
+        tracing::error!("upgrade contexts poisoned, dropping connection request...");
+        return Err(String::from("Server internal error."));
+    };
+    upgrade_contexts.insert(client_id, context);
+
+    Ok(())
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -264,7 +454,11 @@ pub(crate) struct ConnectionInfo<ConnectMsg>
 {
     pub(crate) client_env_type : EnvType,
     pub(crate) id              : u128,
-    pub(crate) connect_msg     : ConnectMsg,
+    pub(crate) auth_request    : AuthRequest,
+    /// `None` if the connect message will be sent in the first binary frame
+    pub(crate) connect_msg     : Option<ConnectMsg>,
+    /// the session's message format
+    pub(crate) msg_format      : MsgFormat,
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -274,7 +468,9 @@ pub(crate) struct ConnectionInfo<ConnectMsg>
 /// Assumes the request has already been pre-validated.
 pub(crate) fn extract_connection_info<ConnectMsg>(
     request          : &ezsockets::Request,
-    session_registry : &HashMap<SessionID, ezsockets::Session<SessionID, ()>>,
+    protocol_version : &'static str,
+    authenticator    : &RwLock<Authenticator>,
+    session_registry : &HashMap<SessionID, SessionEntry>,
 ) -> Result<ConnectionInfo<ConnectMsg>, Option<ezsockets::CloseFrame>>
 where
     ConnectMsg: for<'de> Deserialize<'de> + 'static,
@@ -283,14 +479,27 @@ where
     let query = request.uri().query().ok_or(None)?;
     let mut query_elements_iterator = form_urlencoded::parse(query.as_bytes());
 
-    // ignore protocol version
-    query_elements_iterator.next();
+    // reject protocol version mismatches
+    // - the prevalidator lets these through so the client can be told why it was rejected
+    let version = extract_protocol_version(query_elements_iterator.next()).map_err(|_| None)?;
+    if !protocol_version_matches(&version, protocol_version)
+    {
+        return Err(Some(CloseReason::VersionMismatch.close_frame("Version mismatch.")));
+    }
 
     // get client's implementation type
     let client_env_type = try_extract_client_env(query_elements_iterator.next()).map_err(|_| None)?;
 
-    // try to get client id
-    let id = try_extract_client_id(query_elements_iterator.next()).map_err(|_| None)?;
+    // get the auth request and reject invalid authentication
+    // - the prevalidator lets these through so the client can be told why it was rejected
+    let auth_request = try_extract_auth_request(query_elements_iterator.next()).map_err(|_| None)?;
+    match authentication_is_valid(&auth_request, authenticator)
+    {
+        Ok(true) => (),
+        Ok(false) => return Err(Some(CloseReason::AuthFailed.close_frame("Auth message invalid."))),
+        Err(()) => return Err(Some(CloseReason::ServerError.close_frame("Server internal error."))),
+    }
+    let id = auth_request.client_id();
 
     // reject connection if client id is already registered as a session
     if session_registry.contains_key(&id)
@@ -303,19 +512,41 @@ where
     }
 
     // try to extract connect message
-    let connect_msg = try_extract_connect_msg(query_elements_iterator.next())
-        .map_err(
-            |reason|
-            Some(ezsockets::CloseFrame{
+    // - the connect message is absent if the client will send it in the first binary frame
+    let mut next_element = query_elements_iterator.next();
+    let mut connect_msg = None;
+    if next_element.as_ref().map_or(false, |(key, _)| key == CONNECT_MSG_KEY)
+    {
+        connect_msg = Some(try_extract_connect_msg(next_element.take())
+            .map_err(
+                |reason|
+                Some(ezsockets::CloseFrame{
+                    code   : ezsockets::CloseCode::Protocol,
+                    reason : String::from(reason)
+                })
+            )?);
+        next_element = query_elements_iterator.next();
+    }
+
+    // get the session's message format
+    // - JSON sessions must send their connect message in the connection request
+    let msg_format = try_extract_msg_format(next_element).map_err(|_| None)?;
+
+    if msg_format == MsgFormat::Json && connect_msg.is_none()
+    {
+        tracing::trace!(id, "received JSON connection request without a connect message");
+        return Err(Some(ezsockets::CloseFrame{
                 code   : ezsockets::CloseCode::Protocol,
-                reason : String::from(reason)
-            })
-        )?;
+                reason : String::from("Connect message missing.")
+            }));
+    }
 
     Ok(ConnectionInfo{
             client_env_type,
             id,
+            auth_request,
             connect_msg,
+            msg_format,
         })
 }
 
@@ -28,3 +28,32 @@ impl std::fmt::Display for ConnectionError
 impl std::error::Error for ConnectionError {}
 
 //-------------------------------------------------------------------------------------------------------------------
+
+/// Errors emitted by [`ServerFactory::try_new_server()`].
+#[derive(Debug)]
+pub enum ServerStartError
+{
+    /// The server's async runtime failed to run a startup task.
+    RuntimeError,
+    /// The server address could not be resolved.
+    InvalidAddress,
+    /// The server's TCP listener could not be bound (e.g. the port is in use).
+    BindFailed(std::io::Error),
+}
+
+impl std::fmt::Display for ServerStartError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let _ = write!(f, "ServerStartError::");
+        match self
+        {
+            ServerStartError::RuntimeError   => write!(f, "RuntimeError"),
+            ServerStartError::InvalidAddress => write!(f, "InvalidAddress"),
+            ServerStartError::BindFailed(err) => write!(f, "BindFailed({err})"),
+        }
+    }
+}
+impl std::error::Error for ServerStartError {}
+
+//-------------------------------------------------------------------------------------------------------------------
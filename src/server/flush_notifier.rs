@@ -0,0 +1,120 @@
+//local shortcuts
+
+//third-party shortcuts
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wraps an accepted connection in order to notify the server whenever the connection finishes flushing a write.
+/// - The server uses this to wake the watcher of tracked messages (see `DeliveryReceiptWatcher`).
+#[derive(Debug)]
+pub(crate) struct FlushNotifyStream<S>
+{
+    /// the wrapped stream
+    inner: S,
+    /// notified when a flush completes (shared by all connections)
+    flushed: Arc<tokio::sync::Notify>,
+}
+
+impl<S> FlushNotifyStream<S>
+{
+    /// Get the wrapped stream.
+    pub(crate) fn get_ref(&self) -> &S
+    {
+        &self.inner
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for FlushNotifyStream<S>
+{
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>>
+    {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for FlushNotifyStream<S>
+{
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>>
+    {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self : Pin<&mut Self>,
+        cx       : &mut Context<'_>,
+        bufs     : &[std::io::IoSlice<'_>]
+    ) -> Poll<std::io::Result<usize>>
+    {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool
+    {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>>
+    {
+        let result = Pin::new(&mut self.inner).poll_flush(cx);
+        if result.is_ready() { self.flushed.notify_one(); }
+        result
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>>
+    {
+        let result = Pin::new(&mut self.inner).poll_shutdown(cx);
+        if result.is_ready() { self.flushed.notify_one(); }
+        result
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wraps a connection acceptor in order to notify the server whenever a connection finishes flushing a write.
+#[derive(Debug, Clone)]
+pub(crate) struct FlushNotifyAcceptor<A>
+{
+    /// the wrapped acceptor
+    inner: A,
+    /// notified when a flush completes (shared by all connections)
+    flushed: Arc<tokio::sync::Notify>,
+}
+
+impl<A> FlushNotifyAcceptor<A>
+{
+    pub(crate) fn new(inner: A, flushed: Arc<tokio::sync::Notify>) -> Self
+    {
+        Self{ inner, flushed }
+    }
+}
+
+impl<A, I, S> axum_server::accept::Accept<I, S> for FlushNotifyAcceptor<A>
+where
+    A: axum_server::accept::Accept<I, S>,
+    A::Future: Send + 'static,
+{
+    type Stream  = FlushNotifyStream<A::Stream>;
+    type Service = A::Service;
+    type Future  = Pin<Box<dyn Future<Output = std::io::Result<(Self::Stream, A::Service)>> + Send>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future
+    {
+        let flushed = self.flushed.clone();
+        let accept = self.inner.accept(stream, service);
+
+        Box::pin(async move {
+                let (stream, service) = accept.await?;
+                Ok((FlushNotifyStream{ inner: stream, flushed }, service))
+            })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
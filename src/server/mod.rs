@@ -1,21 +1,47 @@
 //module tree
+mod accept_limiter;
+mod ban_list;
+#[cfg(feature = "bevy")]
+mod bevy_teardown;
 mod config;
 mod connection_handler;
 mod connection_validation;
 mod errors;
+mod flush_notifier;
 mod request_token;
+mod resend_log;
 mod server;
+mod server_context;
+mod server_debug;
 mod server_event;
+mod server_metrics;
 mod session_handler;
 mod session_utils;
+mod tls_info;
+mod topic_registry;
+mod upgrade_timeout;
 
 //API exports
+pub(crate) use crate::server::accept_limiter::*;
+pub(crate) use crate::server::ban_list::*;
+#[cfg(feature = "bevy")]
+pub use crate::server::bevy_teardown::*;
 pub use crate::server::config::*;
 pub(crate) use crate::server::connection_handler::*;
 pub(crate) use crate::server::connection_validation::*;
+pub use crate::server::connection_validation::ConnectionCounter;
 pub use crate::server::errors::*;
+pub(crate) use crate::server::flush_notifier::*;
 pub use crate::server::request_token::*;
+pub(crate) use crate::server::resend_log::*;
 pub use crate::server::server::*;
+pub use crate::server::server_context::*;
+pub use crate::server::server_debug::*;
 pub use crate::server::server_event::*;
+pub use crate::server::server_metrics::*;
 pub(crate) use crate::server::session_handler::*;
 pub(crate) use crate::server::session_utils::*;
+pub use crate::server::tls_info::SessionTlsInfo;
+pub(crate) use crate::server::tls_info::*;
+pub(crate) use crate::server::topic_registry::*;
+pub(crate) use crate::server::upgrade_timeout::*;
@@ -5,8 +5,9 @@ use crate::*;
 
 //standard shortcuts
 use core::fmt::Debug;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -24,6 +25,32 @@ impl SessionDeathSignal
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Counts a request as outstanding until dropped.
+#[derive(Debug)]
+struct OutstandingRequestGuard
+{
+    outstanding: Arc<AtomicU32>
+}
+
+impl OutstandingRequestGuard
+{
+    fn new(outstanding: Arc<AtomicU32>) -> Self
+    {
+        outstanding.fetch_add(1, Ordering::AcqRel);
+        Self{ outstanding }
+    }
+}
+
+impl Drop for OutstandingRequestGuard
+{
+    fn drop(&mut self)
+    {
+        self.outstanding.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Wrapper trait for `Fn(u64)`.
 pub(crate) trait RequestRejectorFn: Fn(u64) + Send + Sync + 'static {}
 impl<F> RequestRejectorFn for F where F: Fn(u64) + Send + Sync + 'static {}
@@ -41,12 +68,17 @@ impl std::fmt::Debug for RequestRejectorFnT
 /// When dropped without using [`Server::respond()`] or [`Server::ack()`], a [`ClientEvent::Reject`] message will be
 /// sent to the client. If the client is disconnected, then the rejection message will fail and the client will
 /// see their request status change to [`RequestStatus::ResponseLost`].
+///
+/// Tokens are single-use. They are not `Clone`, and [`Server::respond()`], [`Server::ack()`], and
+/// [`Server::reject()`] take the token by value, so a request cannot be answered more than once.
 pub struct RequestToken
 {
     client_id    : SessionID,
     request_id   : u64,
     rejector     : Option<Arc<dyn RequestRejectorFn>>,
     death_signal : Option<SessionDeathSignal>,
+    received_at  : Instant,
+    _outstanding : OutstandingRequestGuard,
 }
 
 impl RequestToken
@@ -56,14 +88,17 @@ impl RequestToken
         client_id    : SessionID,
         request_id   : u64,
         rejector     : Arc<dyn RequestRejectorFn>,
-        death_signal : Arc<AtomicBool>
+        death_signal : Arc<AtomicBool>,
+        outstanding  : Arc<AtomicU32>,
     ) -> Self
     {
         Self{
             client_id,
             request_id,
             rejector     : Some(rejector),
-            death_signal : Some(SessionDeathSignal::new(death_signal))
+            death_signal : Some(SessionDeathSignal::new(death_signal)),
+            received_at  : Instant::now(),
+            _outstanding : OutstandingRequestGuard::new(outstanding),
         }
     }
 
@@ -79,6 +114,12 @@ impl RequestToken
         self.request_id
     }
 
+    /// Time elapsed since the server received this request.
+    pub fn elapsed(&self) -> Duration
+    {
+        self.received_at.elapsed()
+    }
+
     /// Check if the destination session is dead.
     ///
     /// Request tokens are tied to a specific server session. When a client reconnects they get a new session and
@@ -88,9 +129,29 @@ impl RequestToken
         self.death_signal.as_ref().unwrap().is_dead()
     }
 
+    /// Check if the token has been consumed.
+    ///
+    /// Since tokens are consumed by value, a token you hold will never be consumed. This is provided for defensive
+    /// checks in app code that wraps tokens.
+    pub fn is_consumed(&self) -> bool
+    {
+        self.rejector.is_none()
+    }
+
+    /// Check if the request can still be answered.
+    ///
+    /// Returns `false` once the destination session is dead or the token has been consumed. Use this to skip building
+    /// expensive responses for clients that already disconnected.
+    pub fn is_respondable(&self) -> bool
+    {
+        if self.is_consumed() { return false; }
+        self.death_signal.as_ref().is_some_and(|death_signal| !death_signal.is_dead())
+    }
+
     /// Consume the token, preventing it from sending a rejection message when dropped.
     pub(crate) fn take(mut self) -> (u64, SessionDeathSignal)
     {
+        debug_assert!(!self.is_consumed(), "request token consumed twice");
         let _ = self.rejector.take();
         (self.request_id, self.death_signal.take().unwrap())
     }
@@ -0,0 +1,130 @@
+//local shortcuts
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Buffers recent one-shot messages sent to a client so they can be resent after the client reconnects.
+/// - Messages are numbered with strictly increasing sequence numbers, starting at 1.
+/// - When a session starts, messages are held (logged but not sent) until the client reports the last sequence number
+///   it received.
+#[derive(Debug)]
+pub(crate) struct ResendLog
+{
+    /// max number of messages retained
+    capacity: usize,
+    /// sequence number of the next message
+    next_seq: u64,
+    /// sequence number of the first message sent after the current session started
+    session_start_seq: u64,
+    /// indicates messages are being held until the client resumes
+    awaiting_resume: bool,
+    /// retained messages (sequence number, serialized message)
+    entries: VecDeque<(u64, Vec<u8>)>,
+    /// when the client disconnected (if it is disconnected)
+    disconnected_at: Option<Instant>,
+}
+
+impl ResendLog
+{
+    pub(crate) fn new(capacity: usize) -> Self
+    {
+        Self{
+            capacity,
+            next_seq          : 1u64,
+            session_start_seq : 1u64,
+            awaiting_resume   : true,
+            entries           : VecDeque::default(),
+            disconnected_at   : None,
+        }
+    }
+
+    /// Reserve a sequence number for the next message.
+    pub(crate) fn reserve_seq(&mut self) -> u64
+    {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        seq
+    }
+
+    /// Log a serialized message.
+    /// - Returns `true` if the message should be sent now.
+    pub(crate) fn push(&mut self, seq: u64, bytes: Vec<u8>) -> bool
+    {
+        if self.capacity == 0 { return !self.awaiting_resume; }
+        if self.entries.len() >= self.capacity { self.entries.pop_front(); }
+        self.entries.push_back((seq, bytes));
+        !self.awaiting_resume
+    }
+
+    /// Mark the start of a new session for the client.
+    pub(crate) fn start_session(&mut self)
+    {
+        self.session_start_seq = self.next_seq;
+        self.awaiting_resume = true;
+        self.disconnected_at = None;
+    }
+
+    /// Mark the end of the client's current session.
+    pub(crate) fn end_session(&mut self)
+    {
+        self.awaiting_resume = true;
+        self.disconnected_at = Some(Instant::now());
+    }
+
+    /// Check if the client has been disconnected for longer than the timeout.
+    pub(crate) fn is_expired(&self, timeout: Duration) -> bool
+    {
+        self.disconnected_at.map_or(false, |disconnected_at| disconnected_at.elapsed() >= timeout)
+    }
+
+    /// Get the number of retained messages.
+    pub(crate) fn len(&self) -> usize
+    {
+        self.entries.len()
+    }
+
+    /// Check if messages are being held until the client resumes.
+    pub(crate) fn is_awaiting_resume(&self) -> bool
+    {
+        self.awaiting_resume
+    }
+
+    /// Resume sending messages to the client.
+    /// - `last_received` is the last sequence number received by the client, or `None` if the client has not received
+    ///   any messages from this server.
+    /// - Returns the sequence number preceding the messages that will be resent, and the messages to resend.
+    /// - Returns `None` if the log is not waiting for the client to resume.
+    pub(crate) fn resume(&mut self, last_received: Option<u64>) -> Option<(u64, Vec<Vec<u8>>)>
+    {
+        if !self.awaiting_resume { return None; }
+        self.awaiting_resume = false;
+
+        // choose the first message to resend
+        // - if the client's sequence numbers don't come from this log, then only messages sent since the session
+        //   started are sent
+        // - messages evicted from the log are lost
+        let start_seq = match last_received
+        {
+            Some(last_received) if last_received < self.next_seq => last_received + 1,
+            _ => self.session_start_seq,
+        };
+        let oldest_seq = self.entries.front().map_or(self.next_seq, |(seq, _)| *seq);
+        let start_seq = start_seq.max(oldest_seq);
+
+        let resends = self.entries
+            .iter()
+            .filter(|(seq, _)| *seq >= start_seq)
+            .map(|(_, bytes)| bytes.clone())
+            .collect();
+
+        Some((start_seq - 1, resends))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -2,15 +2,19 @@
 use crate::*;
 
 //third-party shortcuts
+use bincode::Options;
 use axum::response::IntoResponse;
 use enfync::Handle;
 
 //standard shortcuts
 use core::fmt::Debug;
+use std::any::Any;
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::marker::PhantomData;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
 
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
@@ -32,8 +36,8 @@ fn socket_config(prevalidator: &ConnectionPrevalidator, client_env_type: EnvType
         {
             // use a custom Text-based ping message
             ezsockets::SocketConfig{
-                    heartbeat : prevalidator.heartbeat_interval,
-                    timeout   : prevalidator.keepalive_timeout,
+                    heartbeat : prevalidator.wasm_heartbeat_interval,
+                    timeout   : prevalidator.wasm_keepalive_timeout,
                     heartbeat_ping_msg_fn : Arc::new(text_ping_fn)
                 }
         }
@@ -50,34 +54,267 @@ async fn websocket_handler<Channel: ChannelPack>(
     ezsocket_upgrade        : ezsockets::axum::Upgrade,
 ) -> impl axum::response::IntoResponse
 {
-    // prevalidate then prepare upgrade
-    match prevalidate_connection_request(ezsocket_upgrade.request(), &count, &preval)
+    // prevalidate
+    let prevalidated = match prevalidate_connection_request(ezsocket_upgrade.request(), &count, &preval)
     {
-        Ok(client_env_type) => ezsocket_upgrade.on_upgrade_with_config(server, socket_config(&preval, client_env_type)),
-        Err(err) => err.into_response()
+        Ok(prevalidated) => prevalidated,
+        Err(err) => return err.into_response(),
+    };
+
+    // let the app inspect the request
+    if let Err(reason) = run_upgrade_hook(ezsocket_upgrade.request(), &prevalidated, &preval)
+    {
+        return (axum::http::StatusCode::FORBIDDEN, reason).into_response();
+    }
+
+    // prepare upgrade
+    ezsocket_upgrade.on_upgrade_with_config(server, socket_config(&preval, prevalidated.client_env_type))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn bind_listener(address: SocketAddr, backlog: Option<u32>) -> std::io::Result<std::net::TcpListener>
+{
+    // use the OS default backlog
+    let Some(backlog) = backlog else { return std::net::TcpListener::bind(address); };
+
+    // set a custom backlog
+    // - this must be called from within a tokio runtime
+    let socket = match address
+    {
+        SocketAddr::V4(_) => tokio::net::TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => tokio::net::TcpSocket::new_v6()?,
+    };
+    socket.bind(address)?;
+    socket.listen(backlog)?.into_std()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// How long a dropped server's handler thread waits for its sessions to close before stopping.
+const HANDLER_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A dedicated thread that runs the server's connection handler.
+/// - When this is dropped, the thread waits for the server's connections to close (up to
+///   [`HANDLER_SHUTDOWN_TIMEOUT`]), then it is stopped and joined.
+#[derive(Debug)]
+struct HandlerThread
+{
+    /// handle to the thread's runtime
+    runtime_handle: enfync::builtin::native::TokioHandle,
+    /// stops the thread's runtime
+    stop_sender: Option<tokio::sync::oneshot::Sender<()>>,
+    /// the thread
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl HandlerThread
+{
+    fn spawn(connection_counter: ConnectionCounter) -> std::io::Result<Self>
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let runtime_handle = enfync::builtin::native::TokioHandle::from(runtime.handle().clone());
+        let (stop_sender, stop_receiver) = tokio::sync::oneshot::channel::<()>();
+        let thread = std::thread::Builder::new()
+            .name(String::from("simplenet-handler"))
+            .spawn(
+                move ||
+                {
+                    runtime.block_on(async move {
+                            let _ = stop_receiver.await;
+
+                            // keep the handler running so closing sessions can finish disconnecting
+                            if tokio::time::timeout(HANDLER_SHUTDOWN_TIMEOUT, connection_counter.wait_for_zero())
+                                .await
+                                .is_err()
+                            { tracing::warn!("stopping server handler thread with open connections"); }
+                        });
+                }
+            )?;
+
+        Ok(Self{ runtime_handle, stop_sender: Some(stop_sender), thread: Some(thread) })
+    }
+}
+
+impl Drop for HandlerThread
+{
+    fn drop(&mut self)
+    {
+        // stop the runtime
+        // - this cancels the connection handler and any sessions it spawned that are still running
+        if let Some(stop_sender) = self.stop_sender.take() { let _ = stop_sender.send(()); }
+
+        let Some(thread) = self.thread.take() else { return; };
+        if thread.join().is_err() { tracing::error!("server handler thread panicked"); }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn session_ids(session_info: &SessionInfoRegistry) -> Vec<SessionID>
+{
+    match session_info.read()
+    {
+        Ok(session_info) => session_info.keys().copied().collect(),
+        Err(_) => { tracing::error!("session info registry poisoned"); Vec::default() }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn begin_lame_duck<Channel: ChannelPack>(
+    runtime_handle      : &enfync::builtin::native::TokioHandle,
+    accepting           : &AtomicBool,
+    connection_counter  : ConnectionCounter,
+    session_info        : SessionInfoRegistry,
+    client_event_sender : tokio::sync::mpsc::UnboundedSender<HandlerCommand<Channel>>,
+    grace               : Duration,
+) -> LameDuckSignal
+{
+    tracing::info!(?grace, "server entering lame-duck mode");
+    accepting.store(false, Ordering::Release);
+
+    // notify connected clients
+    for id in session_ids(&session_info)
+    {
+        if let Err(err) = client_event_sender.send(HandlerCommand::session(
+                id,
+                SessionCommand::<Channel>::Send(ClientMetaEventFrom::<Channel>::Draining, None)
+            ))
+        {
+            tracing::error!(?err, "failed to forward draining notice to session");
+            break;
+        }
     }
+
+    // wait for sessions to disconnect, then close the stragglers
+    let signal = LameDuckSignal{ done: Arc::new(AtomicBool::new(false)), notify: Arc::new(tokio::sync::Notify::new()) };
+    let signal_clone = signal.clone();
+    let _ = runtime_handle.spawn(
+            async move {
+                let _ = tokio::time::timeout(grace, connection_counter.wait_for_zero()).await;
+
+                for id in session_ids(&session_info)
+                {
+                    tracing::info!(id, "closing session at end of lame-duck grace period");
+                    let close_frame = CloseReason::Draining.close_frame("server draining");
+                    if let Err(err) = client_event_sender.send(
+                            HandlerCommand::session(id, SessionCommand::<Channel>::Close(close_frame))
+                        )
+                    {
+                        tracing::error!(?err, "failed to forward session close command to session");
+                        break;
+                    }
+                }
+
+                signal_clone.done.store(true, Ordering::Release);
+                signal_clone.notify.notify_waiters();
+            }
+        );
+
+    signal
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
-async fn run_server(app: axum::Router, listener: std::net::TcpListener, acceptor_config: AcceptorConfig)
+/// Wait for a termination signal (SIGINT, or SIGTERM on unix targets).
+async fn wait_for_termination_signal()
 {
+    let interrupt = async {
+            if let Err(err) = tokio::signal::ctrl_c().await
+            {
+                tracing::error!(?err, "failed listening for SIGINT");
+                std::future::pending::<()>().await;
+            }
+        };
+
+    #[cfg(unix)]
+    let terminate = async {
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            {
+                Ok(mut terminate) => { terminate.recv().await; }
+                Err(err) =>
+                {
+                    tracing::error!(?err, "failed listening for SIGTERM");
+                    std::future::pending::<()>().await;
+                }
+            }
+        };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select!{
+        _ = interrupt => (),
+        _ = terminate => (),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+async fn run_server(
+    app               : axum::Router,
+    listener          : std::net::TcpListener,
+    acceptor_config   : AcceptorConfig,
+    accept_rate_limit : Option<RateLimitConfig>,
+    upgrade_timeout   : Duration,
+    flushed           : Arc<tokio::sync::Notify>,
+    tls_infos         : TlsInfoRegistry,
+){
     // set listener
     let server = axum_server::Server::from_tcp(listener);
+    let make_service = app.into_make_service_with_connect_info::<SocketAddr>();
 
-    // set acceptor
-    let server = match acceptor_config
+    // set acceptor and serve it
+    // - the upgrade timeout wraps the inner acceptor so it also bounds TLS handshakes
+    // - flushes are reported above the TLS layer, once the bytes were handed off to the TCP stream
+    // - TLS handshake details are recorded once the handshake is done, so sessions can find them when they connect
+    // - each acceptor has a different type, so the server is served separately for each one
+    #[cfg(not(any(feature = "tls-rustls", feature = "tls-openssl")))]
+    let _ = tls_infos;
+    let result = match acceptor_config
     {
-        AcceptorConfig::Default         => server.acceptor(axum_server::accept::DefaultAcceptor::new()),
+        AcceptorConfig::Default => server.acceptor(
+                RateLimitedAcceptor::new(
+                    FlushNotifyAcceptor::new(
+                        UpgradeTimeoutAcceptor::new(axum_server::accept::DefaultAcceptor::new(), upgrade_timeout),
+                        flushed
+                    ),
+                    accept_rate_limit,
+                    NoAcceptHook
+                )
+            ).serve(make_service).await,
         #[cfg(feature = "tls-rustls")]
-        AcceptorConfig::Rustls(config)  => server.acceptor(axum_server::tls_rustls::RustlsAcceptor::new(config)),
+        AcceptorConfig::Rustls(config) => server.acceptor(
+                RateLimitedAcceptor::new(
+                    FlushNotifyAcceptor::new(
+                        UpgradeTimeoutAcceptor::new(axum_server::tls_rustls::RustlsAcceptor::new(config), upgrade_timeout),
+                        flushed
+                    ),
+                    accept_rate_limit,
+                    tls_infos
+                )
+            ).serve(make_service).await,
         #[cfg(feature = "tls-openssl")]
-        AcceptorConfig::OpenSSL(config) => server.acceptor(axum_server::tls_openssl::OpenSSLAcceptor::new(config)),
+        AcceptorConfig::OpenSSL(config) => server.acceptor(
+                RateLimitedAcceptor::new(
+                    FlushNotifyAcceptor::new(
+                        UpgradeTimeoutAcceptor::new(axum_server::tls_openssl::OpenSSLAcceptor::new(config), upgrade_timeout),
+                        flushed
+                    ),
+                    accept_rate_limit,
+                    tls_infos
+                )
+            ).serve(make_service).await,
     };
 
-    // serve it
-    if let Err(err) = server.serve(app.into_make_service_with_connect_info::<SocketAddr>()).await
+    if let Err(err) = result
     {
         tracing::error!(?err, "server stopped running with error");
     }
@@ -86,11 +323,47 @@ async fn run_server(app: axum::Router, listener: std::net::TcpListener, acceptor
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Tracks a server's lame-duck procedure. See [`Server::begin_lame_duck()`].
+#[derive(Debug, Clone)]
+pub struct LameDuckSignal
+{
+    done: Arc<AtomicBool>,
+    /// Notified when the procedure is done.
+    notify: Arc<tokio::sync::Notify>,
+}
+
+impl LameDuckSignal
+{
+    /// Returns true once all sessions have disconnected or the remaining sessions were closed at the end of the
+    /// grace period.
+    pub fn is_done(&self) -> bool
+    {
+        self.done.load(Ordering::Acquire)
+    }
+
+    /// Wait until the procedure is done. See [`LameDuckSignal::is_done()`].
+    pub async fn wait(&self)
+    {
+        // register for the notification before checking the flag, so finishing after the check will wake us
+        let notified = self.notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        if self.is_done() { return; }
+        notified.await;
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// A server for communicating with [`Client`]s.
 ///
 /// Use a [`ServerFactory`] to produce a new server.
 ///
-/// Note that the server does not currently have a shut-down procedure other than closing the executable.
+/// Note that the server does not currently have a shut-down procedure other than closing the executable. Use
+/// [`Server::begin_lame_duck()`] to drain connections before closing it.
+///
+/// Events that have not been drained when the server is dropped will be lost (a warning is logged).
 #[derive(Debug)]
 #[cfg_attr(feature = "bevy", derive(bevy_ecs::system::Resource))]
 pub struct Server<Channel: ChannelPack>
@@ -101,18 +374,45 @@ pub struct Server<Channel: ChannelPack>
     uses_tls: bool,
     /// The number of current connections.
     connection_counter: ConnectionCounter,
+    /// The server's metrics.
+    metrics: ServerMetrics,
+    /// Metadata of connected sessions.
+    session_info: SessionInfoRegistry,
+    /// Topic subscriptions of connected sessions.
+    topic_registry: Arc<Mutex<TopicRegistry>>,
+    /// The server's config.
+    config: ServerConfig,
+    /// The server's runtime.
+    runtime_handle: enfync::builtin::native::TokioHandle,
+    /// Id for the next streamed message.
+    next_stream_id: AtomicU64,
+    /// Id for the next tracked message.
+    next_signal_id: AtomicU64,
+    /// Shared app context (shared with the connection prevalidator and connection handler).
+    context: ServerContext,
+    /// Indicates the server is accepting new connections (shared with the connection prevalidator).
+    accepting: Arc<AtomicBool>,
+    /// Authenticator for new connections (shared with the connection prevalidator).
+    authenticator: Arc<RwLock<Authenticator>>,
+
+    /// Client ids that may not connect.
+    ban_list: Arc<RwLock<BanList>>,
+    /// Tracks the shutdown procedure triggered by a termination signal.
+    shutdown_signal: Arc<Mutex<Option<LameDuckSignal>>>,
 
     /// Sends client events to the internal connection handler.
-    client_event_sender: tokio::sync::mpsc::UnboundedSender<
-        SessionTargetMsg<SessionID, SessionCommand<Channel>>
-    >,
+    client_event_sender: tokio::sync::mpsc::UnboundedSender<HandlerCommand<Channel>>,
     /// Receives server events from the internal connection handler.
-    server_event_receiver: crossbeam::channel::Receiver<SessionSourceMsg<SessionID, ServerEventFrom<Channel>>>,
+    server_event_receiver: ServerEventReceiver<Channel>,
+    /// Notified when the internal connection handler sends a server event (or its event channel disconnects).
+    server_event_notify: Arc<tokio::sync::Notify>,
 
     /// A signal that indicates if the server's internal worker has stopped.
     server_closed_signal: enfync::PendingResult<()>,
     /// A signal that indicates if the server runner has stopped.
     server_running_signal: enfync::PendingResult<()>,
+    /// The thread running the internal connection handler (if it runs on a dedicated thread).
+    handler_thread: Option<HandlerThread>,
 }
 
 impl<Channel: ChannelPack> Server<Channel>
@@ -126,7 +426,10 @@ impl<Channel: ChannelPack> Server<Channel>
 
         // send to endpoint of ezsockets::Server::call() (will be picked up by ConnectionHandler::on_call())
         if let Err(err) = self.client_event_sender.send(
-                SessionTargetMsg::new(id, SessionCommand::<Channel>::Send(ClientMetaEventFrom::<Channel>::Msg(msg), None))
+                HandlerCommand::session(
+                    id,
+                    SessionCommand::<Channel>::Send(ClientMetaEventFrom::<Channel>::Msg(msg), None)
+                )
             )
         {
             tracing::error!(?err, "failed to forward message to session");
@@ -136,6 +439,167 @@ impl<Channel: ChannelPack> Server<Channel>
         Ok(())
     }
 
+    /// Send a message to the target session, dropping it if the server's internal connection handler doesn't dequeue
+    /// it before a time-to-live expires.
+    /// - Messages will be silently dropped if the session is not connected (there may or may not be a trace message).
+    /// - Returns `Err` if an internal server error occurs.
+    ///
+    /// Use this for real-time state that becomes obsolete quickly. The TTL only bounds how long the message waits in
+    /// the connection handler's queue, so a backlog in the handler will not cause stale messages to be delivered.
+    /// Once dequeued, the message is handed to the session's socket and written regardless of the TTL (e.g. it may
+    /// still be delayed by a slow connection).
+    ///
+    /// Messages sent this way are not logged for resending (see [`ServerConfig::resend_buffer_size`]).
+    pub fn send_with_ttl(&self, id: SessionID, msg: Channel::ServerMsg, ttl: Duration) -> Result<(), ()>
+    {
+        if self.is_dead() { tracing::warn!(id, "tried to send message to session but server is dead"); return Err(()); }
+
+        // send to endpoint of ezsockets::Server::call() (will be picked up by ConnectionHandler::on_call())
+        let deadline = std::time::Instant::now() + ttl;
+        if let Err(err) = self.client_event_sender.send(
+                HandlerCommand::session(
+                    id,
+                    SessionCommand::<Channel>::SendExpiring(ClientMetaEventFrom::<Channel>::Msg(msg), deadline)
+                )
+            )
+        {
+            tracing::error!(?err, "failed to forward message to session");
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    /// Send a message to the target session and get a [`ServerEvent::DeliveryReceipt`] when the message is written to
+    /// the session's socket (or fails).
+    /// - Returns the id that will be reported in the delivery receipt.
+    /// - Returns `Err` if an internal server error occurs.
+    ///
+    /// Unlike requests, delivery receipts don't need the client's cooperation. Tracked messages are not logged for
+    /// resending after reconnects (see [`ServerConfig::resend_buffer_size`]).
+    pub fn send_tracked(&self, id: SessionID, msg: Channel::ServerMsg) -> Result<MessageSignalId, ()>
+    {
+        if self.is_dead() { tracing::warn!(id, "tried to send message to session but server is dead"); return Err(()); }
+
+        // send to endpoint of ezsockets::Server::call() (will be picked up by ConnectionHandler::on_call())
+        let signal_id = self.next_signal_id.fetch_add(1u64, Ordering::Relaxed);
+        if let Err(err) = self.client_event_sender.send(
+                HandlerCommand::session(
+                    id,
+                    SessionCommand::<Channel>::SendTracked(ClientMetaEventFrom::<Channel>::Msg(msg), signal_id)
+                )
+            )
+        {
+            tracing::error!(?err, "failed to forward message to session");
+            return Err(());
+        }
+
+        Ok(signal_id)
+    }
+
+    /// Send a message to all sessions of a client.
+    /// - Returns the number of sessions the message was sent to.
+    /// - Returns `Err` if an internal server error occurs.
+    ///
+    /// Session ids equal client ids and a client can only have one session at a time, so this will send to at most
+    /// one session. Messages may still be dropped if the session disconnects before the message is sent.
+    pub fn send_to_client(&self, client_id: u128, msg: Channel::ServerMsg) -> Result<usize, ()>
+    {
+        // check if the client has a session
+        let Ok(session_info) = self.session_info.read()
+        else { tracing::error!(client_id, "session info registry poisoned"); return Err(()); };
+        let is_connected = session_info.contains_key(&client_id);
+        drop(session_info);
+
+        if !is_connected
+        { tracing::debug!(client_id, "tried to send message to client without sessions"); return Ok(0); }
+
+        // send to the client's session
+        self.send(client_id, msg)?;
+        Ok(1)
+    }
+
+    /// Send a message to the target session as a stream of chunks.
+    /// - Messages will be silently dropped if the session is not connected (there may or may not be a trace message).
+    /// - Returns `Err` if an internal server error occurs.
+    ///
+    /// Use this for messages that may be larger than the client can accept in one frame. Chunks are sized with
+    /// [`ServerConfig::stream_chunk_size`]. The client will receive the message as a normal server message once all
+    /// chunks have arrived.
+    pub fn send_stream(&self, id: SessionID, msg: Channel::ServerMsg) -> Result<(), ()>
+    {
+        if self.is_dead() { tracing::warn!(id, "tried to send stream to session but server is dead"); return Err(()); }
+
+        // serialize message
+        let Ok(ser_msg) = bincode::DefaultOptions::new().serialize(&msg)
+        else { tracing::error!(id, "serializing streamed message failed"); return Err(()); };
+
+        // send chunks to endpoint of ezsockets::Server::call() (will be picked up by ConnectionHandler::on_call())
+        let stream_id = self.next_stream_id.fetch_add(1u64, Ordering::Relaxed);
+        for chunk in split_into_chunks(stream_id, ser_msg, self.config.stream_chunk_size)
+        {
+            if let Err(err) = self.client_event_sender.send(
+                    HandlerCommand::session(
+                        id,
+                        SessionCommand::<Channel>::Send(ClientMetaEventFrom::<Channel>::Chunk(chunk), None)
+                    )
+                )
+            {
+                tracing::error!(?err, "failed to forward stream chunk to session");
+                return Err(());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send a message to all connected sessions except one (e.g. to relay a client's message to its peers).
+    /// - Messages will be silently dropped for sessions that disconnect (there may or may not be a trace message).
+    /// - Returns `Err` if the server is dead or an internal server error occurs.
+    ///
+    /// The message is serialized once for all recipients, so this is cheaper than sending to each session
+    /// separately.
+    pub fn broadcast_except(&self, exclude: SessionID, msg: Channel::ServerMsg) -> Result<(), ()>
+    {
+        if self.is_dead() { tracing::warn!(exclude, "tried to broadcast message but server is dead"); return Err(()); }
+
+        // send to endpoint of ezsockets::Server::call() (will be picked up by ConnectionHandler::on_call())
+        if let Err(err) = self.client_event_sender.send(
+                HandlerCommand::session(exclude, SessionCommand::<Channel>::BroadcastExcept(msg))
+            )
+        {
+            tracing::error!(?err, "failed to forward broadcast message to connection handler");
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    /// Publish a message to all sessions subscribed to a topic.
+    /// - Messages will be silently dropped for sessions that disconnect (there may or may not be a trace message).
+    /// - Returns `Err` if an internal server error occurs.
+    ///
+    /// Clients subscribe to topics with [`Client::subscribe()`]. Subscriptions are removed when a session disconnects.
+    pub fn publish(&self, topic: &str, msg: Channel::ServerMsg) -> Result<(), ()>
+    {
+        if self.is_dead() { tracing::warn!(topic, "tried to publish message but server is dead"); return Err(()); }
+
+        // collect subscribers
+        let Ok(topic_registry) = self.topic_registry.lock()
+        else { tracing::error!(topic, "topic registry poisoned"); return Err(()); };
+        let subscribers = topic_registry.subscribers(topic);
+        drop(topic_registry);
+
+        // send to subscribers
+        tracing::trace!(topic, num_subscribers = subscribers.len(), "publishing message");
+        for id in subscribers
+        {
+            self.send(id, msg.clone())?;
+        }
+
+        Ok(())
+    }
+
     /// Respond to a client request.
     /// - Messages will be silently dropped if the session is not connected (there may or may not be a trace message).
     /// - Returns `Err` if an internal server error occurs.
@@ -158,8 +622,9 @@ impl<Channel: ChannelPack> Server<Channel>
         }
 
         // send to endpoint of ezsockets::Server::call() (will be picked up by ConnectionHandler::on_call())
+        let latency = token.elapsed();
         let (request_id, death_signal) = token.take();
-        if let Err(err) = self.client_event_sender.send(SessionTargetMsg::new(
+        if let Err(err) = self.client_event_sender.send(HandlerCommand::session(
                 client_id,
                 SessionCommand::<Channel>::Send(
                     ClientMetaEventFrom::<Channel>::Response(response, request_id),
@@ -170,6 +635,55 @@ impl<Channel: ChannelPack> Server<Channel>
             tracing::error!(?err, "failed to forward response to session");
             return Err(());
         }
+        self.metrics.record_request_latency(latency);
+
+        Ok(())
+    }
+
+    /// Respond to a batch of client requests.
+    /// - Responses will be silently dropped if their sessions are not connected (there may or may not be a trace
+    ///   message).
+    /// - Returns `Err` if an internal server error occurs.
+    ///
+    /// This is equivalent to calling [`Server::respond()`] for each response, but forwards all responses to the server
+    /// backend at once, which is cheaper when answering many requests at a time (e.g. all requests received in a tick).
+    pub fn respond_many(&self, responses: Vec<(RequestToken, Channel::ServerResponse)>) -> Result<(), ()>
+    {
+        // check server liveness
+        if self.is_dead()
+        {
+            tracing::warn!(num_responses = responses.len(), "tried to send responses to sessions but server is dead");
+            return Err(());
+        }
+
+        // collect responses for live sessions
+        let mut latencies = Vec::with_capacity(responses.len());
+        let mut batch     = Vec::with_capacity(responses.len());
+        for (token, response) in responses
+        {
+            // check token liveness
+            let client_id = token.client_id();
+            if token.destination_is_dead()
+            {
+                tracing::debug!(client_id, request_id = token.request_id(), "tried to send response to dead session");
+                continue;
+            }
+
+            latencies.push(token.elapsed());
+            let (request_id, death_signal) = token.take();
+            batch.push((client_id, ClientMetaEventFrom::<Channel>::Response(response, request_id), death_signal));
+        }
+        if batch.is_empty() { return Ok(()); }
+
+        // send to endpoint of ezsockets::Server::call() (will be picked up by ConnectionHandler::on_call())
+        if let Err(err) = self.client_event_sender.send(
+                HandlerCommand::<Channel>::Control(ControlCommand::<Channel>::SendResponses(batch))
+            )
+        {
+            tracing::error!(?err, "failed to forward responses to sessions");
+            return Err(());
+        }
+        for latency in latencies { self.metrics.record_request_latency(latency); }
 
         Ok(())
     }
@@ -198,8 +712,9 @@ impl<Channel: ChannelPack> Server<Channel>
         }
 
         // send to endpoint of ezsockets::Server::call() (will be picked up by ConnectionHandler::on_call())
+        let latency = token.elapsed();
         let (request_id, death_signal) = token.take();
-        if let Err(err) = self.client_event_sender.send(SessionTargetMsg::new(
+        if let Err(err) = self.client_event_sender.send(HandlerCommand::session(
                 client_id,
                 SessionCommand::<Channel>::Send(ClientMetaEventFrom::<Channel>::Ack(request_id), Some(death_signal))
             ))
@@ -207,11 +722,17 @@ impl<Channel: ChannelPack> Server<Channel>
             tracing::error!(?err, "failed to forward ack to session");
             return Err(());
         }
+        self.metrics.record_request_latency(latency);
 
         Ok(())
     }
 
     /// Reject a client request.
+    ///
+    /// The rejection is sent to the client immediately, and the client will emit [`ClientEvent::Reject`] as soon as
+    /// it arrives (the request's [`RequestSignal`] status becomes [`RequestStatus::Rejected`]). This can be used to
+    /// cancel a request the server is holding (e.g. because a game round ended), so the client can abort any work it
+    /// started in anticipation of the request being handled.
     pub fn reject(&self, _token: RequestToken)
     {
         // drop the token: rejection will happen automatically using the token's custom Drop
@@ -220,6 +741,9 @@ impl<Channel: ChannelPack> Server<Channel>
     /// Close the target session.
     ///
     /// The target session may remain open until some time after this method is called.
+    ///
+    /// Use [`CloseReason::close_frame()`] to make a close frame with a standard close code (e.g. [`CloseReason::Kicked`]),
+    /// or [`CloseReason::close_frame_with_payload()`] to attach a structured payload the client can decode.
     pub fn close_session(&self, id: SessionID, close_frame: ezsockets::CloseFrame) -> Result<(), ()>
     {
         // send to endpoint of ezsockets::Server::call() (will be picked up by ConnectionHandler::on_call())
@@ -230,7 +754,7 @@ impl<Channel: ChannelPack> Server<Channel>
             return Err(());
         }
         if let Err(err) = self.client_event_sender.send(
-                SessionTargetMsg::new(id, SessionCommand::<Channel>::Close(close_frame))
+                HandlerCommand::session(id, SessionCommand::<Channel>::Close(close_frame))
             )
         {
             tracing::error!(?err, "failed to forward session close command to session");
@@ -241,24 +765,400 @@ impl<Channel: ChannelPack> Server<Channel>
     }
 
     /// Get the next available server event
+    ///
+    /// Messages and requests from a session are emitted in the order the client sent them. Use
+    /// [`Server::next_with_generation()`] to also get the generation number of the session that produced the event.
     pub fn next(&self) -> Option<(SessionID, ServerEventFrom<Channel>)>
     {
         let Ok(msg) = self.server_event_receiver.try_recv() else { return None; };
         Some((msg.id, msg.msg))
     }
 
+    /// Get up to `max` available server events.
+    ///
+    /// Events are returned in the same order as [`Server::next()`] would return them. Draining events in bulk is
+    /// cheaper than calling [`Server::next()`] repeatedly when many events are queued (e.g. in a per-frame pump).
+    /// See also [`ServerConfig::coalesce_events`].
+    pub fn next_batch(&self, max: usize) -> Vec<(SessionID, ServerEventFrom<Channel>)>
+    {
+        let Ok(events) = self.server_event_receiver.try_recv_batch(max) else { return Vec::default(); };
+        events
+            .into_iter()
+            .map(|msg| (msg.id, msg.msg))
+            .collect()
+    }
+
+    /// Get the next available server event and the generation number of the session that produced it.
+    ///
+    /// Each new session is assigned a generation number, and generation numbers of sessions with the same id are
+    /// strictly increasing. When a client reconnects, events from its previous session may still be waiting to be
+    /// consumed, so use the generation to discard stale events (see [`Server::session_generation()`]).
+    ///
+    /// Generation numbers are only reported here. Events don't carry them, so they are not available from
+    /// [`Server::next()`], [`Server::next_batch()`], [`Server::next_async()`], or [`Server::on_event()`].
+    pub fn next_with_generation(&self) -> Option<(SessionID, u64, ServerEventFrom<Channel>)>
+    {
+        let Ok(msg) = self.server_event_receiver.try_recv() else { return None; };
+        Some((msg.id, msg.generation, msg.msg))
+    }
+
+    /// Wait for the next server event.
+    ///
+    /// Returns `None` once the server's internal worker shuts down and all events have been consumed. The future is
+    /// woken by a notification sent alongside each event, so it doesn't spin the CPU or occupy a thread while waiting.
+    ///
+    /// The future is cancellation-safe: dropping it before it completes will not consume an event. It can be used
+    /// together with [`Server::next()`], in which case each event will be received by only one of them.
+    pub async fn next_async(&self) -> Option<(SessionID, ServerEventFrom<Channel>)>
+    {
+        loop
+        {
+            // register for the next event notification before checking for events, so an event sent after the check
+            // will wake us
+            let notified = self.server_event_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            match self.server_event_receiver.try_recv()
+            {
+                Ok(msg) => return Some((msg.id, msg.msg)),
+                Err(crossbeam::channel::TryRecvError::Disconnected) => return None,
+                Err(crossbeam::channel::TryRecvError::Empty) => (),
+            }
+
+            // wait until an event is available without consuming it
+            notified.await;
+        }
+    }
+
+    /// Get the number of server events waiting to be consumed.
+    ///
+    /// A steadily growing number indicates the server's events are produced faster than they are consumed.
+    pub fn queued_events(&self) -> usize
+    {
+        self.server_event_receiver.len()
+    }
+
+    /// Panic if any server events are waiting to be consumed.
+    ///
+    /// The panic message lists all unconsumed events. Intended for tests, to check that every event was handled.
+    #[cfg(feature = "testing")]
+    #[track_caller]
+    pub fn assert_drained(&self)
+    {
+        let events: Vec<(SessionID, ServerEventFrom<Channel>)> = self.server_event_receiver
+            .try_recv_batch(usize::MAX)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|msg| (msg.id, msg.msg))
+            .collect();
+        if events.is_empty() { return; }
+
+        panic!("server has {} unconsumed event(s): {:#?}", events.len(), events);
+    }
+
+    /// Invoke a callback for each server event, as an alternative to polling [`Server::next()`].
+    ///
+    /// Spawns a thread that drains the server's events and passes them to the callback. The thread exits when the
+    /// server's internal worker shuts down.
+    ///
+    /// This is mutually exclusive with [`Server::next()`]. If you use both, then each event will be received by only one
+    /// of them.
+    pub fn on_event(&self, mut callback: Box<dyn FnMut(SessionID, ServerEventFrom<Channel>) + Send>)
+    {
+        let server_event_receiver = self.server_event_receiver.clone();
+        std::thread::spawn(
+                move ||
+                {
+                    while let Ok(msg) = server_event_receiver.recv()
+                    {
+                        (callback)(msg.id, msg.msg);
+                    }
+                }
+            );
+    }
+
     /// Get the server's url.
     pub fn url(&self) -> url::Url
     {
         make_websocket_url(self.uses_tls, self.server_address).unwrap()
     }
 
+    /// Set whether the server accepts new connections.
+    ///
+    /// While not accepting, new connection requests are rejected with a `Maintenance` reason. Existing sessions are
+    /// not affected. Servers accept connections by default.
+    pub fn set_accepting(&self, accepting: bool)
+    {
+        tracing::info!(accepting, "setting server connection acceptance");
+        self.accepting.store(accepting, Ordering::Release);
+    }
+
+    /// Test if the server is accepting new connections. See [`Server::set_accepting()`].
+    pub fn is_accepting(&self) -> bool
+    {
+        self.accepting.load(Ordering::Acquire)
+    }
+
+    /// Replace the server's authenticator (e.g. to rotate signing keys).
+    ///
+    /// Only new connections will be authenticated with the new authenticator. Existing sessions are not affected.
+    pub fn set_authenticator(&self, authenticator: Authenticator)
+    {
+        tracing::info!("replacing server authenticator");
+        match self.authenticator.write()
+        {
+            Ok(mut current) => *current = authenticator,
+            Err(_) => tracing::error!("authenticator poisoned"),
+        }
+    }
+
+    /// Ban a client from connecting until the given time, or permanently if `until` is `None`.
+    ///
+    /// New connection requests from the client are rejected with a `Banned` reason. If the client is connected, its
+    /// session is closed with [`CloseReason::Banned`].
+    pub fn ban_client(&self, client_id: u128, until: Option<std::time::Instant>)
+    {
+        tracing::info!(client_id, ?until, "banning client");
+        match self.ban_list.write()
+        {
+            Ok(mut ban_list) => ban_list.ban(client_id, until),
+            Err(_) => tracing::error!("ban list poisoned"),
+        }
+
+        // close the client's session if it has one
+        let Ok(session_info) = self.session_info.read()
+        else { tracing::error!(client_id, "session info registry poisoned"); return; };
+        let is_connected = session_info.contains_key(&client_id);
+        drop(session_info);
+
+        if !is_connected { return; }
+        if let Err(err) = self.client_event_sender.send(HandlerCommand::session(
+                client_id,
+                SessionCommand::<Channel>::Close(CloseReason::Banned.close_frame("banned"))
+            ))
+        {
+            tracing::error!(?err, "failed to forward session close command to session");
+        }
+    }
+
+    /// Remove a client's ban. See [`Server::ban_client()`].
+    pub fn unban_client(&self, client_id: u128)
+    {
+        tracing::info!(client_id, "unbanning client");
+        match self.ban_list.write()
+        {
+            Ok(mut ban_list) => ban_list.unban(client_id),
+            Err(_) => tracing::error!("ban list poisoned"),
+        }
+    }
+
+    /// Put the server into lame-duck mode, draining its connections before it is terminated.
+    ///
+    /// 1) The server stops accepting new connections (see [`Server::set_accepting()`]).
+    /// 2) All connected clients are notified with [`ClientReport::ServerDraining`] so they can migrate.
+    /// 3) Once all sessions disconnect or the grace period expires, remaining sessions are closed with
+    ///    [`CloseReason::Draining`].
+    ///
+    /// Returns a signal that is done after the last step. The procedure runs on the server's runtime.
+    pub fn begin_lame_duck(&self, grace: Duration) -> LameDuckSignal
+    {
+        begin_lame_duck::<Channel>(
+                &self.runtime_handle,
+                &self.accepting,
+                self.connection_counter.clone(),
+                self.session_info.clone(),
+                self.client_event_sender.clone(),
+                grace,
+            )
+    }
+
+    /// Get the signal of the shutdown procedure triggered by a termination signal.
+    ///
+    /// Returns `None` if [`ServerConfig::shutdown_on_signal`] is disabled or no termination signal has arrived yet.
+    /// Once the returned signal is done, the app should exit.
+    pub fn shutdown_signal(&self) -> Option<LameDuckSignal>
+    {
+        match self.shutdown_signal.lock()
+        {
+            Ok(shutdown_signal) => shutdown_signal.clone(),
+            Err(_) => { tracing::error!("shutdown signal poisoned"); None }
+        }
+    }
+
+    /// Get the server's config.
+    pub fn config(&self) -> &ServerConfig
+    {
+        &self.config
+    }
+
     /// Get the number of client connections.
     pub fn num_connections(&self) -> u64
     {
         self.connection_counter.load()
     }
 
+    /// Get a handle to the server's connection counter.
+    ///
+    /// The handle can be polled for the number of client connections without access to the server
+    /// (e.g. from a monitoring thread).
+    pub fn connection_counter_handle(&self) -> ConnectionCounter
+    {
+        self.connection_counter.clone()
+    }
+
+    /// Get a handle to the server's metrics.
+    ///
+    /// The handle can be read without access to the server (e.g. from a monitoring thread).
+    pub fn metrics(&self) -> ServerMetrics
+    {
+        self.metrics.clone()
+    }
+
+    /// Get a snapshot of the server's internal state, for troubleshooting (e.g. from an admin endpoint).
+    ///
+    /// The snapshot is gathered by the server's internal connection handler in between its other work. This blocks
+    /// until the handler responds, and returns `Err` if the handler does not respond within one second.
+    pub fn debug_snapshot(&self) -> Result<ServerDebugSnapshot, ()>
+    {
+        if self.is_dead() { tracing::warn!("tried to take a debug snapshot but server is dead"); return Err(()); }
+
+        // send to endpoint of ezsockets::Server::call() (will be picked up by ConnectionHandler::on_call())
+        let (snapshot_sender, snapshot_receiver) = crossbeam::channel::bounded(1);
+        if let Err(err) = self.client_event_sender.send(
+                HandlerCommand::<Channel>::Control(ControlCommand::<Channel>::DebugSnapshot(snapshot_sender))
+            )
+        {
+            tracing::error!(?err, "failed to request debug snapshot");
+            return Err(());
+        }
+
+        snapshot_receiver.recv_timeout(Duration::from_secs(1)).map_err(
+                |_| tracing::warn!("timed out waiting for a debug snapshot")
+            )
+    }
+
+    /// Get the time when a session connected.
+    ///
+    /// Returns `None` if the session is not connected. Note that session metadata is removed when the session
+    /// disconnects, which may be before you read the session's [`ServerReport::Disconnected`] event.
+    pub fn session_connected_at(&self, id: SessionID) -> Option<std::time::SystemTime>
+    {
+        let Ok(session_info) = self.session_info.read()
+        else { tracing::error!(id, "session info registry poisoned"); return None; };
+        session_info.get(&id).map(|info| info.connected_at)
+    }
+
+    /// Get the auth request a session was authenticated with.
+    ///
+    /// Use this to make authorization decisions without re-verifying the client. Returns `None` if the session is not
+    /// connected.
+    pub fn session_auth(&self, id: SessionID) -> Option<AuthRequest>
+    {
+        let Ok(session_info) = self.session_info.read()
+        else { tracing::error!(id, "session info registry poisoned"); return None; };
+        session_info.get(&id).map(|info| info.auth_request.clone())
+    }
+
+    /// Get the source address of a session.
+    ///
+    /// Returns `None` if the session is not connected.
+    pub fn session_peer_addr(&self, id: SessionID) -> Option<SocketAddr>
+    {
+        let Ok(session_info) = self.session_info.read()
+        else { tracing::error!(id, "session info registry poisoned"); return None; };
+        session_info.get(&id).map(|info| info.peer_addr)
+    }
+
+    /// Get the TLS handshake details of a session's connection.
+    ///
+    /// Returns `None` if the session is not connected or the server doesn't use TLS.
+    pub fn session_tls_info(&self, id: SessionID) -> Option<SessionTlsInfo>
+    {
+        let Ok(session_info) = self.session_info.read()
+        else { tracing::error!(id, "session info registry poisoned"); return None; };
+        session_info.get(&id).and_then(|info| info.tls_info.clone())
+    }
+
+    /// Get the generation number of a session. See [`Server::next_with_generation()`].
+    ///
+    /// Returns `None` if the session is not connected.
+    pub fn session_generation(&self, id: SessionID) -> Option<u64>
+    {
+        let Ok(session_info) = self.session_info.read()
+        else { tracing::error!(id, "session info registry poisoned"); return None; };
+        session_info.get(&id).map(|info| info.generation)
+    }
+
+    /// Get the app context attached to a session by [`ServerConfig::on_upgrade`].
+    ///
+    /// Returns `None` if the session is not connected, has no context, or if the context is not of type `C`.
+    pub fn session_context<C: Any + Send + Sync>(&self, id: SessionID) -> Option<Arc<C>>
+    {
+        let Ok(session_info) = self.session_info.read()
+        else { tracing::error!(id, "session info registry poisoned"); return None; };
+        let context = session_info.get(&id)?.context.clone()?;
+        context.downcast::<C>().ok()
+    }
+
+    /// Attach an app tag to a session (e.g. an identity derived from the session's auth request).
+    ///
+    /// The tag replaces any previous tag, and it is dropped when the session disconnects. Set it while handling the
+    /// session's [`ServerReport::Connected`] event so it is available for the rest of the session's events. If the
+    /// client reconnected in the meantime, the tag is attached to the new session (see
+    /// [`Server::session_generation()`]).
+    ///
+    /// Returns `Err` if the session is not connected.
+    pub fn set_session_tag<T: Any + Send + Sync>(&self, id: SessionID, tag: T) -> Result<(), ()>
+    {
+        let Ok(mut session_info) = self.session_info.write()
+        else { tracing::error!(id, "session info registry poisoned"); return Err(()); };
+        let Some(info) = session_info.get_mut(&id)
+        else { tracing::debug!(id, "tried to tag session that is not connected"); return Err(()); };
+        info.tag = Some(Box::new(tag));
+
+        Ok(())
+    }
+
+    /// Get a clone of the app tag attached to a session with [`Server::set_session_tag()`].
+    ///
+    /// Returns `None` if the session is not connected, has no tag, or if the tag is not of type `T`.
+    pub fn session_tag<T: Any + Clone>(&self, id: SessionID) -> Option<T>
+    {
+        self.with_session_tag(id, |tag: &T| tag.clone())
+    }
+
+    /// Access the app tag attached to a session with [`Server::set_session_tag()`].
+    ///
+    /// Returns `None` if the session is not connected, has no tag, or if the tag is not of type `T`. The session
+    /// registry is locked while the callback runs, so don't call other server methods from it.
+    pub fn with_session_tag<T: Any, R>(&self, id: SessionID, callback: impl FnOnce(&T) -> R) -> Option<R>
+    {
+        let Ok(session_info) = self.session_info.read()
+        else { tracing::error!(id, "session info registry poisoned"); return None; };
+        let tag = session_info.get(&id)?.tag.as_ref()?.downcast_ref::<T>()?;
+        Some((callback)(tag))
+    }
+
+    /// Get the environment type (native or WASM) of a session's client.
+    ///
+    /// This is the same value reported in [`ServerReport::Connected`]. Returns `None` if the session is not connected.
+    pub fn session_env_type(&self, id: SessionID) -> Option<EnvType>
+    {
+        let Ok(session_info) = self.session_info.read()
+        else { tracing::error!(id, "session info registry poisoned"); return None; };
+        session_info.get(&id).map(|info| info.env_type)
+    }
+
+    /// Access the server's shared app context.
+    ///
+    /// Returns `None` if the server was not made with [`ServerFactory::new_server_with_context()`] or if the
+    /// context is not of type `C`.
+    pub fn context<C: Any + Send + Sync>(&self) -> Option<&C>
+    {
+        self.context.get::<C>()
+    }
+
     /// Test if the server is dead.
     pub fn is_dead(&self) -> bool
     {
@@ -266,6 +1166,34 @@ impl<Channel: ChannelPack> Server<Channel>
     }
 }
 
+impl<Channel: ChannelPack> Drop for Server<Channel>
+{
+    fn drop(&mut self)
+    {
+        // log events that will never be read
+        // - this can happen if the server is dropped (e.g. removed as a bevy resource) without being drained
+        let num_lost_events = self.server_event_receiver.len();
+        if num_lost_events > 0 { tracing::warn!(num_lost_events, "dropping server with undrained events"); }
+
+        // close all sessions before the handler thread is stopped
+        // - sockets live on the server's runtime, so they would outlive the handler without being closed
+        if self.handler_thread.is_none() { return; }
+        self.accepting.store(false, Ordering::Release);
+
+        for id in session_ids(&self.session_info)
+        {
+            let close_frame = CloseReason::Draining.close_frame("server shutting down");
+            if let Err(err) = self.client_event_sender.send(
+                    HandlerCommand::session(id, SessionCommand::<Channel>::Close(close_frame))
+                )
+            {
+                tracing::error!(?err, "failed to forward session close command to session");
+                break;
+            }
+        }
+    }
+}
+
 //-------------------------------------------------------------------------------------------------------------------
 
 /// Factory for producing servers that all bake in the same protocol version.
@@ -273,8 +1201,10 @@ impl<Channel: ChannelPack> Server<Channel>
 #[derive(Debug, Clone)]
 pub struct ServerFactory<Channel: ChannelPack>
 {
-    protocol_version : &'static str,
-    _phantom         : PhantomData<Channel>,
+    protocol_version   : &'static str,
+    observer           : Arc<dyn Observer>,
+    inbound_middleware : Option<Arc<dyn InboundMiddlewareFn<Channel::ClientMsg>>>,
+    _phantom           : PhantomData<Channel>,
 }
 
 impl<Channel: ChannelPack> ServerFactory<Channel>
@@ -282,12 +1212,41 @@ impl<Channel: ChannelPack> ServerFactory<Channel>
     /// Make a new server factory with a given protocol version.
     pub fn new(protocol_version: &'static str) -> Self
     {
-        ServerFactory{ protocol_version, _phantom: PhantomData::default() }
+        ServerFactory{
+            protocol_version,
+            observer           : Arc::new(NoopObserver),
+            inbound_middleware : None,
+            _phantom           : PhantomData::default(),
+        }
+    }
+
+    /// Set the [`Observer`] for servers made by this factory.
+    pub fn with_observer(mut self, observer: Arc<dyn Observer>) -> Self
+    {
+        self.observer = observer;
+        self
+    }
+
+    /// Set middleware that runs on client messages before they are emitted as server events, for servers made by
+    /// this factory.
+    ///
+    /// The middleware can validate or normalize messages (e.g. clamp values or reject NaNs) in one place instead of in
+    /// every handler. Messages are dropped if it returns `None`. Requests are not passed to the middleware.
+    ///
+    /// The middleware runs on each session's IO task for every client message, so it should be cheap and must not
+    /// block.
+    pub fn with_inbound_middleware(mut self, middleware: Arc<dyn InboundMiddlewareFn<Channel::ClientMsg>>) -> Self
+    {
+        self.inbound_middleware = Some(middleware);
+        self
     }
 
     /// Make a new server.
     ///
     /// Only works with a tokio runtime handle.
+    ///
+    /// Panics if the server fails to start (e.g. if the address is in use). Use [`ServerFactory::try_new_server()`]
+    /// to handle startup failures.
     pub fn new_server<A>(&self,
         runtime_handle  : enfync::builtin::native::TokioHandle,
         address         : A,
@@ -295,14 +1254,98 @@ impl<Channel: ChannelPack> ServerFactory<Channel>
         authenticator   : Authenticator,
         config          : ServerConfig
     ) -> Server<Channel>
+    where
+        A: std::net::ToSocketAddrs + Send + 'static,
+    {
+        match self.try_new_server(runtime_handle, address, acceptor_config, authenticator, config)
+        {
+            Ok(server) => server,
+            Err(err) => panic!("failed to start server: {err}"),
+        }
+    }
+
+    /// Make a new server, or return an error if the server fails to start.
+    ///
+    /// Only works with a tokio runtime handle.
+    pub fn try_new_server<A>(&self,
+        runtime_handle  : enfync::builtin::native::TokioHandle,
+        address         : A,
+        acceptor_config : AcceptorConfig,
+        authenticator   : Authenticator,
+        config          : ServerConfig
+    ) -> Result<Server<Channel>, ServerStartError>
+    where
+        A: std::net::ToSocketAddrs + Send + 'static,
+    {
+        self.try_new_server_impl(runtime_handle, address, acceptor_config, authenticator, config, ServerContext::default())
+    }
+
+    /// Make a new server that carries shared app context (e.g. a game world or database pool).
+    ///
+    /// The context is passed to the server's connection hooks ([`ServerConfig::load_shed`] and
+    /// [`ServerConfig::on_upgrade`]) and can be accessed with [`Server::context()`].
+    ///
+    /// Only works with a tokio runtime handle.
+    ///
+    /// Panics if the server fails to start (e.g. if the address is in use). Use
+    /// [`ServerFactory::try_new_server_with_context()`] to handle startup failures.
+    pub fn new_server_with_context<A, C>(&self,
+        runtime_handle  : enfync::builtin::native::TokioHandle,
+        address         : A,
+        acceptor_config : AcceptorConfig,
+        authenticator   : Authenticator,
+        config          : ServerConfig,
+        context         : Arc<C>,
+    ) -> Server<Channel>
+    where
+        A: std::net::ToSocketAddrs + Send + 'static,
+        C: Any + Send + Sync,
+    {
+        match self.try_new_server_with_context(runtime_handle, address, acceptor_config, authenticator, config, context)
+        {
+            Ok(server) => server,
+            Err(err) => panic!("failed to start server: {err}"),
+        }
+    }
+
+    /// Make a new server that carries shared app context, or return an error if the server fails to start.
+    ///
+    /// See [`ServerFactory::new_server_with_context()`].
+    pub fn try_new_server_with_context<A, C>(&self,
+        runtime_handle  : enfync::builtin::native::TokioHandle,
+        address         : A,
+        acceptor_config : AcceptorConfig,
+        authenticator   : Authenticator,
+        config          : ServerConfig,
+        context         : Arc<C>,
+    ) -> Result<Server<Channel>, ServerStartError>
+    where
+        A: std::net::ToSocketAddrs + Send + 'static,
+        C: Any + Send + Sync,
+    {
+        let context = ServerContext::new(Some(context));
+        self.try_new_server_impl(runtime_handle, address, acceptor_config, authenticator, config, context)
+    }
+
+    fn try_new_server_impl<A>(&self,
+        runtime_handle  : enfync::builtin::native::TokioHandle,
+        address         : A,
+        acceptor_config : AcceptorConfig,
+        authenticator   : Authenticator,
+        config          : ServerConfig,
+        context         : ServerContext,
+    ) -> Result<Server<Channel>, ServerStartError>
     where
         A: std::net::ToSocketAddrs + Send + 'static,
     {
         // prepare message channel that points out of the connection handler
+        let server_event_notify = Arc::new(tokio::sync::Notify::new());
         let (
                 server_event_sender,
                 server_event_receiver
-            ) = crossbeam::channel::unbounded::<SessionSourceMsg<SessionID, ServerEventFrom<Channel>>>();
+            ) = server_event_channel::<Channel>(config.coalesce_events, server_event_notify.clone());
+        let flushed = Arc::new(tokio::sync::Notify::new());
+        let delivery_receipts = DeliveryReceiptWatcher::new(server_event_sender.clone(), flushed.clone());
 
         // prepare connection counter
         // - this is used to communication the current number of connections from the connection handler to the
@@ -312,36 +1355,94 @@ impl<Channel: ChannelPack> ServerFactory<Channel>
         // make server core with our connection handler
         // note: ezsockets::Server::create() must be called from within a tokio runtime
         let connection_counter_clone = connection_counter.clone();
+        let session_info: SessionInfoRegistry = Arc::new(RwLock::new(HashMap::default()));
+        let session_info_clone = session_info.clone();
+        let topic_registry = Arc::new(Mutex::new(TopicRegistry::default()));
+        let topic_registry_clone = topic_registry.clone();
+        let mut handler_config = config.clone();
+        handler_config.on_send = with_payload_compression(handler_config.on_send, &handler_config.payload_compression);
+        handler_config.on_send = with_payload_encryption(handler_config.on_send, &handler_config.payload_encryption);
+        let observer = self.observer.clone();
+        let inbound_middleware = self.inbound_middleware.clone();
+        let protocol_version = self.protocol_version;
+        let authenticator = Arc::new(RwLock::new(authenticator));
+        let authenticator_clone = authenticator.clone();
+        let upgrade_contexts: UpgradeContexts = Arc::new(Mutex::new(HashMap::default()));
+        let upgrade_contexts_clone = upgrade_contexts.clone();
+        let tls_infos = TlsInfoRegistry::default();
+        let tls_infos_clone = tls_infos.clone();
+
+        // prepare the connection handler's runtime
+        // - ezsockets::Server::create() spawns the handler on the runtime it is called from, so it must be called from
+        //   the dedicated thread's runtime if there is one
+        let handler_thread = match config.dedicated_handler_thread
+        {
+            false => None,
+            true => Some(HandlerThread::spawn(connection_counter.clone()).map_err(
+                    |err| { tracing::error!(?err, "failed spawning handler thread"); ServerStartError::RuntimeError }
+                )?),
+        };
+        let handler_runtime = match &handler_thread
+        {
+            Some(handler_thread) => handler_thread.runtime_handle.clone(),
+            None => runtime_handle.clone(),
+        };
 
-        let (server, server_worker) = enfync::blocking::extract(runtime_handle.spawn(async move {
+        let (server, server_worker) = enfync::blocking::extract(handler_runtime.spawn(async move {
                 ezsockets::Server::create(
                         move |_server|
                         ConnectionHandler::<Channel>{
-                                config,
+                                span: log_span(&handler_config.log_id),
+                                config: handler_config,
                                 connection_counter: connection_counter_clone,
                                 session_registry: HashMap::default(),
+                                session_info: session_info_clone,
+                                topic_registry: topic_registry_clone,
+                                resend_logs: HashMap::default(),
+                                last_generation: 0u64,
+                                started_at: std::time::Instant::now(),
+                                protocol_version,
+                                authenticator: authenticator_clone,
+                                upgrade_contexts: upgrade_contexts_clone,
+                                tls_infos: tls_infos_clone,
+                                observer,
+                                inbound_middleware,
                                 server_event_sender,
+                                delivery_receipts,
                             }
                     )
-            })).unwrap();
+            })).map_err(|_| ServerStartError::RuntimeError)?;
 
-        let server_closed_signal = runtime_handle.spawn(
-                async move {
-                    if let Err(err) = server_worker.await
-                    {
-                        tracing::error!(?err, "server closed with error");
-                    }
+        let server_worker = async move {
+                match server_worker.await
+                {
+                    Ok(()) => (),
+                    // the handler thread was stopped
+                    Err(err) if err.is_cancelled() => tracing::debug!("server handler stopped"),
+                    Err(err) => tracing::error!(?err, "server closed with error"),
                 }
-            );
+            };
+        let server_closed_signal = runtime_handle.spawn(server_worker);
 
         // prepare prevalidator
+        let accepting = Arc::new(AtomicBool::new(true));
+        let ban_list = Arc::new(RwLock::new(BanList::default()));
         let prevalidator = ConnectionPrevalidator{
-                protocol_version   : self.protocol_version,
-                authenticator,
-                max_connections    : config.max_connections,
-                max_msg_size       : config.max_msg_size,
-                heartbeat_interval : config.heartbeat_interval,
-                keepalive_timeout  : config.keepalive_timeout,
+                protocol_version        : self.protocol_version,
+                authenticator           : authenticator.clone(),
+                max_connections         : config.max_connections,
+                load_shed               : config.load_shed.clone(),
+                max_msg_size            : config.max_msg_size,
+                heartbeat_interval      : config.heartbeat_interval,
+                keepalive_timeout       : config.keepalive_timeout,
+                wasm_heartbeat_interval : config.wasm_heartbeat_interval.unwrap_or(config.heartbeat_interval),
+                wasm_keepalive_timeout  : config.wasm_keepalive_timeout.unwrap_or(config.keepalive_timeout),
+                accepting               : accepting.clone(),
+                ban_list                : ban_list.clone(),
+                allowed_origins         : config.allowed_origins.clone(),
+                context                 : context.clone(),
+                on_upgrade              : config.on_upgrade.clone(),
+                upgrade_contexts,
             };
 
         // prepare router
@@ -352,26 +1453,99 @@ impl<Channel: ChannelPack> ServerFactory<Channel>
             .layer(axum::Extension(connection_counter.clone()));
 
         // prepare listener
-        let connection_listener = std::net::TcpListener::bind(address).unwrap();
-        let server_address = connection_listener.local_addr().unwrap();
+        let Some(address) = address.to_socket_addrs().ok().and_then(|mut addresses| addresses.next())
+        else { tracing::error!("failed resolving server address"); return Err(ServerStartError::InvalidAddress); };
+        let accept_backlog = config.accept_backlog;
+        let bind_result = enfync::blocking::extract(runtime_handle.spawn(async move {
+                bind_listener(address, accept_backlog)
+            })).map_err(|_| ServerStartError::RuntimeError)?;
+        let connection_listener = match bind_result
+        {
+            Ok(connection_listener) => connection_listener,
+            Err(err) =>
+            {
+                tracing::error!(?err, ?address, "failed binding server listener");
+                return Err(ServerStartError::BindFailed(err));
+            }
+        };
+        let server_address = connection_listener.local_addr().map_err(ServerStartError::BindFailed)?;
         let uses_tls = !matches!(acceptor_config, AcceptorConfig::Default);
 
         // launch the server core
+        let accept_rate_limit = config.accept_rate_limit;
+        let upgrade_timeout = config.upgrade_timeout;
         let server_running_signal = runtime_handle.spawn(
-                async move { run_server(app, connection_listener, acceptor_config).await }
+                async move {
+                    run_server(
+                            app,
+                            connection_listener,
+                            acceptor_config,
+                            accept_rate_limit,
+                            upgrade_timeout,
+                            flushed,
+                            tls_infos
+                        ).await
+                }
             );
 
+        // drain the server when a termination signal arrives
+        let client_event_sender: tokio::sync::mpsc::UnboundedSender<_> = server.into();  //extract the call sender
+        let shutdown_signal = Arc::new(Mutex::new(None));
+        if config.shutdown_on_signal
+        {
+            let accepting = accepting.clone();
+            let connection_counter = connection_counter.clone();
+            let session_info = session_info.clone();
+            let client_event_sender = client_event_sender.clone();
+            let shutdown_signal = shutdown_signal.clone();
+            let grace = config.shutdown_grace_period;
+            let runtime_handle_clone = runtime_handle.clone();
+            let _ = runtime_handle.spawn(
+                    async move {
+                        wait_for_termination_signal().await;
+                        tracing::info!("termination signal received, shutting down server");
+                        let signal = begin_lame_duck::<Channel>(
+                                &runtime_handle_clone,
+                                &accepting,
+                                connection_counter,
+                                session_info,
+                                client_event_sender,
+                                grace,
+                            );
+                        match shutdown_signal.lock()
+                        {
+                            Ok(mut shutdown_signal) => *shutdown_signal = Some(signal),
+                            Err(_) => tracing::error!("shutdown signal poisoned"),
+                        }
+                    }
+                );
+        }
+
         // finish assembling our server
         tracing::info!("new server created");
-        Server{
+        Ok(Server{
                 server_address,
                 uses_tls,
                 connection_counter,
-                client_event_sender: server.into(),  //extract the call sender
+                metrics: ServerMetrics::default(),
+                session_info,
+                topic_registry,
+                config,
+                runtime_handle,
+                next_stream_id: AtomicU64::new(0u64),
+                next_signal_id: AtomicU64::new(0u64),
+                context,
+                accepting,
+                authenticator,
+                ban_list,
+                shutdown_signal,
+                client_event_sender,
                 server_event_receiver,
+                server_event_notify,
                 server_closed_signal,
                 server_running_signal,
-            }
+                handler_thread,
+            })
     }
 }
 
@@ -0,0 +1,39 @@
+//local shortcuts
+
+//third-party shortcuts
+
+//standard shortcuts
+use std::any::Any;
+use std::sync::Arc;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Shared app context carried by a [`Server`](crate::Server) (e.g. a game world or database pool).
+///
+/// Set with [`ServerFactory::new_server_with_context()`](crate::ServerFactory::new_server_with_context). The context
+/// is passed to [`ServerConfig::load_shed`](crate::ServerConfig::load_shed) and
+/// [`ServerConfig::on_upgrade`](crate::ServerConfig::on_upgrade), and can be accessed with
+/// [`Server::context()`](crate::Server::context).
+#[derive(Debug, Clone, Default)]
+pub struct ServerContext
+{
+    context: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+impl ServerContext
+{
+    pub(crate) fn new(context: Option<Arc<dyn Any + Send + Sync>>) -> Self
+    {
+        Self{ context }
+    }
+
+    /// Access the context.
+    ///
+    /// Returns `None` if the server has no context or if the context is not of type `C`.
+    pub fn get<C: Any + Send + Sync>(&self) -> Option<&C>
+    {
+        self.context.as_ref()?.downcast_ref::<C>()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
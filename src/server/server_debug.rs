@@ -0,0 +1,67 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use serde::{Deserialize, Serialize};
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A snapshot of a session's internal state. See [`ServerDebugSnapshot`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SessionDebugSnapshot
+{
+    /// The session's id.
+    pub id: SessionID,
+    /// The session's generation number.
+    pub generation: u64,
+    /// The session's client environment.
+    pub env_type: Option<EnvType>,
+    /// `true` if the session uses the JSON debug format.
+    pub json_format: bool,
+    /// `true` if the session uses base64-encoded text frames.
+    pub text_frames: bool,
+    /// `true` once the session's connection report has been emitted.
+    pub connect_reported: bool,
+    /// `true` if the server has closed the session.
+    pub closed_by_server: bool,
+    /// Number of topics the session is subscribed to.
+    pub num_topics: usize,
+    /// Number of messages in the client's resend buffer.
+    pub resend_buffered: usize,
+    /// `true` if messages to the client are being held until it resumes.
+    pub resend_awaiting_resume: bool,
+    /// Number of the client's requests waiting for a response (i.e. unconsumed [`RequestToken`]s).
+    pub pending_requests: u32,
+    /// Number of messages counted against the session's rate limit in the current rate limit period.
+    pub rate_limit_count: u64,
+    /// Number of message bytes counted against the session's rate limit in the current rate limit period.
+    pub rate_limit_bytes: u64,
+    /// Number of messages to the client that are waiting in the session's outbound buffer.
+    pub outbound_queued: usize,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A snapshot of a server's internal state, for troubleshooting. See [`Server::debug_snapshot()`].
+///
+/// Sessions are sorted by id.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ServerDebugSnapshot
+{
+    /// Time since the server's connection handler started.
+    pub uptime: Duration,
+    /// Number of client connections.
+    pub num_connections: u64,
+    /// Number of server events waiting to be read by the server owner.
+    pub queued_events: usize,
+    /// Number of resend logs retained for connected and recently-disconnected clients.
+    pub num_resend_logs: usize,
+    /// Sessions registered in the connection handler.
+    pub sessions: Vec<SessionDebugSnapshot>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -8,25 +8,65 @@ use core::fmt::Debug;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Metadata about a session's connection, reported in [`ServerReport::Connected`].
+#[derive(Debug, Clone)]
+pub struct ConnectionDetails
+{
+    /// The session's id (the client id from its [`AuthRequest`]).
+    pub client_id: SessionID,
+    /// The client's environment type.
+    pub env_type: EnvType,
+    /// The session's source address.
+    pub peer_addr: std::net::SocketAddr,
+    /// TLS handshake details of the session's connection (`None` if the server doesn't use TLS).
+    pub tls_info: Option<SessionTlsInfo>,
+    /// When the session connected.
+    pub connected_at: std::time::SystemTime,
+    /// The protocol version negotiated with the client.
+    pub protocol_version: &'static str,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Emitted by servers when a client connects/disconnects.
 #[derive(Debug, Clone)]
 pub enum ServerReport<ConnectMsg: Debug + Clone>
 {
     /// The client connected.
     ///
-    /// This event synchronizes with the corresponding [`ClientReport::Connected`] event in the client. This means you
-    /// can send a 'server state sync' message to the client in response to this event, and the client will obtain that
-    /// message immediately after its [`ClientReport::Connected`] event.
+    /// This event synchronizes with the corresponding [`ClientReport::Connected`] (or [`ClientReport::Reconnected`])
+    /// event in the client. This means you can send a 'server state sync' message to the client in response to this
+    /// event, and the client will obtain that message immediately after its connection event.
     /// 
     /// See the [`ServerEvent::Request`] docs for one qualification on state syncing.
-    Connected(EnvType, ConnectMsg),
+    Connected(ConnectionDetails, ConnectMsg),
     /// The client disconnected.
-    Disconnected,
+    Disconnected(DisconnectReason),
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A protocol violation committed by a session.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ProtocolViolation
+{
+    /// The session sent a request with an id that was not greater than the id of its previous request.
+    ///
+    /// Clients assign strictly increasing request ids, so this indicates a duplicate or reused request id.
+    RequestIdReused(u64),
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Id of a message sent with [`Server::send_tracked()`].
+pub type MessageSignalId = u64;
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// An event received by the server.
+///
+/// Events don't carry the generation number of the session that produced them, see
+/// [`Server::next_with_generation()`].
 #[derive(Debug)]
 pub enum ServerEvent<ConnectMsg: Debug + Clone, ClientMsg: Debug, ClientRequest: Debug>
 {
@@ -64,6 +104,19 @@ pub enum ServerEvent<ConnectMsg: Debug + Clone, ClientMsg: Debug, ClientRequest:
     ///             will receive a server-state sync message that will include the updated state from the prior request
     ///             (which at that point would have been sent two full reconnect cycles ago).
     Request(ClientRequest, RequestToken),
+    /// The session exceeded the server's rate limit. Contains the number of messages dropped.
+    ///
+    /// This is emitted at most once per rate limit period, with the number of messages dropped in that period. The
+    /// event is emitted when the rate limit period ends, when the session's next message is accepted (if that happens
+    /// first), or immediately before the session is closed by the server. See [`RateLimitPolicy`].
+    RateLimited(u64),
+    /// The session violated the client/server protocol and was closed by the server.
+    ProtocolViolation(ProtocolViolation),
+    /// Reports whether a message sent with [`Server::send_tracked()`] was written to the session's socket.
+    ///
+    /// `delivered` is `false` if the session was not connected or the write failed. Note that a successful write does
+    /// not guarantee the client received the message (e.g. if the connection drops before the bytes arrive).
+    DeliveryReceipt{ signal_id: MessageSignalId, delivered: bool },
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,105 @@
+//local shortcuts
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Upper bounds of the buckets in a [`LatencyHistogram`].
+pub const LATENCY_BUCKETS: [Duration; 8] = [
+        Duration::from_millis(1),
+        Duration::from_millis(5),
+        Duration::from_millis(10),
+        Duration::from_millis(50),
+        Duration::from_millis(100),
+        Duration::from_millis(500),
+        Duration::from_secs(1),
+        Duration::from_secs(5),
+    ];
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A snapshot of latency measurements, counted in the buckets defined by [`LATENCY_BUCKETS`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LatencyHistogram
+{
+    /// Number of measurements in each bucket.
+    ///
+    /// `counts[i]` is the number of measurements greater than `LATENCY_BUCKETS[i - 1]` and at most
+    /// `LATENCY_BUCKETS[i]`. The last entry counts measurements greater than the last bucket.
+    pub counts: [u64; LATENCY_BUCKETS.len() + 1],
+}
+
+impl LatencyHistogram
+{
+    /// Get the total number of measurements.
+    pub fn total(&self) -> u64
+    {
+        self.counts.iter().sum()
+    }
+
+    /// Get the upper bound of the bucket containing the given percentile (`0.0..=1.0`) of measurements.
+    ///
+    /// Returns `None` if there are no measurements, or if the percentile falls in the unbounded last bucket.
+    pub fn percentile(&self, percentile: f64) -> Option<Duration>
+    {
+        let total = self.total();
+        if total == 0 { return None; }
+
+        let target = ((total as f64) * percentile.clamp(0.0, 1.0)).ceil().max(1.0) as u64;
+        let mut count = 0;
+        for (bucket, bucket_count) in self.counts.iter().enumerate()
+        {
+            count += bucket_count;
+            if count >= target { return LATENCY_BUCKETS.get(bucket).copied(); }
+        }
+
+        None
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Metrics collected by a server.
+///
+/// The metrics are shared, so clones observe the same values. See [`Server::metrics()`].
+#[derive(Debug, Clone, Default)]
+pub struct ServerMetrics
+{
+    /// request latency bucket counts
+    request_latency: Arc<[AtomicU64; LATENCY_BUCKETS.len() + 1]>,
+}
+
+impl ServerMetrics
+{
+    /// Get the distribution of request-handling latencies.
+    ///
+    /// Latency is measured from when a client request is received by the server to when it is responded to or
+    /// acknowledged with [`Server::respond()`] or [`Server::ack()`]. Rejected requests are not measured.
+    pub fn request_latency(&self) -> LatencyHistogram
+    {
+        let mut counts = [0u64; LATENCY_BUCKETS.len() + 1];
+        for (count, bucket_count) in counts.iter_mut().zip(self.request_latency.iter())
+        {
+            *count = bucket_count.load(Ordering::Relaxed);
+        }
+
+        LatencyHistogram{ counts }
+    }
+
+    pub(crate) fn record_request_latency(&self, latency: Duration)
+    {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bucket| latency <= *bucket)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.request_latency[bucket].fetch_add(1u64, Ordering::Relaxed);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -5,8 +5,8 @@ use crate::*;
 use bincode::Options;
 
 //standard shortcuts
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::fmt::Debug;
 use std::vec::Vec;
 
@@ -17,20 +17,58 @@ pub(crate) struct SessionHandler<Channel: ChannelPack>
 {
     /// id of this session
     pub(crate) id: SessionID,
+    /// generation number of this session
+    pub(crate) generation: u64,
+    /// tracing span for the server's logs
+    pub(crate) span: tracing::Span,
     /// this session
-    pub(crate) session: ezsockets::Session<SessionID, ()>,
+    pub(crate) session: ezsockets::Session<SessionID, SessionCall>,
     /// sender for forwarding messages from the session's client to the server
-    pub(crate) server_event_sender: crossbeam::channel::Sender<
-        SessionSourceMsg<SessionID, ServerEventFrom<Channel>>
-    >,
+    pub(crate) server_event_sender: ServerEventSender<Channel>,
 
     /// config: maximum message size (bytes)
     pub(crate) max_msg_size: u32,
+    /// config: hook applied to sent messages
+    pub(crate) on_send: Option<Arc<dyn BytesHookFn>>,
+    /// config: hook applied to received messages
+    pub(crate) on_recv: Option<Arc<dyn BytesHookFn>>,
+    /// config: cipher for decrypting received messages
+    pub(crate) payload_cipher: Option<Arc<dyn PayloadCipher>>,
+    /// config: compression for decompressing received messages
+    pub(crate) payload_compression: Option<PayloadCompression>,
+    /// observer for lifecycle events
+    pub(crate) observer: Arc<dyn Observer>,
+    /// middleware applied to client messages
+    pub(crate) inbound_middleware: Option<Arc<dyn InboundMiddlewareFn<Channel::ClientMsg>>>,
     /// client's environment type
     pub(crate) client_env_type: EnvType,
+    /// the session's connection details (for reporting the connection after receiving the connect message)
+    pub(crate) connection_details: ConnectionDetails,
+    /// the session's message format
+    pub(crate) msg_format: MsgFormat,
 
-    /// rate limit tracker
-    pub(crate) rate_limit_tracker: RateLimitTracker,
+    /// rate limit tracker (shared with the connection handler for debug snapshots)
+    pub(crate) rate_limit_tracker: Arc<Mutex<RateLimitTracker>>,
+    /// config: rate limit policy
+    pub(crate) rate_limit_policy: RateLimitPolicy,
+    /// number of messages dropped for exceeding the rate limit since the last rate limit report
+    pub(crate) rate_limit_dropped: u64,
+    /// indicates a rate limit report is scheduled for the end of the current rate limit period
+    pub(crate) rate_limit_report_scheduled: bool,
+    /// id of the last request received from the client (request ids must be strictly increasing)
+    pub(crate) last_request_id: Option<u64>,
+    /// config: max number of outstanding requests
+    pub(crate) max_concurrent_requests: Option<u32>,
+    /// number of requests waiting for a response (shared with request tokens)
+    pub(crate) outstanding_requests: Arc<AtomicU32>,
+    /// messages waiting in the session's outbound buffer (shared with the connection handler)
+    pub(crate) outbound_queue: Arc<Mutex<OutboundQueue>>,
+    /// reassembles streamed messages
+    pub(crate) stream_reassembler: StreamReassembler,
+    /// topic subscriptions (shared with the server)
+    pub(crate) topic_registry: Arc<Mutex<TopicRegistry>>,
+    /// the client's resend log (shared with the connection handler; `None` if resends are disabled)
+    pub(crate) resend_log: Option<Arc<Mutex<ResendLog>>>,
 
     /// session wrapper for sending request rejections
     pub(crate) request_rejector: Arc<dyn RequestRejectorFn>,
@@ -38,13 +76,25 @@ pub(crate) struct SessionHandler<Channel: ChannelPack>
     /// Signal used to inform request tokens of the session's death, to avoid sending responses to new sessions
     /// for requests made with old sessions.
     pub(crate) death_signal: Arc<AtomicBool>,
+    /// Flag set when the server closes this session, used to classify the session's disconnect.
+    pub(crate) closed_by_server: Arc<AtomicBool>,
+    /// Flag set when the session's connection report has been emitted.
+    ///
+    /// If not set when the session is created, then the client's first binary frame will be its connect message.
+    pub(crate) connect_reported: Arc<AtomicBool>,
+    /// Tracks when the session last received a frame, used to classify the session's disconnect.
+    pub(crate) recv_activity: RecvActivity,
+    /// Tracks heartbeat pings sent to the client (`None` if the client is not pinged).
+    pub(crate) pong_tracker: Option<PongTracker>,
+    /// Id for the next heartbeat ping.
+    pub(crate) next_ping_id: u64,
 }
 
 #[async_trait::async_trait]
 impl<Channel: ChannelPack> ezsockets::SessionExt for SessionHandler<Channel>
 {
     type ID   = SessionID;
-    type Call = ();
+    type Call = SessionCall;
 
     fn id(&self) -> &SessionID
     {
@@ -54,13 +104,26 @@ impl<Channel: ChannelPack> ezsockets::SessionExt for SessionHandler<Channel>
     // Receive text from client (via session connection).
     async fn on_text(&mut self, text: String) -> Result<(), ezsockets::Error>
     {
+        let span = self.span.clone();
+        let _entered = span.enter();
+        self.recv_activity.touch();
+
+        // handle messages from sessions that use text frames
+        // - base64 text can't contain ':', so it can't be confused with WASM ping/pong text
+        match self.msg_format
+        {
+            MsgFormat::Json => { self.handle_json_text(text); return Ok(()); }
+            MsgFormat::Base64 if !text.contains(':') => return self.handle_base64_text(text).await,
+            _ => (),
+        }
+
         match self.client_env_type
         {
             EnvType::Native =>
             {
                 // reject text from client
                 tracing::trace!("received text from native client (not implemented), closing session...");
-                self.close("text not allowed"); return Ok(());
+                self.close(CloseReason::ProtocolViolation, "text not allowed"); return Ok(());
             }
             EnvType::Wasm =>
             {
@@ -69,7 +132,7 @@ impl<Channel: ChannelPack> ezsockets::SessionExt for SessionHandler<Channel>
                 else
                 {
                     tracing::trace!("received invalid text from WASM client, closing session...");
-                    self.close("only ping/pong text allowed"); return Ok(());
+                    self.close(CloseReason::ProtocolViolation, "only ping/pong text allowed"); return Ok(());
                 };
 
                 // try to deserialize timestamp
@@ -77,7 +140,7 @@ impl<Channel: ChannelPack> ezsockets::SessionExt for SessionHandler<Channel>
                 else
                 {
                     tracing::trace!("received invalid ping/pong timestamp from WASM client, closing session...");
-                    self.close("only timestamp ping/pong allowed"); return Ok(());
+                    self.close(CloseReason::ProtocolViolation, "only timestamp ping/pong allowed"); return Ok(());
                 };
 
                 match var
@@ -95,7 +158,7 @@ impl<Channel: ChannelPack> ezsockets::SessionExt for SessionHandler<Channel>
                     _ =>
                     {
                         tracing::trace!("received invalid ping/pong timestamp from WASM client, closing session...");
-                        self.close("only ping/pong prefixes allowed");
+                        self.close(CloseReason::ProtocolViolation, "only ping/pong prefixes allowed");
                     }
                 }
             }
@@ -105,87 +168,459 @@ impl<Channel: ChannelPack> ezsockets::SessionExt for SessionHandler<Channel>
     }
 
     // Receive binary from client (via session connection).
-    async fn on_binary(&mut self, bytes: Vec<u8>) -> Result<(), ezsockets::Error>
+    async fn on_binary(&mut self, mut bytes: Vec<u8>) -> Result<(), ezsockets::Error>
     {
+        let span = self.span.clone();
+        let _entered = span.enter();
+        self.recv_activity.touch();
+
         // try to update rate limit tracker
-        if !self.rate_limit_tracker.try_count_msg()
-        {
-            tracing::trace!("client messages exceeded rate limit, closing session...");
-            self.close("rate limit violation"); return Ok(());
-        }
+        self.observer.on_msg_in(self.id, bytes.len());
+        if !self.try_count_msg(bytes.len()) { return Ok(()); }
 
         // try to deserialize message
         if bytes.len() > self.max_msg_size as usize
         {
             tracing::trace!("received client message that's too large, closing session...");
-            self.close("message size violation"); return Ok(());
+            self.close(CloseReason::MsgSizeViolation, "message size violation"); return Ok(());
+        }
+        if decrypt_payload(&self.payload_cipher, &mut bytes).is_err()
+        {
+            tracing::trace!("received client message that failed to decrypt, closing session...");
+            self.close(CloseReason::ProtocolViolation, "decryption failure"); return Ok(());
         }
+        if decompress_payload(&self.payload_compression, &mut bytes, self.max_msg_size as usize).is_err()
+        {
+            tracing::trace!("received client message that failed to decompress, closing session...");
+            self.close(CloseReason::ProtocolViolation, "decompression failure"); return Ok(());
+        }
+        if bytes.len() > self.max_msg_size as usize
+        {
+            // in case the codec doesn't respect the size limit
+            tracing::trace!("received client message that's too large after decompression, closing session...");
+            self.close(CloseReason::MsgSizeViolation, "message size violation"); return Ok(());
+        }
+        apply_bytes_hook(&self.on_recv, &mut bytes);
+
+        // handle the connect message if it is expected in the first binary frame
+        if !self.connect_reported.load(Ordering::Acquire)
+        {
+            self.handle_connect_msg(&bytes);
+            return Ok(());
+        }
+
+        // check the message's type-specific size limit
+        if let Some(limit) = server_meta_event_size_limit::<Channel>(&bytes)
+        {
+            if bytes.len() > limit as usize
+            {
+                tracing::trace!(limit, "received client message that exceeds its type's size limit, closing session...");
+                self.close(CloseReason::MsgSizeViolation, "message size violation"); return Ok(());
+            }
+        }
+
         let Ok(message) = bincode::DefaultOptions::new().deserialize(&bytes[..])
         else
         {
             tracing::trace!("received client message that failed to deserialize, closing session...");
-            self.close("deserialization failure"); return Ok(());
+            self.close(CloseReason::ProtocolViolation, "deserialization failure"); return Ok(());
         };
 
+        self.handle_message(message);
+
+        Ok(())
+    }
+
+    // Responds to calls to the session connected to this handler (i.e. ezsockets::Session::call()).
+    async fn on_call(&mut self, msg: SessionCall) -> Result<(), ezsockets::Error>
+    {
+        let span = self.span.clone();
+        let _entered = span.enter();
+
+        match msg
+        {
+            SessionCall::ReportRateLimited =>
+            {
+                self.rate_limit_report_scheduled = false;
+                self.report_rate_limited();
+            }
+            SessionCall::Heartbeat =>
+            {
+                self.handle_heartbeat();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<Channel: ChannelPack> SessionHandler<Channel>
+{
+    /// Count a message against the rate limit.
+    /// - Returns `false` if the message should be discarded.
+    fn try_count_msg(&mut self, num_bytes: usize) -> bool
+    {
+        let within_limit = self.rate_limit_tracker
+            .lock()
+            .map(|mut rate_limit_tracker| rate_limit_tracker.try_count_msg_bytes(num_bytes as u64))
+            .ok();
+        let Some(within_limit) = within_limit
+        else
+        {
+            tracing::error!("rate limit tracker poisoned, closing session...");
+            self.close(CloseReason::ServerError, "session error");
+            return false;
+        };
+        if !within_limit
+        {
+            self.rate_limit_dropped += 1;
+            self.observer.on_rate_limit_drop(self.id);
+            match self.rate_limit_policy
+            {
+                RateLimitPolicy::CloseSession =>
+                {
+                    tracing::trace!("client messages exceeded rate limit, closing session...");
+                    self.close(CloseReason::RateLimited, "rate limit violation");
+                }
+                RateLimitPolicy::DropMessages =>
+                {
+                    tracing::trace!("client messages exceeded rate limit, dropping message...");
+                    self.schedule_rate_limit_report();
+                }
+            }
+            return false;
+        }
+        self.report_rate_limited();
+
+        true
+    }
+
+    /// Handle a message from a session that uses the JSON debug format.
+    fn handle_json_text(&mut self, text: String)
+    {
+        // try to update rate limit tracker
+        self.observer.on_msg_in(self.id, text.len());
+        if !self.try_count_msg(text.len()) { return; }
+
+        // try to deserialize message
+        if text.len() > self.max_msg_size as usize
+        {
+            tracing::trace!("received client message that's too large, closing session...");
+            self.close(CloseReason::MsgSizeViolation, "message size violation"); return;
+        }
+
+        let Ok(message) = serde_json::from_str(&text)
+        else
+        {
+            tracing::trace!("received JSON client message that failed to deserialize, closing session...");
+            self.close(CloseReason::ProtocolViolation, "deserialization failure"); return;
+        };
+
+        self.handle_message(message);
+    }
+
+    /// Handle a message from a session that uses base64-encoded text frames.
+    async fn handle_base64_text(&mut self, text: String) -> Result<(), ezsockets::Error>
+    {
+        // reject oversized frames before decoding them
+        // - base64 encodes 3 bytes in 4 characters
+        if text.len() / 4 * 3 > self.max_msg_size as usize
+        {
+            tracing::trace!("received client message that's too large, closing session...");
+            self.close(CloseReason::MsgSizeViolation, "message size violation"); return Ok(());
+        }
+
+        let Ok(bytes) = decode_text_frame(&text)
+        else
+        {
+            tracing::trace!("received text frame that failed to decode, closing session...");
+            self.close(CloseReason::ProtocolViolation, "text frame decoding failure"); return Ok(());
+        };
+
+        ezsockets::SessionExt::on_binary(self, bytes).await
+    }
+
+    /// Apply the inbound middleware to a client message. Returns `None` if the message should be dropped.
+    fn apply_inbound_middleware(&self, msg: Channel::ClientMsg) -> Option<Channel::ClientMsg>
+    {
+        let Some(middleware) = &self.inbound_middleware else { return Some(msg); };
+        let msg = (middleware)(self.id, msg);
+        if msg.is_none() { tracing::trace!("inbound middleware dropped client message"); }
+        msg
+    }
+
+    /// Handle a deserialized message from the client.
+    fn handle_message(&mut self, message: ServerMetaEventFrom<Channel>)
+    {
         // decide what to do with the message
         match message
         {
             ServerMetaEventFrom::<Channel>::Msg(msg) =>
             {
+                let Some(msg) = self.apply_inbound_middleware(msg) else { return; };
+
                 // try to forward client message to session owner
                 if let Err(err) = self.server_event_sender.send(
-                        SessionSourceMsg::new(self.id, ServerEventFrom::<Channel>::Msg(msg))
+                        SessionSourceMsg::new(self.id, self.generation, ServerEventFrom::<Channel>::Msg(msg))
                     )
                 {
                     tracing::debug!(?err, "client msg sender is broken, closing session...");
-                    self.close("session error"); return Ok(());
+                    self.close(CloseReason::ServerError, "session error"); return;
                 }
             }
             ServerMetaEventFrom::<Channel>::Request(request, request_id) =>
             {
+                // validate the request id
+                // - this prevents duplicate requests and responses to requests that were already handled
+                if self.last_request_id.map_or(false, |last_request_id| request_id <= last_request_id)
+                {
+                    tracing::trace!(request_id, "received reused request id, closing session...");
+                    self.report_protocol_violation(ProtocolViolation::RequestIdReused(request_id));
+                    self.close(CloseReason::ProtocolViolation, "request id violation"); return;
+                }
+                self.last_request_id = Some(request_id);
+
+                // reject the request if the session has too many outstanding requests
+                if let Some(max_concurrent_requests) = self.max_concurrent_requests
+                {
+                    if self.outstanding_requests.load(Ordering::Acquire) >= max_concurrent_requests
+                    {
+                        tracing::trace!(request_id, "too many outstanding requests, rejecting request...");
+                        (self.request_rejector)(request_id);
+                        return;
+                    }
+                }
+
                 // prepare token
                 let token = RequestToken::new(
                         self.id,
                         request_id,
                         self.request_rejector.clone(),
                         self.death_signal.clone(),
+                        self.outstanding_requests.clone(),
                     );
 
                 // try to forward client request to session owner
                 if let Err(err) = self.server_event_sender.send(
-                        SessionSourceMsg::new(self.id, ServerEventFrom::<Channel>::Request(request, token))
+                        SessionSourceMsg::new(self.id, self.generation, ServerEventFrom::<Channel>::Request(request, token))
                     )
                 {
                     tracing::debug!(?err, "client msg sender is broken, closing session...");
-                    self.close("session error"); return Ok(());
+                    self.close(CloseReason::ServerError, "session error"); return;
+                }
+            }
+            ServerMetaEventFrom::<Channel>::Chunk(chunk) =>
+            {
+                // try to reassemble the stream
+                let bytes = match self.stream_reassembler.add_chunk(chunk)
+                {
+                    Ok(Some(bytes)) => bytes,
+                    Ok(None) => return,
+                    Err(err) =>
+                    {
+                        tracing::trace!(?err, "received invalid stream chunk, closing session...");
+                        self.close(CloseReason::ProtocolViolation, "stream violation"); return;
+                    }
+                };
+
+                // try to deserialize the streamed message
+                let Ok(msg) = bincode::DefaultOptions::new().deserialize(&bytes[..])
+                else
+                {
+                    tracing::trace!("received streamed client message that failed to deserialize, closing session...");
+                    self.close(CloseReason::ProtocolViolation, "deserialization failure"); return;
+                };
+                let Some(msg) = self.apply_inbound_middleware(msg) else { return; };
+
+                // try to forward client message to session owner
+                if let Err(err) = self.server_event_sender.send(
+                        SessionSourceMsg::new(self.id, self.generation, ServerEventFrom::<Channel>::Msg(msg))
+                    )
+                {
+                    tracing::debug!(?err, "client msg sender is broken, closing session...");
+                    self.close(CloseReason::ServerError, "session error"); return;
+                }
+            }
+            ServerMetaEventFrom::<Channel>::Subscribe(topic) =>
+            {
+                tracing::trace!(self.id, topic, "subscribing session to topic");
+                let Ok(mut topic_registry) = self.topic_registry.lock()
+                else { tracing::error!("topic registry poisoned, closing session..."); self.close(CloseReason::ServerError, "session error"); return; };
+                topic_registry.subscribe(self.id, topic);
+            }
+            ServerMetaEventFrom::<Channel>::Unsubscribe(topic) =>
+            {
+                tracing::trace!(self.id, topic, "unsubscribing session from topic");
+                let Ok(mut topic_registry) = self.topic_registry.lock()
+                else { tracing::error!("topic registry poisoned, closing session..."); self.close(CloseReason::ServerError, "session error"); return; };
+                topic_registry.unsubscribe(self.id, &topic);
+            }
+            ServerMetaEventFrom::<Channel>::Resume(last_received) =>
+            {
+                self.handle_resume(last_received);
+            }
+            ServerMetaEventFrom::<Channel>::Ping(ping_id) =>
+            {
+                self.handle_ping(ping_id);
+            }
+            ServerMetaEventFrom::<Channel>::Pong(_) =>
+            {
+                match &mut self.pong_tracker
+                {
+                    Some(pong_tracker) => pong_tracker.on_pong(),
+                    None => tracing::trace!(self.id, "ignoring unexpected pong from client"),
                 }
             }
         }
+    }
 
-        Ok(())
+    /// Report the session's connection using a connect message sent in the client's first binary frame.
+    fn handle_connect_msg(&mut self, bytes: &[u8])
+    {
+        let Ok(connect_msg) = bincode::DefaultOptions::new().deserialize::<Channel::ConnectMsg>(bytes)
+        else
+        {
+            tracing::trace!("received connect message that failed to deserialize, closing session...");
+            self.close(CloseReason::ProtocolViolation, "Connect message malformed."); return;
+        };
+
+        // tell the client its session is ready
+        // - do this before reporting the connection so the client is ready before any messages sent in response to
+        //   the report
+        match bincode::DefaultOptions::new().serialize(&ClientMetaEventFrom::<Channel>::Ready(self.max_msg_size))
+        {
+            Ok(mut ser_msg) =>
+            {
+                apply_bytes_hook(&self.on_send, &mut ser_msg);
+                if let Err(()) = send_session_frame(&self.session, &self.outbound_queue, self.msg_format, ser_msg)
+                { tracing::debug!(self.id, "failed sending ready message to session"); }
+            }
+            Err(_) => tracing::error!(self.id, "serializing ready message failed"),
+        }
+
+        let report = ServerReport::<Channel::ConnectMsg>::Connected(self.connection_details.clone(), connect_msg);
+        if let Err(err) = self.server_event_sender.send(
+                SessionSourceMsg::new(self.id, self.generation, ServerEventFrom::<Channel>::Report(report))
+            )
+        {
+            tracing::error!(?err, "forwarding connection report failed, closing session...");
+            self.close(CloseReason::ServerError, "Server internal error."); return;
+        }
+        self.connect_reported.store(true, Ordering::Release);
     }
 
-    // Responds to calls to the session connected to this handler (i.e. ezsockets::Session::call()).
-    async fn on_call(&mut self, _msg: ()) -> Result<(), ezsockets::Error>
+    /// Resend messages the client missed, then release messages held by the client's resend log.
+    fn handle_resume(&mut self, last_received: Option<u64>)
     {
-        tracing::info!(self.id, "received call (not implemented), closing session...");
-        self.close("session error"); return Ok(());
+        let Some(resend_log) = self.resend_log.clone()
+        else { tracing::trace!("ignoring resume from client (resends disabled)"); return; };
+        let Ok(mut resend_log) = resend_log.lock()
+        else { tracing::error!("resend log poisoned, closing session..."); self.close(CloseReason::ServerError, "session error"); return; };
+
+        let Some((base_seq, resends)) = resend_log.resume(last_received)
+        else
+        {
+            tracing::trace!("received unexpected resume from client, closing session...");
+            self.close(CloseReason::ProtocolViolation, "resume violation"); return;
+        };
+
+        // send the sequence base followed by the resent messages
+        // - this is done inside the resend log lock so new messages are sent after the resent messages
+        tracing::trace!(self.id, base_seq, num_resends = resends.len(), "resuming client message sequence");
+        let Ok(mut ser_msg) = bincode::DefaultOptions::new().serialize(&ClientMetaEventFrom::<Channel>::Resumed(base_seq))
+        else { tracing::error!("serializing resume failed, closing session..."); self.close(CloseReason::ServerError, "session error"); return; };
+        apply_bytes_hook(&self.on_send, &mut ser_msg);
+
+        for bytes in std::iter::once(ser_msg).chain(resends)
+        {
+            if let Err(_) = send_session_frame(&self.session, &self.outbound_queue, self.msg_format, bytes)
+            { tracing::debug!(self.id, "dropping message sent to broken session"); return; }
+        }
+    }
+
+    /// Answer an on-demand ping from the client.
+    fn handle_ping(&mut self, ping_id: u64)
+    {
+        let Ok(mut ser_msg) = bincode::DefaultOptions::new().serialize(&ClientMetaEventFrom::<Channel>::Pong(ping_id))
+        else { tracing::error!(self.id, "serializing pong failed"); return; };
+        apply_bytes_hook(&self.on_send, &mut ser_msg);
+
+        if let Err(()) = send_session_frame(&self.session, &self.outbound_queue, self.msg_format, ser_msg)
+        { tracing::debug!(self.id, "failed sending pong to session"); }
+    }
+
+    /// Send a heartbeat ping to the client, or close the session if the client missed too many pongs.
+    fn handle_heartbeat(&mut self)
+    {
+        let Some(pong_tracker) = &mut self.pong_tracker else { return; };
+        if !pong_tracker.ping_due()
+        {
+            tracing::trace!(self.id, "client missed too many pongs, closing session...");
+            self.close(CloseReason::Unresponsive, "missed pongs"); return;
+        }
+
+        let ping_id = self.next_ping_id;
+        self.next_ping_id += 1;
+        let Ok(mut ser_msg) = bincode::DefaultOptions::new().serialize(&ClientMetaEventFrom::<Channel>::Ping(ping_id))
+        else { tracing::error!(self.id, "serializing heartbeat ping failed"); return; };
+        apply_bytes_hook(&self.on_send, &mut ser_msg);
+
+        if let Err(()) = send_session_frame(&self.session, &self.outbound_queue, self.msg_format, ser_msg)
+        { tracing::debug!(self.id, "failed sending heartbeat ping to session"); }
+    }
+
+    /// Report a protocol violation.
+    fn report_protocol_violation(&mut self, violation: ProtocolViolation)
+    {
+        if let Err(err) = self.server_event_sender.send(
+                SessionSourceMsg::new(self.id, self.generation, ServerEventFrom::<Channel>::ProtocolViolation(violation))
+            )
+        {
+            tracing::debug!(?err, "failed forwarding protocol violation report");
+        }
+    }
+
+    /// Schedule a rate limit report for the end of the current rate limit period.
+    /// - If the session's next message is accepted before the timer fires, the report is emitted then instead.
+    fn schedule_rate_limit_report(&mut self)
+    {
+        if self.rate_limit_report_scheduled { return; }
+        self.rate_limit_report_scheduled = true;
+
+        let delay = match self.rate_limit_tracker.lock()
+        {
+            Ok(rate_limit_tracker) => rate_limit_tracker.time_until_next_period(),
+            Err(_) => { tracing::error!("rate limit tracker poisoned"); std::time::Duration::default() }
+        };
+        let session = self.session.clone();
+        tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                // this fails if the session is already closed
+                let _ = session.call(SessionCall::ReportRateLimited);
+            });
+    }
+
+    /// Report messages dropped for exceeding the rate limit (if any).
+    fn report_rate_limited(&mut self)
+    {
+        if self.rate_limit_dropped == 0 { return; }
+        let dropped_count = std::mem::take(&mut self.rate_limit_dropped);
+
+        if let Err(err) = self.server_event_sender.send(
+                SessionSourceMsg::new(self.id, self.generation, ServerEventFrom::<Channel>::RateLimited(dropped_count))
+            )
+        {
+            tracing::debug!(?err, "failed forwarding rate limit report");
+        }
     }
-}
 
-impl<Channel: ChannelPack> SessionHandler<Channel>
-{
     /// Close the session
-    fn close(&mut self, reason: &str)
+    fn close(&mut self, close_reason: CloseReason, reason: &str)
     {
+        self.report_rate_limited();
         tracing::info!(self.id, "closing...");
-        if let Err(_) = self.session.close(Some(
-                ezsockets::CloseFrame
-                {
-                    code   : ezsockets::CloseCode::Error,
-                    reason : String::from(reason)
-                }
-            ))
+        self.closed_by_server.store(true, Ordering::Release);
+        if let Err(_) = self.session.close(Some(close_reason.close_frame(reason)))
         {
             tracing::error!(self.id, "failed closing session");
         }
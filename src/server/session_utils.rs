@@ -5,6 +5,168 @@ use crate::*;
 
 //standard shortcuts
 use core::fmt::Debug;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Calls a session's handler makes to itself (i.e. `ezsockets::Session::call()`).
+#[derive(Debug)]
+pub(crate) enum SessionCall
+{
+    /// Report messages dropped for exceeding the rate limit at the end of the rate limit period.
+    ReportRateLimited,
+    /// Send a heartbeat ping to the client (see [`ServerConfig::max_missed_pongs`]).
+    Heartbeat,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Spawn a task that asks a session to ping its client at each heartbeat.
+/// - The task exits once the session is closed.
+pub(crate) fn spawn_session_heartbeat(session: ezsockets::Session<SessionID, SessionCall>, heartbeat_interval: Duration)
+{
+    tokio::spawn(async move {
+            loop
+            {
+                tokio::time::sleep(heartbeat_interval).await;
+                // this fails if the session is already closed
+                if session.call(SessionCall::Heartbeat).is_err() { break; }
+            }
+        });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A session registered in the connection handler.
+#[derive(Debug)]
+pub(crate) struct SessionEntry
+{
+    /// the session
+    pub(crate) session: ezsockets::Session<SessionID, SessionCall>,
+    /// set when the server closes the session (used to classify disconnects)
+    pub(crate) closed_by_server: Arc<AtomicBool>,
+    /// set when the session's connection report has been emitted
+    pub(crate) connect_reported: Arc<AtomicBool>,
+    /// the session's message format
+    pub(crate) msg_format: MsgFormat,
+    /// the session's generation number
+    pub(crate) generation: u64,
+    /// tracks when the session last received a frame (used to classify disconnects)
+    pub(crate) recv_activity: RecvActivity,
+    /// the session's keepalive timeout
+    pub(crate) keepalive_timeout: std::time::Duration,
+    /// messages waiting in the session's outbound buffer (shared with the session handler)
+    pub(crate) outbound_queue: Arc<Mutex<OutboundQueue>>,
+    /// number of requests waiting for a response (shared with the session handler)
+    pub(crate) outstanding_requests: Arc<AtomicU32>,
+    /// the session's rate limit tracker (shared with the session handler)
+    pub(crate) rate_limit_tracker: Arc<Mutex<RateLimitTracker>>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks messages that are waiting in a session's outbound buffer (for debug snapshots).
+/// - Messages are sent in order, so messages that finished sending are pruned from the front.
+#[derive(Debug, Default)]
+pub(crate) struct OutboundQueue
+{
+    /// signals of messages that may still be sending
+    pending: VecDeque<ezsockets::MessageSignal>,
+}
+
+impl OutboundQueue
+{
+    /// Add a message that was just sent.
+    pub(crate) fn add(&mut self, signal: ezsockets::MessageSignal)
+    {
+        self.prune();
+        self.pending.push_back(signal);
+    }
+
+    /// Get the number of messages that are still sending.
+    pub(crate) fn len(&mut self) -> usize
+    {
+        self.prune();
+        self.pending.len()
+    }
+
+    /// Remove messages that finished sending.
+    fn prune(&mut self)
+    {
+        while let Some(signal) = self.pending.front()
+        {
+            if signal.status() == ezsockets::MessageStatus::Sending { break; }
+            self.pending.pop_front();
+        }
+    }
+}
+
+/// Record a message in a session's outbound queue.
+pub(crate) fn track_outbound(outbound_queue: &Mutex<OutboundQueue>, signal: &ezsockets::MessageSignal)
+{
+    match outbound_queue.lock()
+    {
+        Ok(mut outbound_queue) => outbound_queue.add(signal.clone()),
+        Err(_) => tracing::error!("outbound queue poisoned"),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Send a serialized message to a session in the session's frame format.
+/// - Returns `Err` if the session is broken.
+pub(crate) fn send_session_frame(
+    session        : &ezsockets::Session<SessionID, SessionCall>,
+    outbound_queue : &Mutex<OutboundQueue>,
+    msg_format     : MsgFormat,
+    bytes          : Vec<u8>
+) -> Result<ezsockets::MessageSignal, ()>
+{
+    let result = match msg_format
+    {
+        MsgFormat::Base64 => session.text(encode_text_frame(&bytes)),
+        _                 => session.binary(bytes),
+    };
+    let signal = result.map_err(|_| ())?;
+    track_outbound(outbound_queue, &signal);
+    Ok(signal)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Metadata about a connected session.
+/// - Shared between the server API and the connection handler.
+#[derive(Debug)]
+pub(crate) struct SessionInfo
+{
+    /// when the session connected
+    pub(crate) connected_at: std::time::SystemTime,
+    /// the auth request the session was authenticated with
+    pub(crate) auth_request: AuthRequest,
+    /// the session's source address
+    pub(crate) peer_addr: std::net::SocketAddr,
+    /// TLS handshake details of the session's connection
+    pub(crate) tls_info: Option<SessionTlsInfo>,
+    /// the session's client environment
+    pub(crate) env_type: EnvType,
+    /// the session's generation number
+    pub(crate) generation: u64,
+    /// app context attached to the session by [`ServerConfig::on_upgrade`]
+    pub(crate) context: Option<Arc<dyn std::any::Any + Send + Sync>>,
+    /// app tag attached to the session with [`Server::set_session_tag()`]
+    pub(crate) tag: Option<Box<dyn std::any::Any + Send + Sync>>,
+}
+
+/// Registry of metadata for connected sessions.
+pub(crate) type SessionInfoRegistry = Arc<RwLock<HashMap<SessionID, SessionInfo>>>;
+
+/// Session contexts returned by [`ServerConfig::on_upgrade`] for connection requests that haven't connected yet.
+/// - Keyed by client id. The hook runs before the connection is upgraded, and the connection handler takes the
+///   context when the session connects.
+pub(crate) type UpgradeContexts = Arc<Mutex<HashMap<SessionID, Option<Arc<dyn std::any::Any + Send + Sync>>>>>;
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -27,13 +189,261 @@ impl<I, T> SessionTargetMsg<I, T>
 #[derive(Debug)]
 pub(crate) struct SessionSourceMsg<I, T>
 {
-    pub(crate) id  : I,
-    pub(crate) msg : T
+    pub(crate) id         : I,
+    pub(crate) generation : u64,
+    pub(crate) msg        : T
 }
 
 impl<I, T> SessionSourceMsg<I, T>
 {
-    pub(crate) fn new(id: I, msg: T) -> SessionSourceMsg<I, T> { SessionSourceMsg::<I, T> { id, msg } }
+    pub(crate) fn new(id: I, generation: u64, msg: T) -> SessionSourceMsg<I, T>
+    {
+        SessionSourceMsg::<I, T> { id, generation, msg }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A server event from a session.
+pub(crate) type ServerEventMsg<Channel> = SessionSourceMsg<SessionID, ServerEventFrom<Channel>>;
+
+/// A batch of server events, sent through the server's event channel as one item.
+pub(crate) type ServerEventBatch<Channel> = Vec<ServerEventMsg<Channel>>;
+
+/// Make a channel for sending server events to the [`Server`].
+/// - If `coalesce` is set, events sent close together are coalesced into one channel item.
+pub(crate) fn server_event_channel<Channel: ChannelPack>(
+    coalesce : bool,
+    notify   : Arc<tokio::sync::Notify>,
+) -> (ServerEventSender<Channel>, ServerEventReceiver<Channel>)
+{
+    let (sender, receiver) = crossbeam::channel::unbounded::<ServerEventBatch<Channel>>();
+    let queued = Arc::new(AtomicUsize::new(0usize));
+    let pending = match coalesce
+    {
+        true  => Some(Arc::new(Mutex::new(Vec::default()))),
+        false => None,
+    };
+
+    (
+        ServerEventSender{
+                sender,
+                notify,
+                queued       : queued.clone(),
+                pending,
+                disconnected : Arc::new(AtomicBool::new(false)),
+            },
+        ServerEventReceiver{ receiver, buffer: Arc::new(Mutex::new(VecDeque::default())), queued },
+    )
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Sends server events to the [`Server`].
+/// - Wakes tasks waiting in [`Server::next_async()`] after each batch of events is sent, and when a sender is dropped
+///   (the server's event channel disconnects once all senders are dropped).
+/// - When coalescing, the first event of a batch schedules a flush on the current runtime after yielding, so events
+///   sent by other tasks before the flush runs are pushed into the channel together.
+#[derive(Debug)]
+pub(crate) struct ServerEventSender<Channel: ChannelPack>
+{
+    sender: crossbeam::channel::Sender<ServerEventBatch<Channel>>,
+    notify: Arc<tokio::sync::Notify>,
+    /// number of events sent but not consumed yet (shared with the receiver)
+    queued: Arc<AtomicUsize>,
+    /// events waiting to be flushed into the channel (`None` if events are not coalesced)
+    pending: Option<Arc<Mutex<ServerEventBatch<Channel>>>>,
+    /// set when a batch could not be flushed because the channel is disconnected
+    disconnected: Arc<AtomicBool>,
+}
+
+impl<Channel: ChannelPack> ServerEventSender<Channel>
+{
+    /// Send a server event.
+    /// - Returns `Err` if the server's event channel is disconnected.
+    pub(crate) fn send(&self, msg: ServerEventMsg<Channel>) -> Result<(), ()>
+    {
+        if self.disconnected.load(Ordering::Acquire) { return Err(()); }
+        self.queued.fetch_add(1, Ordering::AcqRel);
+
+        let Some(pending) = &self.pending else { return self.send_batch(vec![msg]); };
+
+        // add the event to the pending batch
+        let Ok(mut batch) = pending.lock()
+        else { tracing::error!("pending server events poisoned"); return Err(()); };
+        batch.push(msg);
+        if batch.len() > 1 { return Ok(()); }
+        drop(batch);
+
+        // schedule a flush for the new batch
+        // - outside a runtime there is nothing to coalesce with, so flush immediately
+        let Ok(runtime_handle) = tokio::runtime::Handle::try_current() else { return self.flush(); };
+        let sender = self.clone();
+        runtime_handle.spawn(
+                async move
+                {
+                    tokio::task::yield_now().await;
+                    let _ = sender.flush();
+                }
+            );
+
+        Ok(())
+    }
+
+    /// Get the number of events waiting to be consumed by the server.
+    pub(crate) fn len(&self) -> usize
+    {
+        self.queued.load(Ordering::Acquire)
+    }
+
+    /// Push the pending batch into the channel.
+    fn flush(&self) -> Result<(), ()>
+    {
+        let Some(pending) = &self.pending else { return Ok(()); };
+        let Ok(mut batch) = pending.lock()
+        else { tracing::error!("pending server events poisoned"); return Err(()); };
+        if batch.is_empty() { return Ok(()); }
+
+        // send while holding the lock so batches enter the channel in the order their events were sent
+        self.send_batch(std::mem::take(&mut *batch))
+    }
+
+    fn send_batch(&self, batch: ServerEventBatch<Channel>) -> Result<(), ()>
+    {
+        let num_events = batch.len();
+        let result = self.sender.send(batch);
+        self.notify.notify_waiters();
+
+        if result.is_err()
+        {
+            tracing::debug!(num_events, "server event channel is disconnected, dropping events");
+            self.queued.fetch_sub(num_events, Ordering::AcqRel);
+            self.disconnected.store(true, Ordering::Release);
+            return Err(());
+        }
+
+        Ok(())
+    }
+}
+
+impl<Channel: ChannelPack> Clone for ServerEventSender<Channel>
+{
+    fn clone(&self) -> Self
+    {
+        Self{
+            sender       : self.sender.clone(),
+            notify       : self.notify.clone(),
+            queued       : self.queued.clone(),
+            pending      : self.pending.clone(),
+            disconnected : self.disconnected.clone(),
+        }
+    }
+}
+
+impl<Channel: ChannelPack> Drop for ServerEventSender<Channel>
+{
+    fn drop(&mut self)
+    {
+        // flush events that haven't been pushed into the channel yet (e.g. if a scheduled flush was cancelled)
+        let _ = self.flush();
+
+        // drop the sender before waking waiting tasks, so if this was the last sender they will see the channel
+        // is disconnected
+        let (detached_sender, _) = crossbeam::channel::unbounded();
+        drop(std::mem::replace(&mut self.sender, detached_sender));
+        self.notify.notify_waiters();
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Receives server events in the [`Server`].
+/// - Batches received from the channel are unpacked into a buffer that events are consumed from one at a time.
+#[derive(Debug)]
+pub(crate) struct ServerEventReceiver<Channel: ChannelPack>
+{
+    receiver: crossbeam::channel::Receiver<ServerEventBatch<Channel>>,
+    /// events received from the channel but not consumed yet
+    buffer: Arc<Mutex<VecDeque<ServerEventMsg<Channel>>>>,
+    /// number of events sent but not consumed yet (shared with the senders)
+    queued: Arc<AtomicUsize>,
+}
+
+impl<Channel: ChannelPack> ServerEventReceiver<Channel>
+{
+    /// Try to get the next server event.
+    pub(crate) fn try_recv(&self) -> Result<ServerEventMsg<Channel>, crossbeam::channel::TryRecvError>
+    {
+        let mut events = self.try_recv_batch(1usize)?;
+        events.pop().ok_or(crossbeam::channel::TryRecvError::Empty)
+    }
+
+    /// Try to get up to `max` server events.
+    /// - Returns `Err` if no events are available.
+    pub(crate) fn try_recv_batch(&self, max: usize) -> Result<ServerEventBatch<Channel>, crossbeam::channel::TryRecvError>
+    {
+        let Ok(mut buffer) = self.buffer.lock()
+        else
+        {
+            tracing::error!("server event buffer poisoned");
+            return Err(crossbeam::channel::TryRecvError::Disconnected);
+        };
+
+        let mut events = Vec::default();
+        while events.len() < max
+        {
+            if buffer.is_empty()
+            {
+                match self.receiver.try_recv()
+                {
+                    Ok(batch) => buffer.extend(batch),
+                    Err(err) if events.is_empty() => return Err(err),
+                    Err(_) => break,
+                }
+            }
+            let num_events = buffer.len().min(max - events.len());
+            events.extend(buffer.drain(..num_events));
+        }
+        if events.is_empty() { return Err(crossbeam::channel::TryRecvError::Empty); }
+
+        self.queued.fetch_sub(events.len(), Ordering::AcqRel);
+        Ok(events)
+    }
+
+    /// Wait for the next server event.
+    /// - Returns `Err` once the channel is disconnected and all events have been consumed.
+    pub(crate) fn recv(&self) -> Result<ServerEventMsg<Channel>, crossbeam::channel::RecvError>
+    {
+        loop
+        {
+            match self.try_recv()
+            {
+                Ok(msg) => return Ok(msg),
+                Err(crossbeam::channel::TryRecvError::Disconnected) => return Err(crossbeam::channel::RecvError),
+                Err(crossbeam::channel::TryRecvError::Empty) => (),
+            }
+
+            // wait for a batch without holding the buffer lock
+            let batch = self.receiver.recv()?;
+            let Ok(mut buffer) = self.buffer.lock()
+            else { tracing::error!("server event buffer poisoned"); return Err(crossbeam::channel::RecvError); };
+            buffer.extend(batch);
+        }
+    }
+
+    /// Get the number of events waiting to be consumed.
+    pub(crate) fn len(&self) -> usize
+    {
+        self.queued.load(Ordering::Acquire)
+    }
+}
+
+impl<Channel: ChannelPack> Clone for ServerEventReceiver<Channel>
+{
+    fn clone(&self) -> Self
+    {
+        Self{ receiver: self.receiver.clone(), buffer: self.buffer.clone(), queued: self.queued.clone() }
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -48,8 +458,46 @@ pub(crate) enum SessionCommand<Channel: ChannelPack>
     /// address a race condition between the server API and the server backend where a response for a request received
     /// by an old session could be sent via a new session.
     Send(ClientMetaEventFrom<Channel>, Option<SessionDeathSignal>),
+    /// Send a client meta event unless the deadline passed before this command was dequeued.
+    SendExpiring(ClientMetaEventFrom<Channel>, std::time::Instant),
+    /// Send a client meta event and report its delivery receipt.
+    SendTracked(ClientMetaEventFrom<Channel>, MessageSignalId),
+    /// Send a one-shot message to all sessions except the target session.
+    BroadcastExcept(Channel::ServerMsg),
     /// Close a session.
-    Close(ezsockets::CloseFrame)
+    Close(ezsockets::CloseFrame),
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for the connection handler that doesn't target a session.
+#[derive(Debug, Clone)]
+pub(crate) enum ControlCommand<Channel: ChannelPack>
+{
+    /// Send a batch of responses, each with the 'death signal' of its target session (see [`SessionCommand::Send`]).
+    SendResponses(Vec<(SessionID, ClientMetaEventFrom<Channel>, SessionDeathSignal)>),
+    /// Take a snapshot of the connection handler's state.
+    DebugSnapshot(crossbeam::channel::Sender<ServerDebugSnapshot>),
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for the connection handler.
+#[derive(Debug)]
+pub(crate) enum HandlerCommand<Channel: ChannelPack>
+{
+    /// Command for a session.
+    Session(SessionTargetMsg<SessionID, SessionCommand<Channel>>),
+    /// Command for the connection handler itself.
+    Control(ControlCommand<Channel>),
+}
+
+impl<Channel: ChannelPack> HandlerCommand<Channel>
+{
+    pub(crate) fn session(id: SessionID, command: SessionCommand<Channel>) -> Self
+    {
+        Self::Session(SessionTargetMsg::new(id, command))
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,192 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// TLS handshake details of a session's connection.
+///
+/// Available for sessions connected to servers with `AcceptorConfig::Rustls` or `AcceptorConfig::OpenSSL` (see
+/// [`Server::session_tls_info()`] and [`ConnectionDetails::tls_info`]). Names are reported as the TLS backend
+/// formats them.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SessionTlsInfo
+{
+    /// The negotiated protocol version (e.g. `TLSv1.3`).
+    pub protocol_version: Option<String>,
+    /// The negotiated cipher suite.
+    pub cipher_suite: Option<String>,
+    /// The protocol negotiated with ALPN, if any.
+    pub alpn_protocol: Option<Vec<u8>>,
+    /// The server name the client requested with SNI, if any.
+    pub server_name: Option<String>,
+    /// The DER-encoded certificate chain presented by the client, starting with its own certificate.
+    ///
+    /// Empty unless the server's TLS config requests client certificates (mTLS). Parse the first certificate with an
+    /// X.509 library to read the client's subject.
+    pub peer_certificates: Vec<Vec<u8>>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Implemented by accepted streams that may carry TLS handshake details.
+pub(crate) trait TlsInfoSource
+{
+    /// Get the TLS handshake details of the stream's connection.
+    fn tls_info(&self) -> Option<SessionTlsInfo>;
+}
+
+impl<S: TlsInfoSource> TlsInfoSource for FlushNotifyStream<S>
+{
+    fn tls_info(&self) -> Option<SessionTlsInfo>
+    {
+        self.get_ref().tls_info()
+    }
+}
+
+impl<S: TlsInfoSource> TlsInfoSource for UpgradeTimeoutStream<S>
+{
+    fn tls_info(&self) -> Option<SessionTlsInfo>
+    {
+        self.get_ref().tls_info()
+    }
+}
+
+#[cfg(feature = "tls-rustls")]
+impl<S> TlsInfoSource for tokio_rustls::server::TlsStream<S>
+{
+    fn tls_info(&self) -> Option<SessionTlsInfo>
+    {
+        let (_, connection) = self.get_ref();
+
+        // rustls names versions like `TLSv1_3`
+        let protocol_version = connection.protocol_version()
+            .map(|version| version.as_str().map(|name| name.replace('_', ".")).unwrap_or(format!("{:?}", version)));
+        let cipher_suite = connection.negotiated_cipher_suite()
+            .map(|suite| suite.suite().as_str().map(String::from).unwrap_or(format!("{:?}", suite.suite())));
+
+        Some(SessionTlsInfo{
+                protocol_version,
+                cipher_suite,
+                alpn_protocol     : connection.alpn_protocol().map(Vec::from),
+                server_name       : connection.server_name().map(String::from),
+                peer_certificates : connection.peer_certificates()
+                    .map(|certificates| certificates.iter().map(|certificate| certificate.0.clone()).collect())
+                    .unwrap_or_default(),
+            })
+    }
+}
+
+#[cfg(feature = "tls-openssl")]
+impl<S> TlsInfoSource for tokio_openssl::SslStream<S>
+{
+    fn tls_info(&self) -> Option<SessionTlsInfo>
+    {
+        let ssl = self.ssl();
+
+        // on the server side the peer's chain doesn't include the peer's own certificate
+        let mut peer_certificates = Vec::default();
+        if let Some(certificate) = ssl.peer_certificate()
+        {
+            peer_certificates.extend(certificate.to_der().ok());
+            if let Some(chain) = ssl.peer_cert_chain()
+            {
+                peer_certificates.extend(chain.iter().filter_map(|certificate| certificate.to_der().ok()));
+            }
+        }
+
+        Some(SessionTlsInfo{
+                protocol_version  : Some(String::from(ssl.version_str())),
+                cipher_suite      : ssl.current_cipher()
+                    .map(|cipher| String::from(cipher.standard_name().unwrap_or(cipher.name()))),
+                alpn_protocol     : ssl.selected_alpn_protocol().map(Vec::from),
+                server_name       : ssl.servername(openssl::ssl::NameType::HOST_NAME).map(String::from),
+                peer_certificates,
+            })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Registers TLS handshake details of accepted connections so the connection handler can find them when the
+/// connections' sessions connect.
+/// - Keyed by peer address, since that is all the connection handler learns about the underlying connection.
+/// - Entries are removed when their connections close (see [`TlsInfoRegistration`]).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TlsInfoRegistry
+{
+    /// TLS details of open connections, tagged with registration ids
+    infos: Arc<Mutex<HashMap<SocketAddr, (u64, SessionTlsInfo)>>>,
+    /// source of registration ids
+    next_id: Arc<AtomicU64>,
+}
+
+impl TlsInfoRegistry
+{
+    /// Get the TLS handshake details of the open connection with a peer.
+    pub(crate) fn get(&self, peer_addr: &SocketAddr) -> Option<SessionTlsInfo>
+    {
+        let Ok(infos) = self.infos.lock() else { tracing::error!("tls info registry poisoned"); return None; };
+        infos.get(peer_addr).map(|(_, info)| info.clone())
+    }
+
+    /// Register the TLS handshake details of a new connection.
+    /// - The details are removed when the returned registration is dropped.
+    fn register(&self, peer_addr: SocketAddr, info: SessionTlsInfo) -> TlsInfoRegistration
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        match self.infos.lock()
+        {
+            Ok(mut infos) => { infos.insert(peer_addr, (id, info)); }
+            Err(_) => tracing::error!("tls info registry poisoned"),
+        }
+
+        TlsInfoRegistration{ registry: self.clone(), peer_addr, id }
+    }
+}
+
+impl<S: TlsInfoSource> AcceptHook<S> for TlsInfoRegistry
+{
+    type Guard = Option<TlsInfoRegistration>;
+
+    fn on_accept(&self, peer_addr: SocketAddr, stream: &S) -> Self::Guard
+    {
+        stream.tls_info().map(|info| self.register(peer_addr, info))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Removes a connection's entry from a [`TlsInfoRegistry`] when dropped.
+#[derive(Debug)]
+pub(crate) struct TlsInfoRegistration
+{
+    /// the registry that holds the entry
+    registry: TlsInfoRegistry,
+    /// the connection's peer address
+    peer_addr: SocketAddr,
+    /// the entry's registration id
+    id: u64,
+}
+
+impl Drop for TlsInfoRegistration
+{
+    fn drop(&mut self)
+    {
+        let Ok(mut infos) = self.registry.infos.lock() else { tracing::error!("tls info registry poisoned"); return; };
+
+        // don't remove the entry of a newer connection from the same address
+        if infos.get(&self.peer_addr).map(|(id, _)| *id) != Some(self.id) { return; }
+        infos.remove(&self.peer_addr);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
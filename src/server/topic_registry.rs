@@ -0,0 +1,71 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::collections::{HashMap, HashSet};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks which sessions are subscribed to which topics.
+/// - Shared between the server API, the connection handler (for cleanup), and session handlers (for updates).
+#[derive(Debug, Default)]
+pub(crate) struct TopicRegistry
+{
+    /// topic : subscribed sessions
+    topics: HashMap<String, HashSet<SessionID>>,
+    /// session : subscribed topics
+    subscriptions: HashMap<SessionID, HashSet<String>>,
+}
+
+impl TopicRegistry
+{
+    /// Subscribe a session to a topic.
+    pub(crate) fn subscribe(&mut self, id: SessionID, topic: String)
+    {
+        self.topics.entry(topic.clone()).or_default().insert(id);
+        self.subscriptions.entry(id).or_default().insert(topic);
+    }
+
+    /// Unsubscribe a session from a topic.
+    pub(crate) fn unsubscribe(&mut self, id: SessionID, topic: &str)
+    {
+        if let Some(topics) = self.subscriptions.get_mut(&id)
+        {
+            topics.remove(topic);
+            if topics.is_empty() { self.subscriptions.remove(&id); }
+        }
+        self.remove_subscriber(id, topic);
+    }
+
+    /// Remove a session from all topics.
+    pub(crate) fn remove_session(&mut self, id: SessionID)
+    {
+        let Some(topics) = self.subscriptions.remove(&id) else { return; };
+        for topic in topics.iter() { self.remove_subscriber(id, topic); }
+    }
+
+    /// Get the sessions subscribed to a topic.
+    pub(crate) fn subscribers(&self, topic: &str) -> Vec<SessionID>
+    {
+        let Some(subscribers) = self.topics.get(topic) else { return Vec::default(); };
+        subscribers.iter().copied().collect()
+    }
+
+    /// Get the number of topics a session is subscribed to.
+    pub(crate) fn num_topics(&self, id: SessionID) -> usize
+    {
+        self.subscriptions.get(&id).map_or(0, |topics| topics.len())
+    }
+
+    fn remove_subscriber(&mut self, id: SessionID, topic: &str)
+    {
+        let Some(subscribers) = self.topics.get_mut(topic) else { return; };
+        subscribers.remove(&id);
+        if subscribers.is_empty() { self.topics.remove(topic); }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
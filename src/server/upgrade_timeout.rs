@@ -0,0 +1,158 @@
+//local shortcuts
+
+//third-party shortcuts
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn upgrade_timed_out() -> std::io::Error
+{
+    std::io::Error::new(std::io::ErrorKind::TimedOut, "websocket upgrade timed out")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wraps an accepted connection in order to drop it if the server doesn't respond to the connection's upgrade request
+/// before a deadline.
+/// - The deadline is disarmed when the server first writes to the stream (i.e. once the upgrade request was fully
+///   received and handled).
+#[derive(Debug)]
+pub(crate) struct UpgradeTimeoutStream<S>
+{
+    /// the wrapped stream
+    inner: S,
+    /// deadline for the server's upgrade response (`None` once the server has responded)
+    deadline: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl<S> UpgradeTimeoutStream<S>
+{
+    fn new(inner: S, deadline: tokio::time::Instant) -> Self
+    {
+        Self{ inner, deadline: Some(Box::pin(tokio::time::sleep_until(deadline))) }
+    }
+
+    /// Get the wrapped stream.
+    pub(crate) fn get_ref(&self) -> &S
+    {
+        &self.inner
+    }
+
+    fn disarm_on_write(&mut self, result: &Poll<std::io::Result<usize>>)
+    {
+        if let Poll::Ready(Ok(n)) = result { if *n > 0 { self.deadline = None; } }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for UpgradeTimeoutStream<S>
+{
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>>
+    {
+        // fail the read if the deadline expired
+        // - polling the deadline registers a wakeup, so stalled connections are dropped even if they send nothing
+        if let Some(deadline) = &mut self.deadline
+        {
+            if deadline.as_mut().poll(cx).is_ready()
+            {
+                tracing::trace!("websocket upgrade timed out, dropping connection...");
+                return Poll::Ready(Err(upgrade_timed_out()));
+            }
+        }
+
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for UpgradeTimeoutStream<S>
+{
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>>
+    {
+        let result = Pin::new(&mut self.inner).poll_write(cx, buf);
+        self.disarm_on_write(&result);
+        result
+    }
+
+    fn poll_write_vectored(
+        mut self : Pin<&mut Self>,
+        cx       : &mut Context<'_>,
+        bufs     : &[std::io::IoSlice<'_>]
+    ) -> Poll<std::io::Result<usize>>
+    {
+        let result = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+        self.disarm_on_write(&result);
+        result
+    }
+
+    fn is_write_vectored(&self) -> bool
+    {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>>
+    {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>>
+    {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Wraps a connection acceptor in order to drop connections that don't complete their websocket upgrade in time.
+/// - The timeout covers the inner acceptor (e.g. the TLS handshake) and receiving the upgrade request.
+#[derive(Debug, Clone)]
+pub(crate) struct UpgradeTimeoutAcceptor<A>
+{
+    /// the wrapped acceptor
+    inner: A,
+    /// max duration between accepting a connection and responding to its upgrade request
+    timeout: Duration,
+}
+
+impl<A> UpgradeTimeoutAcceptor<A>
+{
+    pub(crate) fn new(inner: A, timeout: Duration) -> Self
+    {
+        Self{ inner, timeout }
+    }
+}
+
+impl<A, I, S> axum_server::accept::Accept<I, S> for UpgradeTimeoutAcceptor<A>
+where
+    A: axum_server::accept::Accept<I, S>,
+    A::Future: Send + 'static,
+{
+    type Stream  = UpgradeTimeoutStream<A::Stream>;
+    type Service = A::Service;
+    type Future  = Pin<Box<dyn Future<Output = std::io::Result<(Self::Stream, A::Service)>> + Send>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future
+    {
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        let accept = self.inner.accept(stream, service);
+
+        Box::pin(async move {
+                let Ok(result) = tokio::time::timeout_at(deadline, accept).await
+                else
+                {
+                    tracing::trace!("connection accept timed out, dropping connection...");
+                    return Err(upgrade_timed_out());
+                };
+                let (stream, service) = result?;
+
+                Ok((UpgradeTimeoutStream::new(stream, deadline), service))
+            })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
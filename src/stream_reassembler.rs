@@ -0,0 +1,159 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+
+//standard shortcuts
+use core::fmt::Debug;
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[cfg(not(target_family = "wasm"))]
+use std::time::Instant;
+
+#[cfg(target_family = "wasm")]
+use wasm_timer::Instant;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Split a serialized message into stream chunks.
+/// - Always produces at least one chunk.
+pub(crate) fn split_into_chunks(stream_id: u64, bytes: Vec<u8>, chunk_size: u32) -> Vec<StreamChunk>
+{
+    let chunk_size = (chunk_size as usize).max(1);
+    let total = ((bytes.len() + chunk_size - 1) / chunk_size).max(1) as u32;
+
+    if bytes.len() <= chunk_size
+    {
+        return vec![StreamChunk{ stream_id, index: 0, total, bytes }];
+    }
+
+    bytes
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(index, chunk)| StreamChunk{ stream_id, index: index as u32, total, bytes: chunk.to_vec() })
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Error produced when a stream chunk is invalid.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum StreamError
+{
+    /// The chunk's header doesn't match the stream it belongs to.
+    Malformed,
+    /// The partial streams exceed the max stream size.
+    TooLarge,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct PartialStream
+{
+    total       : u32,
+    next_index  : u32,
+    bytes       : Vec<u8>,
+    last_update : Instant,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reassembles chunked streams into complete serialized messages.
+/// - Chunks must arrive in order, which is guaranteed by the websocket transport.
+/// - Partial streams are discarded if no chunk arrives before the stream timeout.
+/// - The max stream size bounds the total bytes buffered across all partial streams.
+#[derive(Debug)]
+pub(crate) struct StreamReassembler
+{
+    /// max number of bytes buffered across partial streams
+    max_stream_size: usize,
+    /// number of bytes buffered across partial streams
+    buffered: usize,
+    /// max duration between chunks of a stream
+    stream_timeout: Duration,
+    /// partially received streams
+    streams: HashMap<u64, PartialStream>,
+}
+
+impl StreamReassembler
+{
+    pub(crate) fn new(max_stream_size: u32, stream_timeout: Duration) -> Self
+    {
+        Self{ max_stream_size: max_stream_size as usize, buffered: 0, stream_timeout, streams: HashMap::default() }
+    }
+
+    /// Add a chunk to the reassembler.
+    /// - Returns `Ok(Some(bytes))` when the chunk completes its stream.
+    /// - Returns `Err` if the chunk is invalid. The chunk's stream is discarded.
+    pub(crate) fn add_chunk(&mut self, chunk: StreamChunk) -> Result<Option<Vec<u8>>, StreamError>
+    {
+        self.discard_expired();
+
+        let StreamChunk{ stream_id, index, total, bytes } = chunk;
+        if total == 0 || index >= total { self.discard(stream_id); return Err(StreamError::Malformed); }
+
+        // validate the chunk
+        if self.buffered + bytes.len() > self.max_stream_size
+        {
+            self.discard(stream_id);
+            return Err(StreamError::TooLarge);
+        }
+
+        let stream = self.streams.entry(stream_id).or_insert_with(
+                || PartialStream{ total, next_index: 0, bytes: Vec::default(), last_update: Instant::now() }
+            );
+        if stream.total != total || stream.next_index != index
+        {
+            self.discard(stream_id);
+            return Err(StreamError::Malformed);
+        }
+
+        // add the chunk
+        stream.bytes.extend_from_slice(&bytes);
+        stream.next_index += 1;
+        stream.last_update = Instant::now();
+        self.buffered += bytes.len();
+
+        if stream.next_index < stream.total { return Ok(None); }
+
+        // the stream is complete
+        let Some(stream) = self.streams.remove(&stream_id) else { return Ok(None); };
+        self.buffered -= stream.bytes.len();
+        Ok(Some(stream.bytes))
+    }
+
+    /// Discard all partial streams.
+    /// - Clients reuse their reassembler across reconnects, while server sessions each get a new one.
+    #[cfg(feature = "client")]
+    pub(crate) fn clear(&mut self)
+    {
+        self.streams.clear();
+        self.buffered = 0;
+    }
+
+    fn discard(&mut self, stream_id: u64)
+    {
+        let Some(stream) = self.streams.remove(&stream_id) else { return; };
+        self.buffered -= stream.bytes.len();
+    }
+
+    fn discard_expired(&mut self)
+    {
+        let stream_timeout = self.stream_timeout;
+        let mut discarded = 0;
+        self.streams.retain(
+                |stream_id, stream|
+                {
+                    if stream.last_update.elapsed() < stream_timeout { return true; }
+                    tracing::debug!(stream_id, "discarding expired stream");
+                    discarded += stream.bytes.len();
+                    false
+                }
+            );
+        self.buffered -= discarded;
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,97 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bincode::Options;
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn round_trip<T>(value: &T) -> Result<T, ()>
+where
+    T: Serialize + for<'de> Deserialize<'de>
+{
+    let Ok(ser_value) = bincode::DefaultOptions::new().serialize(value)
+    else { tracing::debug!("round trip serialization failed"); return Err(()); };
+    let Ok(value) = bincode::DefaultOptions::new().deserialize(&ser_value[..])
+    else { tracing::debug!("round trip deserialization failed"); return Err(()); };
+
+    Ok(value)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Serialize and deserialize a connect message the same way it is sent from clients to servers.
+///
+/// Connect messages are checked in both formats they can be sent with: JSON (in the connection request URL) and
+/// `bincode` (in the first binary frame, see [`ClientConfig::binary_connect_msg`]).
+///
+/// Use this to test that your [`ChannelPack`] types are compatible with the wire format.
+pub fn round_trip_connect_msg<Channel: ChannelPack>(
+    connect_msg: &Channel::ConnectMsg
+) -> Result<Channel::ConnectMsg, ()>
+{
+    let Ok(ser_connect_msg) = serde_json::to_string(connect_msg)
+    else { tracing::debug!("round trip json serialization failed"); return Err(()); };
+    let Ok(_) = serde_json::from_str::<Channel::ConnectMsg>(&ser_connect_msg)
+    else { tracing::debug!("round trip json deserialization failed"); return Err(()); };
+
+    round_trip(connect_msg)
+}
+
+/// Serialize and deserialize a server message the same way it is sent from servers to clients.
+///
+/// Use this to test that your [`ChannelPack`] types are compatible with the wire format.
+pub fn round_trip_server_msg<Channel: ChannelPack>(msg: Channel::ServerMsg) -> Result<Channel::ServerMsg, ()>
+{
+    let ClientMetaEventFrom::<Channel>::Msg(msg) = round_trip(&ClientMetaEventFrom::<Channel>::Msg(msg))?
+    else { return Err(()); };
+
+    Ok(msg)
+}
+
+/// Serialize and deserialize a server response the same way it is sent from servers to clients.
+///
+/// Use this to test that your [`ChannelPack`] types are compatible with the wire format.
+pub fn round_trip_server_response<Channel: ChannelPack>(
+    response   : Channel::ServerResponse,
+    request_id : u64,
+) -> Result<(Channel::ServerResponse, u64), ()>
+{
+    let ClientMetaEventFrom::<Channel>::Response(response, request_id) =
+        round_trip(&ClientMetaEventFrom::<Channel>::Response(response, request_id))?
+    else { return Err(()); };
+
+    Ok((response, request_id))
+}
+
+/// Serialize and deserialize a client message the same way it is sent from clients to servers.
+///
+/// Use this to test that your [`ChannelPack`] types are compatible with the wire format.
+pub fn round_trip_client_msg<Channel: ChannelPack>(msg: Channel::ClientMsg) -> Result<Channel::ClientMsg, ()>
+{
+    let ServerMetaEventFrom::<Channel>::Msg(msg) = round_trip(&ServerMetaEventFrom::<Channel>::Msg(msg))?
+    else { return Err(()); };
+
+    Ok(msg)
+}
+
+/// Serialize and deserialize a client request the same way it is sent from clients to servers.
+///
+/// Use this to test that your [`ChannelPack`] types are compatible with the wire format.
+pub fn round_trip_client_request<Channel: ChannelPack>(
+    request    : Channel::ClientRequest,
+    request_id : u64,
+) -> Result<(Channel::ClientRequest, u64), ()>
+{
+    let ServerMetaEventFrom::<Channel>::Request(request, request_id) =
+        round_trip(&ServerMetaEventFrom::<Channel>::Request(request, request_id))?
+    else { return Err(()); };
+
+    Ok((request, request_id))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
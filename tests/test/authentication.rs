@@ -67,10 +67,12 @@ fn authentication_test(authenticator: bevy_simplenet::Authenticator, auth_reques
                 max_msg_size      : 10_000,
                 rate_limit_config : bevy_simplenet::RateLimitConfig{
                         period    : std::time::Duration::from_millis(15),
-                        max_count : 25
+                        max_count : 25,
+                        max_bytes : u64::MAX
                     },
                 heartbeat_interval : std::time::Duration::from_secs(5),
                 keepalive_timeout  : std::time::Duration::from_secs(10),
+                ..Default::default()
             }
         );
 
@@ -88,10 +90,59 @@ fn authentication_test(authenticator: bevy_simplenet::Authenticator, auth_reques
 
     std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
 
+    // rejected clients are closed by the server
+    if websocket_client.is_dead()
+    {
+        let Some(bevy_simplenet::DeathReason::ClosedByServer(Some(close_frame))) = websocket_client.death_reason()
+        else { panic!("rejected client should be closed by the server"); };
+        assert_eq!(
+                bevy_simplenet::CloseReason::from_close_frame(&close_frame),
+                Some(bevy_simplenet::CloseReason::AuthFailed)
+            );
+    }
+
     // return connection result
     return !websocket_client.is_dead();
 }
 
+fn authentication_test_blocking(
+    authenticator : bevy_simplenet::Authenticator,
+    auth_request  : bevy_simplenet::AuthRequest
+) -> Result<(), bevy_simplenet::ConnectError>
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            authenticator,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client_blocking(
+            client_runtime,
+            websocket_server.url(),
+            auth_request,
+            bevy_simplenet::ClientConfig{
+                max_initial_connect_attempts: 1usize,
+                ..Default::default()
+            },
+            DemoConnectMsg(String::from("hello")),
+            std::time::Duration::from_secs(1)
+        )?;
+
+    // the connection report is still available
+    let Some(bevy_simplenet::ClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+    Ok(())
+}
+
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -118,3 +169,129 @@ fn authentication()
 }
 
 //-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn authentication_blocking()
+{
+    // prep authenticators
+    let secret_authenticator_a = bevy_simplenet::Authenticator::Secret{secret: (0u128).to_le_bytes()};
+    let secret_authenticator_b = bevy_simplenet::Authenticator::Secret{secret: (1u128).to_le_bytes()};
+
+    // prep auth requests
+    let secret_request_a = bevy_simplenet::AuthRequest::Secret{client_id: 1u128, secret: (0u128).to_le_bytes()};
+
+    // test cases
+    assert!(authentication_test_blocking(secret_authenticator_a.clone(), secret_request_a.clone()).is_ok());
+    let Err(bevy_simplenet::ConnectError::AuthFailed) =
+        authentication_test_blocking(secret_authenticator_b.clone(), secret_request_a.clone())
+    else { panic!("client should fail to connect"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn version_mismatch_blocking()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client with a different protocol version
+    let Err(bevy_simplenet::ConnectError::VersionMismatch) =
+        bevy_simplenet::ClientFactory::<DemoChannel>::new("test_other").new_client_blocking(
+                client_runtime,
+                websocket_server.url(),
+                bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+                bevy_simplenet::ClientConfig::default(),
+                DemoConnectMsg(String::from("hello")),
+                std::time::Duration::from_secs(1)
+            )
+    else { panic!("client should fail to connect"); };
+
+    // the server never saw the client
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+    assert_eq!(websocket_server.num_connections(), 0u64);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn authentication_rotation()
+{
+    // prep authenticators
+    let secret_authenticator_a = bevy_simplenet::Authenticator::Secret{secret: (0u128).to_le_bytes()};
+    let secret_authenticator_b = bevy_simplenet::Authenticator::Secret{secret: (1u128).to_le_bytes()};
+
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            secret_authenticator_a,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client_a = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::Secret{client_id: 1u128, secret: (0u128).to_le_bytes()},
+            bevy_simplenet::ClientConfig{
+                max_initial_connect_attempts: 1usize,
+                ..Default::default()
+            },
+            DemoConnectMsg(String::from("hello"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+    assert!(websocket_client_a.is_connected());
+
+    // rotate the authenticator
+    websocket_server.set_authenticator(secret_authenticator_b);
+
+    // clients using the old secret fail to connect
+    let websocket_client_old = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::Secret{client_id: 2u128, secret: (0u128).to_le_bytes()},
+            bevy_simplenet::ClientConfig{
+                max_initial_connect_attempts: 1usize,
+                ..Default::default()
+            },
+            DemoConnectMsg(String::from("hello"))
+        );
+
+    // clients using the new secret connect
+    let websocket_client_new = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::Secret{client_id: 3u128, secret: (1u128).to_le_bytes()},
+            bevy_simplenet::ClientConfig{
+                max_initial_connect_attempts: 1usize,
+                ..Default::default()
+            },
+            DemoConnectMsg(String::from("hello"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+    assert!(websocket_client_old.is_dead());
+    assert!(websocket_client_new.is_connected());
+
+    // existing sessions are unaffected
+    assert!(websocket_client_a.is_connected());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,118 @@
+//local shortcuts
+
+//third-party shortcuts
+use bevy_ecs::event::{Events, ManualEventReader};
+use bevy_ecs::system::Command;
+use bevy_ecs::world::World;
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoServerMsg(pub u64);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoClientMsg(pub u64);
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = ();
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = ();
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+}
+
+type DemoClientEvent = bevy_simplenet::ClientEventFrom<DemoChannel>;
+type DemoServerEvent = bevy_simplenet::ServerEventFrom<DemoChannel>;
+type DemoServerReport = bevy_simplenet::ServerReport<<DemoChannel as bevy_simplenet::ChannelPack>::ConnectMsg>;
+
+fn server_demo_factory() -> bevy_simplenet::ServerFactory<DemoChannel>
+{
+    bevy_simplenet::ServerFactory::<DemoChannel>::new("test")
+}
+
+fn client_demo_factory() -> bevy_simplenet::ClientFactory<DemoChannel>
+{
+    bevy_simplenet::ClientFactory::<DemoChannel>::new("test")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn bevy_teardown()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // prepare world
+    let mut world = World::new();
+    world.init_resource::<Events<bevy_simplenet::ClientEventOf<DemoChannel>>>();
+    world.init_resource::<Events<bevy_simplenet::ServerEventOf<DemoChannel>>>();
+    world.insert_resource(websocket_client);
+    world.insert_resource(websocket_server);
+
+    // tear down the client without draining it
+    bevy_simplenet::RemoveClient::<DemoChannel>::new(std::time::Duration::from_secs(1)).apply(&mut world);
+    assert!(!world.contains_resource::<bevy_simplenet::Client<DemoChannel>>());
+
+    // the client's events were forwarded, including its final report
+    let client_events = world.resource::<Events<bevy_simplenet::ClientEventOf<DemoChannel>>>();
+    let mut client_reader = ManualEventReader::default();
+    let mut client_events = client_reader.read(client_events).map(|event| &event.0);
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = client_events.next()
+    else { panic!("client should be connected to server"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedBySelf)) = client_events.next()
+    else { panic!("client should be closed by self"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::IsDead(_))) = client_events.next()
+    else { panic!("client should be dead"); };
+    let None = client_events.next()
+    else { panic!("client should have no more events"); };
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // tear down the server without draining it
+    bevy_simplenet::RemoveServer::<DemoChannel>::default().apply(&mut world);
+    assert!(!world.contains_resource::<bevy_simplenet::Server<DemoChannel>>());
+
+    // the server's events were forwarded
+    let server_events = world.resource::<Events<bevy_simplenet::ServerEventOf<DemoChannel>>>();
+    let mut server_reader = ManualEventReader::default();
+    let mut server_events = server_reader.read(server_events).map(|event| &event.event);
+    let Some(DemoServerEvent::Report(DemoServerReport::Connected(_, _))) = server_events.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoServerEvent::Report(DemoServerReport::Disconnected(_))) = server_events.next()
+    else { panic!("server should be disconnected after client is disconnected"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
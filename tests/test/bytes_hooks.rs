@@ -0,0 +1,336 @@
+//local shortcuts
+
+//third-party shortcuts
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoServerMsg(pub u64);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoClientMsg(pub u64);
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = ();
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = ();
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+}
+
+type _DemoServer = bevy_simplenet::Server<DemoChannel>;
+type _DemoClient = bevy_simplenet::Client<DemoChannel>;
+type DemoClientEvent = bevy_simplenet::ClientEventFrom<DemoChannel>;
+type DemoServerEvent = bevy_simplenet::ServerEventFrom<DemoChannel>;
+type DemoServerReport = bevy_simplenet::ServerReport<<DemoChannel as bevy_simplenet::ChannelPack>::ConnectMsg>;
+
+fn server_demo_factory() -> bevy_simplenet::ServerFactory<DemoChannel>
+{
+    bevy_simplenet::ServerFactory::<DemoChannel>::new("test")
+}
+
+fn client_demo_factory() -> bevy_simplenet::ClientFactory<DemoChannel>
+{
+    bevy_simplenet::ClientFactory::<DemoChannel>::new("test")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Make a hook that xors all bytes with a key and counts how many times it was called.
+fn xor_hook(key: u8, counter: Arc<AtomicUsize>) -> Arc<dyn bevy_simplenet::BytesHookFn>
+{
+    Arc::new(
+            move |bytes: &mut Vec<u8>|
+            {
+                counter.fetch_add(1, Ordering::Relaxed);
+                for byte in bytes.iter_mut() { *byte ^= key; }
+            }
+        )
+}
+
+
+/// Toy cipher that xors all bytes with a key and appends the key as a tag.
+#[derive(Debug)]
+struct XorCipher(u8);
+
+impl bevy_simplenet::PayloadCipher for XorCipher
+{
+    fn encrypt(&self, bytes: &mut Vec<u8>)
+    {
+        for byte in bytes.iter_mut() { *byte ^= self.0; }
+        bytes.push(self.0);
+    }
+
+    fn decrypt(&self, bytes: &mut Vec<u8>) -> Result<(), ()>
+    {
+        if bytes.pop() != Some(self.0) { return Err(()); }
+        for byte in bytes.iter_mut() { *byte ^= self.0; }
+        Ok(())
+    }
+}
+
+/// Test codec that reverses payloads and counts how many payloads it compressed.
+struct ReverseCodec(Arc<AtomicUsize>);
+
+impl bevy_simplenet::PayloadCodec for ReverseCodec
+{
+    fn compress(&self, bytes: &[u8]) -> Vec<u8>
+    {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        bytes.iter().rev().copied().collect()
+    }
+
+    fn decompress(&self, bytes: &[u8], max_size: usize) -> Result<Vec<u8>, ()>
+    {
+        if bytes.len() > max_size { return Err(()); }
+        Ok(bytes.iter().rev().copied().collect())
+    }
+}
+
+fn reverse_compression(threshold: usize, counter: Arc<AtomicUsize>) -> bevy_simplenet::PayloadCompression
+{
+    bevy_simplenet::PayloadCompression{ codec: Arc::new(ReverseCodec(counter)), threshold }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn bytes_hooks()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // prepare hooks
+    // - both ends xor all bytes with the same key, so messages can only be read if the hooks are applied on both ends
+    let server_sent = Arc::new(AtomicUsize::new(0));
+    let server_recv = Arc::new(AtomicUsize::new(0));
+    let client_sent = Arc::new(AtomicUsize::new(0));
+    let client_recv = Arc::new(AtomicUsize::new(0));
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                on_send: Some(xor_hook(0x5Au8, server_sent.clone())),
+                on_recv: Some(xor_hook(0x5Au8, server_recv.clone())),
+                ..Default::default()
+            }
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 7321u128 },
+            bevy_simplenet::ClientConfig{
+                on_send: Some(xor_hook(0x5Au8, client_sent.clone())),
+                on_recv: Some(xor_hook(0x5Au8, client_recv.clone())),
+                ..Default::default()
+            },
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send message: client -> server
+    let client_val = 42;
+    websocket_client.send(DemoClientMsg(client_val)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((msg_client_id, DemoServerEvent::Msg(DemoClientMsg(msg_client_val)))) = websocket_server.next()
+    else { panic!("server did not receive client msg"); };
+    assert_eq!(client_id, msg_client_id);
+    assert_eq!(client_val, msg_client_val);
+    assert_eq!(client_sent.load(Ordering::Relaxed), 1);
+    assert_eq!(server_recv.load(Ordering::Relaxed), 1);
+
+
+    // send message: server -> client
+    let server_val = 24;
+    websocket_server.send(client_id, DemoServerMsg(server_val)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Msg(DemoServerMsg(msg_server_val))) = websocket_client.next()
+    else { panic!("client did not receive server msg"); };
+    assert_eq!(server_val, msg_server_val);
+    assert_eq!(server_sent.load(Ordering::Relaxed), 2);  //the session ready message also passes through the hooks
+    assert_eq!(client_recv.load(Ordering::Relaxed), 2);
+
+
+    // no more outputs
+    let None = websocket_server.next()
+    else { panic!("server should receive no more values"); };
+    let None = websocket_client.next()
+    else { panic!("client should receive no more values"); };
+    #[cfg(feature = "testing")]
+    {
+        websocket_server.assert_drained();
+        websocket_client.assert_drained();
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn payload_encryption()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                payload_encryption: Some(Arc::new(XorCipher(0xA5u8))),
+                ..Default::default()
+            }
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 1u128 },
+            bevy_simplenet::ClientConfig{
+                binary_connect_msg : true,
+                payload_encryption : Some(Arc::new(XorCipher(0xA5u8))),
+                ..Default::default()
+            },
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send messages in both directions
+    websocket_client.send(DemoClientMsg(42)).unwrap();
+    websocket_server.send(client_id, DemoServerMsg(24)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Msg(DemoClientMsg(42)))) = websocket_server.next()
+    else { panic!("server did not receive client msg"); };
+    let Some(DemoClientEvent::Msg(DemoServerMsg(24))) = websocket_client.next()
+    else { panic!("client did not receive server msg"); };
+
+
+    // a client with the wrong key is rejected after its first message
+    let bad_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 2u128 },
+            bevy_simplenet::ClientConfig{
+                binary_connect_msg : true,
+                payload_encryption : Some(Arc::new(XorCipher(0x11u8))),
+                ..Default::default()
+            },
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = bad_client.next()
+    else { panic!("client with the wrong key should connect"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(_))) = bad_client.next()
+    else { panic!("client with the wrong key should be closed by the server"); };
+    let None = websocket_server.next()
+    else { panic!("server should not report the client with the wrong key"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn payload_compression()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    // - the server compresses everything, the client only compresses large messages
+    let server_compressed = Arc::new(AtomicUsize::new(0));
+    let client_compressed = Arc::new(AtomicUsize::new(0));
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                payload_compression: Some(reverse_compression(0, server_compressed.clone())),
+                ..Default::default()
+            }
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 1u128 },
+            bevy_simplenet::ClientConfig{
+                binary_connect_msg  : true,
+                payload_compression : Some(reverse_compression(1000, client_compressed.clone())),
+                ..Default::default()
+            },
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+    let server_compressed_on_connect = server_compressed.load(Ordering::Relaxed);
+
+
+    // send messages in both directions
+    websocket_client.send(DemoClientMsg(42)).unwrap();
+    websocket_server.send(client_id, DemoServerMsg(24)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Msg(DemoClientMsg(42)))) = websocket_server.next()
+    else { panic!("server did not receive client msg"); };
+    let Some(DemoClientEvent::Msg(DemoServerMsg(24))) = websocket_client.next()
+    else { panic!("client did not receive server msg"); };
+
+    // small client messages are not compressed
+    assert_eq!(client_compressed.load(Ordering::Relaxed), 0);
+    assert_eq!(server_compressed.load(Ordering::Relaxed), server_compressed_on_connect + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
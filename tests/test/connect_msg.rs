@@ -0,0 +1,376 @@
+//local shortcuts
+
+//third-party shortcuts
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoServerMsg(pub u64);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoClientMsg(pub u64);
+
+/// client connect message
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoConnectMsg(pub Vec<u8>);
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = DemoConnectMsg;
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = ();
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+}
+
+type _DemoServer = bevy_simplenet::Server<DemoChannel>;
+type _DemoClient = bevy_simplenet::Client<DemoChannel>;
+type DemoClientEvent = bevy_simplenet::ClientEventFrom<DemoChannel>;
+type DemoServerEvent = bevy_simplenet::ServerEventFrom<DemoChannel>;
+type DemoServerReport = bevy_simplenet::ServerReport<<DemoChannel as bevy_simplenet::ChannelPack>::ConnectMsg>;
+
+fn server_demo_factory() -> bevy_simplenet::ServerFactory<DemoChannel>
+{
+    bevy_simplenet::ServerFactory::<DemoChannel>::new("test")
+}
+
+fn client_demo_factory() -> bevy_simplenet::ClientFactory<DemoChannel>
+{
+    bevy_simplenet::ClientFactory::<DemoChannel>::new("test")
+}
+
+/// Send a raw websocket upgrade request to the server and return the status line of its response.
+fn upgrade_response_status(server_url: url::Url, client_id: u128) -> String
+{
+    use std::io::{Read, Write};
+
+    // build the connection request the same way clients do
+    let auth = serde_json::to_string(&bevy_simplenet::AuthRequest::None{ client_id }).unwrap();
+    let connect_msg = serde_json::to_string(&DemoConnectMsg(Vec::default())).unwrap();
+    let mut url = server_url.clone();
+    url.query_pairs_mut()
+        .append_pair("v", "test")
+        .append_pair("t", bevy_simplenet::env_type_as_str(bevy_simplenet::EnvType::Native))
+        .append_pair("a", auth.as_str())
+        .append_pair("c", connect_msg.as_str());
+
+    // send the upgrade request
+    let address = server_url.socket_addrs(|| None).unwrap()[0];
+    let mut stream = std::net::TcpStream::connect(address).unwrap();
+    let request = format!(
+            "GET {} HTTP/1.1\r\n\
+            Host: {}\r\n\
+            Upgrade: websocket\r\n\
+            Connection: Upgrade\r\n\
+            Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+            Sec-WebSocket-Version: 13\r\n\r\n",
+            &url[url::Position::BeforePath..],
+            address
+        );
+    stream.write_all(request.as_bytes()).unwrap();
+
+    // read the status line
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n")
+    {
+        stream.read_exact(&mut byte).unwrap();
+        response.push(byte[0]);
+    }
+
+    String::from_utf8_lossy(&response).trim_end().to_string()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn binary_connect_msg()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    // - the connect message contains raw bytes
+    let connect_bytes: Vec<u8> = (0..=255u8).collect();
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                binary_connect_msg: true,
+                ..Default::default()
+            },
+            DemoConnectMsg(connect_bytes.clone())
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(_, connect_msg)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+    assert_eq!(connect_msg.0, connect_bytes);
+
+
+    // send message: client -> server
+    websocket_client.send(DemoClientMsg(42)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((msg_client_id, DemoServerEvent::Msg(DemoClientMsg(42)))) = websocket_server.next()
+    else { panic!("server did not receive client msg"); };
+    assert_eq!(client_id, msg_client_id);
+
+
+    // client closes itself
+    websocket_client.close();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(_)))) = websocket_server.next()
+    else { panic!("server should be disconnected after client is disconnected"); };
+    let None = websocket_server.next()
+    else { panic!("server should receive no more values"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn upgrade_hook()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    // - connections are tagged with their request path, and client 1 is rejected
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                on_upgrade: Some(std::sync::Arc::new(
+                        |_: &bevy_simplenet::ServerContext, request: &ezsockets::Request|
+                        -> Result<Option<std::sync::Arc<dyn std::any::Any + Send + Sync>>, String>
+                        {
+                            if request.uri().query().unwrap_or_default().contains("client_id%22%3A1%7D")
+                            { return Err(String::from("rejected")); }
+                            Ok(Some(std::sync::Arc::new(String::from(request.uri().path()))))
+                        }
+                    )),
+                ..Default::default()
+            }
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(Vec::default())
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+    // the hook's context is attached to the session
+    let Some(path) = websocket_server.session_context::<String>(client_id)
+    else { panic!("session should have context"); };
+    assert_eq!(path.as_str(), "/ws");
+    assert!(websocket_server.session_context::<u64>(client_id).is_none());
+
+
+    // make rejected client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 1u128 },
+            bevy_simplenet::ClientConfig{
+                max_initial_connect_attempts: 1usize,
+                ..Default::default()
+            },
+            DemoConnectMsg(Vec::default())
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert!(!websocket_client.is_connected());
+    assert_eq!(websocket_server.num_connections(), 1u64);
+    let None = websocket_server.next()
+    else { panic!("server should not report the rejected client"); };
+
+    // the rejected client's request is answered with an HTTP error instead of being upgraded
+    assert_eq!(upgrade_response_status(websocket_server.url(), 1u128), "HTTP/1.1 403 Forbidden");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn server_context_hooks()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // app context
+    // - the load shed hook reads the overload flag, and the upgrade hook tags sessions with the server name
+    #[derive(Debug)]
+    struct AppContext
+    {
+        name       : String,
+        overloaded : std::sync::atomic::AtomicBool,
+    }
+    let context = std::sync::Arc::new(AppContext{
+            name       : String::from("app"),
+            overloaded : std::sync::atomic::AtomicBool::new(true),
+        });
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().try_new_server_with_context(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                load_shed: Some(std::sync::Arc::new(
+                        |context: &bevy_simplenet::ServerContext|
+                        context.get::<AppContext>().unwrap().overloaded.load(std::sync::atomic::Ordering::Relaxed)
+                    )),
+                on_upgrade: Some(std::sync::Arc::new(
+                        |context: &bevy_simplenet::ServerContext, _: &ezsockets::Request|
+                        -> Result<Option<std::sync::Arc<dyn std::any::Any + Send + Sync>>, String>
+                        {
+                            let Some(context) = context.get::<AppContext>() else { return Err(String::from("no context")); };
+                            Ok(Some(std::sync::Arc::new(context.name.clone())))
+                        }
+                    )),
+                ..Default::default()
+            },
+            context.clone()
+        ).unwrap();
+    assert_eq!(websocket_server.context::<AppContext>().unwrap().name, "app");
+
+    // connecting fails while the context reports the server is overloaded
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                max_initial_connect_attempts: 1usize,
+                ..Default::default()
+            },
+            DemoConnectMsg(Vec::default())
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert!(!websocket_client.is_connected());
+    assert_eq!(websocket_server.num_connections(), 0u64);
+
+    // connecting succeeds once the context reports the server is not overloaded
+    context.overloaded.store(false, std::sync::atomic::Ordering::Relaxed);
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(Vec::default())
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+    // the upgrade hook read the context
+    let Some(name) = websocket_server.session_context::<String>(client_id)
+    else { panic!("session should have context"); };
+    assert_eq!(name.as_str(), "app");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn session_tag()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // sessions can't be tagged before they connect
+    assert!(websocket_server.set_session_tag(0u128, String::from("alice")).is_err());
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(Vec::default())
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    assert!(websocket_server.session_tag::<String>(client_id).is_none());
+
+    // tag the session
+    websocket_server.set_session_tag(client_id, String::from("alice")).unwrap();
+    assert_eq!(websocket_server.session_tag::<String>(client_id), Some(String::from("alice")));
+    assert_eq!(websocket_server.with_session_tag(client_id, |tag: &String| tag.len()), Some(5));
+    assert!(websocket_server.session_tag::<u64>(client_id).is_none());
+
+    // the tag can be replaced
+    websocket_server.set_session_tag(client_id, 7u64).unwrap();
+    assert_eq!(websocket_server.session_tag::<u64>(client_id), Some(7u64));
+    assert!(websocket_server.session_tag::<String>(client_id).is_none());
+
+    // the tag is dropped when the session disconnects
+    websocket_client.close();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(_)))) = websocket_server.next()
+    else { panic!("server should be disconnected after client is disconnected"); };
+    assert!(websocket_server.session_tag::<u64>(client_id).is_none());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
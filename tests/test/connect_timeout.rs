@@ -0,0 +1,76 @@
+//local shortcuts
+
+//third-party shortcuts
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoServerMsg(pub u64);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoClientMsg(pub u64);
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = ();
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = ();
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+}
+
+fn client_demo_factory() -> bevy_simplenet::ClientFactory<DemoChannel>
+{
+    bevy_simplenet::ClientFactory::<DemoChannel>::new("test")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn connect_timeout()
+{
+    // prepare tokio runtime for client
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch a listener that accepts connections but never answers the websocket handshake
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = url::Url::parse(&format!("ws://{}/ws", listener.local_addr().unwrap())).unwrap();
+    let _stalled_streams = std::thread::spawn(
+            move ||
+            {
+                let mut streams = Vec::new();
+                for stream in listener.incoming() { streams.push(stream); }
+            }
+        );
+
+    // make client
+    // - the connection attempt stalls, so the client only fails if the connect timeout elapses
+    let start = std::time::Instant::now();
+    let Err(bevy_simplenet::ConnectError::ConnectionFailed) = client_demo_factory().new_client_blocking(
+            client_runtime,
+            url,
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                connect_timeout              : Some(std::time::Duration::from_millis(50)),
+                max_initial_connect_attempts : 1usize,
+                ..Default::default()
+            },
+            (),
+            std::time::Duration::from_secs(5)
+        )
+    else { panic!("client should fail to connect"); };
+
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
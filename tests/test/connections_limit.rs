@@ -1,6 +1,7 @@
 //local shortcuts
 
 //third-party shortcuts
+use enfync::Handle;
 use serde::{Serialize, Deserialize};
 
 //standard shortcuts
@@ -70,15 +71,19 @@ fn connections_limit_test(max_connections: u32)
                 max_msg_size      : 10_000,
                 rate_limit_config : bevy_simplenet::RateLimitConfig{
                         period    : std::time::Duration::from_millis(15),
-                        max_count : 25
+                        max_count : 25,
+                        max_bytes : u64::MAX
                     },
                 heartbeat_interval : std::time::Duration::from_secs(5),
                 keepalive_timeout  : std::time::Duration::from_secs(10),
+                ..Default::default()
             }
         );
 
     let websocket_url = websocket_server.url();
+    let connection_counter = websocket_server.connection_counter_handle();
     assert_eq!(websocket_server.num_connections(), 0u64);
+    assert_eq!(websocket_server.config().max_connections, max_connections);
 
 
     // 1. connect 'max connections' clients
@@ -140,7 +145,7 @@ fn connections_limit_test(max_connections: u32)
 
     let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedBySelf)) = client_to_disconnect.next()
     else { panic!("client should be closed by self"); };
-    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected))) = websocket_server.next()
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(_)))) = websocket_server.next()
     else { panic!("server should see a disconnected client"); };
     assert_eq!(websocket_server.num_connections(), (max_connections - 1) as u64);
 
@@ -188,6 +193,7 @@ fn connections_limit_test(max_connections: u32)
     let None = websocket_server.next()
     else { panic!("server should not connect to another client"); };
     assert_eq!(websocket_server.num_connections(), max_connections as u64);
+    assert_eq!(connection_counter.load(), max_connections as u64);
 
 
     // no more connection reports
@@ -209,3 +215,445 @@ fn connections_limit()
 }
 
 //-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn connections_not_accepted()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+    let websocket_url = websocket_server.url();
+    let connect_msg = DemoConnectMsg(String::from("hello"));
+
+    // stop accepting connections
+    assert!(websocket_server.is_accepting());
+    websocket_server.set_accepting(false);
+    assert!(!websocket_server.is_accepting());
+
+    // connecting a client should fail
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                max_initial_connect_attempts: 1usize,
+                ..Default::default()
+            },
+            connect_msg.clone()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert!(websocket_client.is_dead());
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::IsDead(_))) = websocket_client.next()
+    else { panic!("client should have failed to connect"); };
+    let None = websocket_server.next()
+    else { panic!("server should not connect to the client"); };
+
+    // resume accepting connections
+    websocket_server.set_accepting(true);
+
+    // connecting a client should succeed
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 1u128 },
+            bevy_simplenet::ClientConfig::default(),
+            connect_msg.clone()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, _)))) = websocket_server.next()
+    else { panic!("server should be connected to client"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn connections_load_shed()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server with a controllable load signal
+    let overloaded = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let overloaded_clone = overloaded.clone();
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                load_shed: Some(std::sync::Arc::new(
+                        move |_: &bevy_simplenet::ServerContext| overloaded_clone.load(std::sync::atomic::Ordering::Relaxed)
+                    )),
+                ..Default::default()
+            }
+        );
+    let websocket_url = websocket_server.url();
+    let connect_msg = DemoConnectMsg(String::from("hello"));
+
+    // connecting a client should fail while the server is overloaded
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                max_initial_connect_attempts: 1usize,
+                ..Default::default()
+            },
+            connect_msg.clone()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert!(websocket_client.is_dead());
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::IsDead(_))) = websocket_client.next()
+    else { panic!("client should have failed to connect"); };
+    let None = websocket_server.next()
+    else { panic!("server should not connect to the client"); };
+
+    // the load drops
+    overloaded.store(false, std::sync::atomic::Ordering::Relaxed);
+
+    // connecting a client should succeed
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 1u128 },
+            bevy_simplenet::ClientConfig::default(),
+            connect_msg.clone()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, _)))) = websocket_server.next()
+    else { panic!("server should be connected to client"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn lame_duck()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime.clone(),
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+    let websocket_url = websocket_server.url();
+    let connect_msg = DemoConnectMsg(String::from("hello"));
+
+    // connect two clients
+    let websocket_client1 = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 1u128 },
+            bevy_simplenet::ClientConfig::default(),
+            connect_msg.clone()
+        );
+    let websocket_client2 = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 2u128 },
+            bevy_simplenet::ClientConfig::default(),
+            connect_msg.clone()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert_eq!(websocket_server.num_connections(), 2u64);
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client1.next()
+    else { panic!("client should be connected to server"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client2.next()
+    else { panic!("client should be connected to server"); };
+
+    // enter lame-duck mode
+    let signal = websocket_server.begin_lame_duck(std::time::Duration::from_millis(100));
+    assert!(!websocket_server.is_accepting());
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // clients are notified
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ServerDraining)) = websocket_client1.next()
+    else { panic!("client should be notified that the server is draining"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ServerDraining)) = websocket_client2.next()
+    else { panic!("client should be notified that the server is draining"); };
+    assert!(!signal.is_done());
+
+    // one client migrates voluntarily
+    websocket_client1.close();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert_eq!(websocket_server.num_connections(), 1u64);
+    assert!(!signal.is_done());
+
+    // the other client is closed when the grace period expires
+    let signal_clone = signal.clone();
+    enfync::blocking::extract(server_runtime.spawn(async move { signal_clone.wait().await })).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert!(signal.is_done());
+    assert_eq!(websocket_server.num_connections(), 0u64);
+    assert!(websocket_client2.is_dead());
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(Some(close_frame)))) =
+        websocket_client2.next()
+    else { panic!("client should be closed by server"); };
+    assert_eq!(
+            bevy_simplenet::CloseReason::from_close_frame(&close_frame),
+            Some(bevy_simplenet::CloseReason::Draining)
+        );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn server_bind_failed()
+{
+    // prepare tokio runtime for servers
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime.clone(),
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+    let port = websocket_server.url().port().unwrap();
+
+    // launching another server on the same address should fail
+    let Err(bevy_simplenet::ServerStartError::BindFailed(_)) = server_demo_factory().try_new_server(
+            server_runtime,
+            ("127.0.0.1", port),
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        )
+    else { panic!("second server should fail to bind"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn ban_client()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+    let websocket_url = websocket_server.url();
+    let connect_msg = DemoConnectMsg(String::from("hello"));
+
+    // connect a client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            connect_msg.clone()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, _)))) = websocket_server.next()
+    else { panic!("server should be connected to client"); };
+
+    // ban the client: its session is closed
+    websocket_server.ban_client(0u128, None);
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert_eq!(websocket_server.num_connections(), 0u64);
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(Some(close_frame)))) =
+        websocket_client.next()
+    else { panic!("client should be closed by server"); };
+    assert_eq!(
+            bevy_simplenet::CloseReason::from_close_frame(&close_frame),
+            Some(bevy_simplenet::CloseReason::Banned)
+        );
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(_)))) = websocket_server.next()
+    else { panic!("server should be disconnected from client"); };
+
+    // the banned client can't reconnect
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                max_initial_connect_attempts: 1usize,
+                ..Default::default()
+            },
+            connect_msg.clone()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert!(websocket_client.is_dead());
+    let None = websocket_server.next()
+    else { panic!("server should not connect to the banned client"); };
+
+    // unban the client: it can connect again
+    websocket_server.unban_client(0u128);
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            connect_msg.clone()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, _)))) = websocket_server.next()
+    else { panic!("server should be connected to client"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn upgrade_timeout()
+{
+    // prepare tokio runtime for server
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                upgrade_timeout: std::time::Duration::from_millis(25),
+                ..Default::default()
+            }
+        );
+
+    // open a raw connection that never sends an upgrade request
+    let address = websocket_server.url().socket_addrs(|| None).unwrap()[0];
+    let mut stalled_stream = std::net::TcpStream::connect(address).unwrap();
+    stalled_stream.set_read_timeout(Some(std::time::Duration::from_secs(1))).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));  //wait for the upgrade timeout
+
+    // the server should have dropped the connection
+    let mut buf = [0u8; 16];
+    let closed = match std::io::Read::read(&mut stalled_stream, &mut buf)
+    {
+        Ok(num_bytes) => num_bytes == 0,
+        Err(err) => err.kind() != std::io::ErrorKind::WouldBlock && err.kind() != std::io::ErrorKind::TimedOut,
+    };
+    assert!(closed);
+    assert!(!websocket_server.is_dead());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn allowed_origins()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server that only allows one origin
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                allowed_origins: Some(vec![String::from("https://good.example")]),
+                ..Default::default()
+            }
+        );
+    let websocket_url = websocket_server.url();
+    let connect_msg = DemoConnectMsg(String::from("hello"));
+
+    // a client with a disallowed origin can't connect
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                max_initial_connect_attempts: 1usize,
+                origin: Some(String::from("https://bad.example")),
+                ..Default::default()
+            },
+            connect_msg.clone()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert!(websocket_client.is_dead());
+    let None = websocket_server.next()
+    else { panic!("server should not connect to a client with a disallowed origin"); };
+
+    // clients with an allowed origin or no origin can connect
+    let websocket_client1 = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 1u128 },
+            bevy_simplenet::ClientConfig{
+                origin: Some(String::from("https://good.example")),
+                user_agent: Some(String::from("simplenet-test")),
+                ..Default::default()
+            },
+            connect_msg.clone()
+        );
+    let websocket_client2 = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 2u128 },
+            bevy_simplenet::ClientConfig::default(),
+            connect_msg.clone()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client1.next()
+    else { panic!("client with allowed origin should be connected to server"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client2.next()
+    else { panic!("client without an origin should be connected to server"); };
+    assert_eq!(websocket_server.num_connections(), 2u64);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,265 @@
+//local shortcuts
+
+//third-party shortcuts
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+use std::io::{Read, Write};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoServerMsg(pub u64);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoClientMsg(pub u64);
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = ();
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = ();
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+}
+
+type _DemoServer = bevy_simplenet::Server<DemoChannel>;
+type DemoServerEvent = bevy_simplenet::ServerEventFrom<DemoChannel>;
+type DemoServerReport = bevy_simplenet::ServerReport<<DemoChannel as bevy_simplenet::ChannelPack>::ConnectMsg>;
+
+fn server_demo_factory() -> bevy_simplenet::ServerFactory<DemoChannel>
+{
+    bevy_simplenet::ServerFactory::<DemoChannel>::new("test")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Open a raw websocket connection to the server, without any automatic ping/pong or close handling.
+fn raw_websocket_connect(server_url: url::Url, client_id: u128) -> std::net::TcpStream
+{
+    // build the connection request the same way clients do
+    let auth = serde_json::to_string(&bevy_simplenet::AuthRequest::None{ client_id }).unwrap();
+    let mut url = server_url.clone();
+    url.query_pairs_mut()
+        .append_pair("v", "test")
+        .append_pair("t", bevy_simplenet::env_type_as_str(bevy_simplenet::EnvType::Native))
+        .append_pair("a", auth.as_str())
+        .append_pair("c", "null");
+
+    // send the upgrade request
+    let address = server_url.socket_addrs(|| None).unwrap()[0];
+    let mut stream = std::net::TcpStream::connect(address).unwrap();
+    let request = format!(
+            "GET {} HTTP/1.1\r\n\
+            Host: {}\r\n\
+            Upgrade: websocket\r\n\
+            Connection: Upgrade\r\n\
+            Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+            Sec-WebSocket-Version: 13\r\n\r\n",
+            &url[url::Position::BeforePath..],
+            address
+        );
+    stream.write_all(request.as_bytes()).unwrap();
+
+    // read the upgrade response
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n")
+    {
+        stream.read_exact(&mut byte).unwrap();
+        response.push(byte[0]);
+    }
+    assert!(String::from_utf8_lossy(&response).starts_with("HTTP/1.1 101"));
+
+    stream
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn disconnect_transport_error()
+{
+    // launch websocket server
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // connect a raw client
+    let mut stream = raw_websocket_connect(websocket_server.url(), 1u128);
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+
+
+    // send an unmasked frame, which clients are not allowed to send
+    stream.write_all(&[0x82u8, 0x01u8, 0x00u8]).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // the server drops the connection
+    // - ezsockets ends the session without surfacing the protocol error, so it may be reported as a lost connection
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(reason)))) = websocket_server.next()
+    else { panic!("server should report the disconnect"); };
+    assert!(matches!(
+            reason,
+            bevy_simplenet::DisconnectReason::ConnectionLost | bevy_simplenet::DisconnectReason::TransportError
+        ));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn disconnect_connection_lost()
+{
+    // launch websocket server
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // connect a raw client
+    let stream = raw_websocket_connect(websocket_server.url(), 1u128);
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+
+
+    // cut the connection without a close frame
+    stream.shutdown(std::net::Shutdown::Both).unwrap();
+    drop(stream);
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // the keepalive timeout has not elapsed, so this must not be reported as a timeout
+    // - the transport may report the reset as an error
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(reason)))) = websocket_server.next()
+    else { panic!("server should report the disconnect"); };
+    assert!(matches!(
+            reason,
+            bevy_simplenet::DisconnectReason::ConnectionLost | bevy_simplenet::DisconnectReason::TransportError
+        ));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn disconnect_timed_out()
+{
+    // launch websocket server with a short keepalive timeout
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                heartbeat_interval : std::time::Duration::from_millis(10),
+                keepalive_timeout  : std::time::Duration::from_millis(50),
+                ..Default::default()
+            }
+        );
+
+    // connect a raw client that never answers heartbeat pings
+    let stream = raw_websocket_connect(websocket_server.url(), 1u128);
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+
+
+    // wait for the keepalive timeout
+    // - the server sends a close frame once the timeout elapses, but a silent peer never completes the close
+    //   handshake, so the raw client drops its end afterward
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    stream.shutdown(std::net::Shutdown::Both).unwrap();
+    drop(stream);
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(
+            bevy_simplenet::DisconnectReason::TimedOut
+        )))) = websocket_server.next()
+    else { panic!("server should report a keepalive timeout"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn disconnect_missed_pongs()
+{
+    // launch websocket server that closes sessions after missed pongs
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                heartbeat_interval : std::time::Duration::from_millis(10),
+                max_missed_pongs   : Some(2),
+                ..Default::default()
+            }
+        );
+
+    // connect a raw client that never answers heartbeat pings
+    let mut stream = raw_websocket_connect(websocket_server.url(), 1u128);
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(1))).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+
+
+    // read frames until the server closes the session
+    // - the server's frames are unmasked
+    let close_code = loop
+    {
+        let mut header = [0u8; 2];
+        stream.read_exact(&mut header).unwrap();
+        let len = match header[1] & 0x7fu8
+        {
+            126 => { let mut len = [0u8; 2]; stream.read_exact(&mut len).unwrap(); u16::from_be_bytes(len) as usize }
+            127 => { let mut len = [0u8; 8]; stream.read_exact(&mut len).unwrap(); u64::from_be_bytes(len) as usize }
+            len => len as usize,
+        };
+        let mut payload = vec![0u8; len];
+        stream.read_exact(&mut payload).unwrap();
+
+        if header[0] & 0x0fu8 == 0x08u8 { break u16::from_be_bytes([payload[0], payload[1]]); }
+    };
+    assert_eq!(
+            bevy_simplenet::CloseReason::from_code(close_code),
+            Some(bevy_simplenet::CloseReason::Unresponsive)
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(
+            bevy_simplenet::DisconnectReason::ServerClosed
+        )))) = websocket_server.next()
+    else { panic!("server should report closing the unresponsive session"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,381 @@
+//local shortcuts
+
+//third-party shortcuts
+use enfync::Handle;
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoServerMsg(pub u64);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoClientMsg(pub u64);
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = ();
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = ();
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+}
+
+type _DemoServer = bevy_simplenet::Server<DemoChannel>;
+type _DemoClient = bevy_simplenet::Client<DemoChannel>;
+type DemoClientEvent = bevy_simplenet::ClientEventFrom<DemoChannel>;
+type DemoServerEvent = bevy_simplenet::ServerEventFrom<DemoChannel>;
+type DemoServerReport = bevy_simplenet::ServerReport<<DemoChannel as bevy_simplenet::ChannelPack>::ConnectMsg>;
+
+fn server_demo_factory() -> bevy_simplenet::ServerFactory<DemoChannel>
+{
+    bevy_simplenet::ServerFactory::<DemoChannel>::new("test")
+}
+
+fn client_demo_factory() -> bevy_simplenet::ClientFactory<DemoChannel>
+{
+    bevy_simplenet::ClientFactory::<DemoChannel>::new("test")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn event_callbacks()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    let (server_event_sender, server_event_receiver) = std::sync::mpsc::channel();
+    websocket_server.on_event(Box::new(move |id, event| { let _ = server_event_sender.send((id, event)); }));
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+
+    let (client_event_sender, client_event_receiver) = std::sync::mpsc::channel();
+    websocket_client.on_event(Box::new(move |event| { let _ = client_event_sender.send(event); }));
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Ok((client_id, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = server_event_receiver.try_recv()
+    else { panic!("server should be connected once client is connected"); };
+    let Ok(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = client_event_receiver.try_recv()
+    else { panic!("client should be connected to server"); };
+
+
+    // send messages: client -> server, server -> client
+    websocket_client.send(DemoClientMsg(42)).unwrap();
+    websocket_server.send(client_id, DemoServerMsg(24)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Ok((msg_client_id, DemoServerEvent::Msg(DemoClientMsg(42)))) = server_event_receiver.try_recv()
+    else { panic!("server did not receive client msg"); };
+    assert_eq!(client_id, msg_client_id);
+    let Ok(DemoClientEvent::Msg(DemoServerMsg(24))) = client_event_receiver.try_recv()
+    else { panic!("client did not receive server msg"); };
+
+    // events are not available for polling
+    let None = websocket_server.next()
+    else { panic!("server events should only go to the callback"); };
+    let None = websocket_client.next()
+    else { panic!("client events should only go to the callback"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn server_next_async()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = std::sync::Arc::new(server_demo_factory().new_server(
+            server_runtime.clone(),
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        ));
+
+    // wait for events asynchronously
+    let server_clone = websocket_server.clone();
+    let pending_events = server_runtime.spawn(
+            async move
+            {
+                let connected = server_clone.next_async().await;
+                let msg = server_clone.next_async().await;
+                (connected, msg)
+            }
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send message: client -> server
+    websocket_client.send(DemoClientMsg(42)).unwrap();
+
+    let (connected, msg) = enfync::blocking::extract(pending_events).unwrap();
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = connected
+    else { panic!("server should be connected once client is connected"); };
+    let Some((msg_client_id, DemoServerEvent::Msg(DemoClientMsg(42)))) = msg
+    else { panic!("server did not receive client msg"); };
+    assert_eq!(client_id, msg_client_id);
+
+    // events are not available for polling
+    let None = websocket_server.next()
+    else { panic!("server events should only be received once"); };
+
+
+    // cancelled waits don't consume events
+    let server_clone = websocket_server.clone();
+    let timed_out = enfync::blocking::extract(server_runtime.spawn(
+            async move
+            {
+                tokio::time::timeout(std::time::Duration::from_millis(10), server_clone.next_async()).await.is_err()
+            }
+        )).unwrap();
+    assert!(timed_out);
+
+    websocket_client.send(DemoClientMsg(43)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Msg(DemoClientMsg(43)))) = websocket_server.next()
+    else { panic!("server did not receive client msg"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Default)]
+struct CountingObserver
+{
+    connects    : std::sync::atomic::AtomicU64,
+    disconnects : std::sync::atomic::AtomicU64,
+    msgs_in     : std::sync::atomic::AtomicU64,
+    msgs_out    : std::sync::atomic::AtomicU64,
+}
+
+impl bevy_simplenet::Observer for CountingObserver
+{
+    fn on_connect(&self, _id: u128) { self.connects.fetch_add(1, std::sync::atomic::Ordering::Relaxed); }
+    fn on_disconnect(&self, _id: u128) { self.disconnects.fetch_add(1, std::sync::atomic::Ordering::Relaxed); }
+    fn on_msg_in(&self, _id: u128, _size: usize) { self.msgs_in.fetch_add(1, std::sync::atomic::Ordering::Relaxed); }
+    fn on_msg_out(&self, _id: u128, _size: usize) { self.msgs_out.fetch_add(1, std::sync::atomic::Ordering::Relaxed); }
+}
+
+#[test]
+fn observer()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server with an observer
+    let server_observer = std::sync::Arc::new(CountingObserver::default());
+    let websocket_server = server_demo_factory().with_observer(server_observer.clone()).new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client with an observer
+    let client_observer = std::sync::Arc::new(CountingObserver::default());
+    let websocket_client = client_demo_factory().with_observer(client_observer.clone()).new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+    assert_eq!(server_observer.connects.load(std::sync::atomic::Ordering::Relaxed), 1);
+    assert_eq!(client_observer.connects.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+
+    // send messages: client -> server, server -> client
+    let server_msgs_out = server_observer.msgs_out.load(std::sync::atomic::Ordering::Relaxed);
+    websocket_client.send(DemoClientMsg(42)).unwrap();
+    websocket_server.send(client_id, DemoServerMsg(24)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Msg(DemoClientMsg(42)))) = websocket_server.next()
+    else { panic!("server did not receive client msg"); };
+    let Some(DemoClientEvent::Msg(DemoServerMsg(24))) = websocket_client.next()
+    else { panic!("client did not receive server msg"); };
+    assert_eq!(server_observer.msgs_in.load(std::sync::atomic::Ordering::Relaxed), 1);
+    assert_eq!(server_observer.msgs_out.load(std::sync::atomic::Ordering::Relaxed), server_msgs_out + 1);
+    assert_eq!(client_observer.msgs_out.load(std::sync::atomic::Ordering::Relaxed), 1);
+    assert!(client_observer.msgs_in.load(std::sync::atomic::Ordering::Relaxed) >= 1);
+
+
+    // disconnect
+    websocket_client.close();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert_eq!(server_observer.disconnects.load(std::sync::atomic::Ordering::Relaxed), 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn inbound_middleware()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server with middleware that drops zeros and clamps large values
+    let websocket_server = server_demo_factory()
+        .with_inbound_middleware(std::sync::Arc::new(
+                |_id: bevy_simplenet::SessionID, msg: DemoClientMsg|
+                {
+                    if msg.0 == 0 { return None; }
+                    Some(DemoClientMsg(msg.0.min(100)))
+                }
+            ))
+        .new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send messages: client -> server
+    websocket_client.send(DemoClientMsg(0)).unwrap();
+    websocket_client.send(DemoClientMsg(42)).unwrap();
+    websocket_client.send(DemoClientMsg(500)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Msg(DemoClientMsg(42)))) = websocket_server.next()
+    else { panic!("server should receive the unmodified msg"); };
+    let Some((_, DemoServerEvent::Msg(DemoClientMsg(100)))) = websocket_server.next()
+    else { panic!("server should receive the clamped msg"); };
+    let None = websocket_server.next()
+    else { panic!("server should receive no more values"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn client_ping()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime.clone(),
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // ping the server
+    let ping = websocket_client.ping();
+    let rtt = enfync::blocking::extract(server_runtime.spawn(ping)).unwrap();
+    let Some(rtt) = rtt else { panic!("ping should receive a pong"); };
+    assert!(rtt < std::time::Duration::from_secs(1));
+
+    // pongs are not emitted as events
+    let None = websocket_client.next()
+    else { panic!("client should receive no more values"); };
+    let None = websocket_server.next()
+    else { panic!("server should receive no more values"); };
+
+
+    // pings fail while disconnected
+    websocket_client.close();
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let rtt = enfync::blocking::extract(server_runtime.spawn(websocket_client.ping())).unwrap();
+    assert_eq!(rtt, None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -1,9 +1,11 @@
 //local shortcuts
 
 //third-party shortcuts
+use enfync::Handle;
 use serde::{Serialize, Deserialize};
 
 //standard shortcuts
+use std::sync::{Arc, Mutex};
 
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -17,6 +19,14 @@ pub struct DemoServerMsg(pub u64);
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DemoClientMsg(pub u64);
 
+/// close payload
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct DemoBanDetails
+{
+    pub days: u32,
+    pub appeal_url: String,
+}
+
 /// client connect message
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DemoConnectMsg(pub String);
@@ -79,10 +89,12 @@ fn hello_world()
                 max_msg_size      : 1_000,
                 rate_limit_config : bevy_simplenet::RateLimitConfig{
                         period    : std::time::Duration::from_secs(1),
-                        max_count : 20
+                        max_count : 20,
+                        max_bytes : u64::MAX
                     },
                 heartbeat_interval : std::time::Duration::from_secs(5),
                 keepalive_timeout  : std::time::Duration::from_secs(10),
+                ..Default::default()
             }
         );
 
@@ -105,12 +117,37 @@ fn hello_world()
 
     std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
 
-    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(_, connect_msg)))) = websocket_server.next()
+    assert_eq!(websocket_server.queued_events(), 1usize);
+    assert_eq!(websocket_client.queued_events(), 1usize);
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(details, connect_msg)))) = websocket_server.next()
     else { panic!("server should be connected once client is connected"); };
     let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
     else { panic!("client should be connected to server"); };
     assert_eq!(connect_msg.0, connect_msg1.0);
+    assert_eq!(details.client_id, client_id);
+    assert_eq!(details.env_type, bevy_simplenet::EnvType::Native);
+    assert!(details.peer_addr.ip().is_loopback());
+    assert!(details.tls_info.is_none());
+    assert_eq!(details.protocol_version, "test");
+    assert_eq!(websocket_server.queued_events(), 0usize);
+    assert_eq!(websocket_client.queued_events(), 0usize);
     assert_eq!(websocket_server.num_connections(), 1u64);
+    assert_eq!(websocket_client.status(), bevy_simplenet::ClientStatus::Connected);
+    assert!(websocket_server.session_connected_at(client_id).unwrap() <= std::time::SystemTime::now());
+    let Some(bevy_simplenet::AuthRequest::None{ client_id: auth_client_id }) = websocket_server.session_auth(client_id)
+    else { panic!("server should expose the session's auth request"); };
+    assert_eq!(auth_client_id, client_id);
+    assert!(websocket_server.session_peer_addr(client_id).unwrap().ip().is_loopback());
+    assert!(websocket_server.session_tls_info(client_id).is_none());
+    assert_eq!(websocket_server.session_env_type(client_id), Some(bevy_simplenet::EnvType::Native));
+    let snapshot = websocket_server.debug_snapshot().unwrap();
+    assert_eq!(snapshot.num_connections, 1);
+    assert_eq!(snapshot.sessions.len(), 1);
+    assert_eq!(snapshot.sessions[0].id, client_id);
+    assert_eq!(snapshot.sessions[0].env_type, Some(bevy_simplenet::EnvType::Native));
+    assert_eq!(snapshot.sessions[0].pending_requests, 0);
+    assert_eq!(snapshot.sessions[0].outbound_queued, 0);
+    assert_eq!(websocket_server.send_to_client(0u128, DemoServerMsg(0)), Ok(0));
 
 
     // send message: client -> server
@@ -128,6 +165,17 @@ fn hello_world()
     assert_eq!(signal.status(), ezsockets::MessageStatus::Sent);
 
 
+    // send untracked message: client -> server
+    tracing::info!("ws hello world test: client sending untracked msg...");
+    websocket_client.send_and_forget(DemoClientMsg(client_val + 1)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Msg(DemoClientMsg(msg_client_val)))) = websocket_server.next()
+    else { panic!("server did not receive untracked client msg"); };
+    assert_eq!(client_val + 1, msg_client_val);
+
+
     // send message: server -> client
     tracing::info!("ws hello world test: server sending msg...");
     let server_val = 24;
@@ -140,13 +188,20 @@ fn hello_world()
     assert_eq!(server_val, msg_server_val);
 
 
+    // send message with a time-to-live: server -> client
+    tracing::info!("ws hello world test: server sending msg with ttl...");
+    websocket_server.send_with_ttl(client_id, DemoServerMsg(server_val + 1), std::time::Duration::from_secs(1)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Msg(DemoServerMsg(msg_server_val))) = websocket_client.next()
+    else { panic!("client did not receive server msg"); };
+    assert_eq!(server_val + 1, msg_server_val);
+
+
     // server closes client
     tracing::info!("ws hello world test: server closing client...");
-    let closure_frame =
-        ezsockets::CloseFrame{
-            code   : ezsockets::CloseCode::Normal,
-            reason : String::from("test")
-        };
+    let closure_frame = bevy_simplenet::CloseReason::Kicked.close_frame("test");
     websocket_server.close_session(client_id, closure_frame).unwrap();
 
     std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
@@ -154,10 +209,24 @@ fn hello_world()
     assert!(!websocket_server.is_dead());
     assert!(websocket_client.is_dead());
     assert_eq!(websocket_server.num_connections(), 0u64);
+    assert!(websocket_server.session_connected_at(client_id).is_none());
+    assert_eq!(websocket_client.status(), bevy_simplenet::ClientStatus::Dead);
 
-    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(_))) = websocket_client.next()
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(Some(close_frame)))) = websocket_client.next()
     else { panic!("client should be closed by server"); };
-    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected))) = websocket_server.next()
+    assert_eq!(
+            bevy_simplenet::CloseReason::from_close_frame(&close_frame),
+            Some(bevy_simplenet::CloseReason::Kicked)
+        );
+    let Some(bevy_simplenet::DeathReason::ClosedByServer(Some(close_frame))) = websocket_client.death_reason()
+    else { panic!("client's death reason should be the server close"); };
+    assert_eq!(
+            bevy_simplenet::CloseReason::from_close_frame(&close_frame),
+            Some(bevy_simplenet::CloseReason::Kicked)
+        );
+    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected(
+            bevy_simplenet::DisconnectReason::ServerClosed
+        )))) = websocket_server.next()
     else { panic!("server should be disconnected after client is disconnected (by server)"); };
     assert_eq!(client_id, dc_client_id);
 
@@ -195,7 +264,9 @@ fn hello_world()
     assert!(websocket_client.is_dead());
     assert_eq!(websocket_server.num_connections(), 0u64);
 
-    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected))) = websocket_server.next()
+    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected(
+            bevy_simplenet::DisconnectReason::ClientClosed(_)
+        )))) = websocket_server.next()
     else { panic!("server should be disconnected after client is disconnected (by client)"); };
     let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedBySelf)) = websocket_client.next()
     else { panic!("client should have closed itself"); };
@@ -212,3 +283,767 @@ fn hello_world()
 }
 
 //-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn close_after_flush()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send goodbye messages then close
+    // - the rate limiter counts the connection as a message, so stay below the default limit
+    let signals: Vec<_> = (0..5u64).map(|val| websocket_client.send(DemoClientMsg(val)).unwrap()).collect();
+    let mut flush = websocket_client.flush();
+    websocket_client.close_after_flush();
+    assert!(websocket_client.is_closed());
+    assert_eq!(websocket_client.status(), bevy_simplenet::ClientStatus::ClosingBySelf);
+    assert!(websocket_client.send(DemoClientMsg(5u64)).is_err());
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    assert!(websocket_client.is_dead());
+    for signal in signals.iter() { assert_eq!(signal.status(), ezsockets::MessageStatus::Sent); }
+    assert!(flush.is_done());
+
+
+    // server receives all messages before the disconnect
+    for val in 0..5u64
+    {
+        let Some((msg_client_id, DemoServerEvent::Msg(DemoClientMsg(msg_val)))) = websocket_server.next()
+        else { panic!("server did not receive client msg"); };
+        assert_eq!(msg_client_id, client_id);
+        assert_eq!(msg_val, val);
+    }
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(
+            bevy_simplenet::DisconnectReason::ClientClosed(_)
+        )))) = websocket_server.next()
+    else { panic!("server should be disconnected after client closes itself"); };
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedBySelf)) = websocket_client.next()
+    else { panic!("client should have closed itself"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::IsDead(_))) = websocket_client.next()
+    else { panic!("client should be dead"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn await_flush()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime.clone(),
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send messages then wait for them to flush
+    // - the rate limiter counts the connection as a message, so stay below the default limit
+    let signals: Vec<_> = (0..5u64).map(|val| websocket_client.send(DemoClientMsg(val)).unwrap()).collect();
+    enfync::blocking::extract(server_runtime.spawn(websocket_client.flush())).unwrap();
+    for signal in signals.iter() { assert_eq!(signal.status(), ezsockets::MessageStatus::Sent); }
+
+    // flushing with nothing pending resolves immediately
+    enfync::blocking::extract(server_runtime.spawn(websocket_client.flush())).unwrap();
+    assert!(websocket_client.is_connected());
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // server receives all messages
+    for val in 0..5u64
+    {
+        let Some((msg_client_id, DemoServerEvent::Msg(DemoClientMsg(msg_val)))) = websocket_server.next()
+        else { panic!("server did not receive client msg"); };
+        assert_eq!(msg_client_id, client_id);
+        assert_eq!(msg_val, val);
+    }
+
+
+    // flushes resolve when the client dies with messages still pending
+    let signal = websocket_client.send(DemoClientMsg(5u64)).unwrap();
+    let flush = websocket_client.flush();
+    websocket_client.close();
+    enfync::blocking::extract(server_runtime.spawn(flush)).unwrap();
+    assert_ne!(signal.status(), ezsockets::MessageStatus::Sending);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Records the threads that the connection handler and session handler run on.
+#[derive(Debug, Default)]
+struct HandlerThreadObserver
+{
+    thread_names: Mutex<Vec<String>>,
+}
+
+impl HandlerThreadObserver
+{
+    fn record(&self)
+    {
+        let name = std::thread::current().name().unwrap_or_default().to_string();
+        self.thread_names.lock().unwrap().push(name);
+    }
+}
+
+impl bevy_simplenet::Observer for HandlerThreadObserver
+{
+    fn on_connect(&self, _id: bevy_simplenet::SessionID) { self.record(); }
+    fn on_msg_in(&self, _id: bevy_simplenet::SessionID, _size: usize) { self.record(); }
+}
+
+#[test]
+fn dedicated_handler_thread()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server with the connection handler on its own thread
+    let observer = Arc::new(HandlerThreadObserver::default());
+    let websocket_server = server_demo_factory().with_observer(observer.clone()).new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                dedicated_handler_thread: true,
+                ..Default::default()
+            }
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send messages: client -> server, server -> client
+    websocket_client.send(DemoClientMsg(42)).unwrap();
+    websocket_server.send(client_id, DemoServerMsg(24)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((msg_client_id, DemoServerEvent::Msg(DemoClientMsg(42)))) = websocket_server.next()
+    else { panic!("server did not receive client msg"); };
+    assert_eq!(client_id, msg_client_id);
+    let Some(DemoClientEvent::Msg(DemoServerMsg(24))) = websocket_client.next()
+    else { panic!("client did not receive server msg"); };
+    assert!(!websocket_server.is_dead());
+
+    // the connection handler and session handler ran on the dedicated thread
+    assert_eq!(observer.thread_names.lock().unwrap().as_slice(), ["simplenet-handler", "simplenet-handler"]);
+
+    // dropping the server closes the session before stopping the handler thread
+    drop(websocket_server);
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(Some(close_frame)))) =
+        websocket_client.next()
+    else { panic!("client should be closed by server"); };
+    assert_eq!(
+            bevy_simplenet::CloseReason::from_close_frame(&close_frame),
+            Some(bevy_simplenet::CloseReason::Draining)
+        );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn text_frames()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client that uses text frames
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                text_frames: true,
+                ..Default::default()
+            },
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+    assert!(websocket_server.debug_snapshot().unwrap().sessions[0].text_frames);
+
+
+    // send messages: client -> server, server -> client
+    websocket_client.send(DemoClientMsg(42)).unwrap();
+    websocket_server.send(client_id, DemoServerMsg(24)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((msg_client_id, DemoServerEvent::Msg(DemoClientMsg(42)))) = websocket_server.next()
+    else { panic!("server did not receive client msg"); };
+    assert_eq!(client_id, msg_client_id);
+    let Some(DemoClientEvent::Msg(DemoServerMsg(24))) = websocket_client.next()
+    else { panic!("client did not receive server msg"); };
+    assert!(!websocket_server.is_dead());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn send_tracked()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send tracked messages to the client and to an unknown session
+    let signal_id = websocket_server.send_tracked(client_id, DemoServerMsg(24)).unwrap();
+    let unknown_signal_id = websocket_server.send_tracked(client_id + 1, DemoServerMsg(25)).unwrap();
+    assert_ne!(signal_id, unknown_signal_id);
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Msg(DemoServerMsg(24))) = websocket_client.next()
+    else { panic!("client did not receive server msg"); };
+
+    let mut receipts = Vec::default();
+    while let Some((id, DemoServerEvent::DeliveryReceipt{ signal_id, delivered })) = websocket_server.next()
+    {
+        receipts.push((id, signal_id, delivered));
+    }
+    receipts.sort_by_key(|(_, signal_id, _)| *signal_id);
+    assert_eq!(receipts, vec![(client_id, signal_id, true), (client_id + 1, unknown_signal_id, false)]);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn disable_auto_reconnect()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client that reconnects when closed by the server
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                reconnect_on_server_close : true,
+                reconnect_interval        : std::time::Duration::from_millis(10),
+                ..Default::default()
+            },
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // disable reconnects, then close the client from the server
+    websocket_client.set_auto_reconnect(false);
+    let closure_frame = bevy_simplenet::CloseReason::Kicked.close_frame("test");
+    websocket_server.close_session(client_id, closure_frame).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(50));  //wait for async machinery
+
+    // the client should die instead of reconnecting
+    assert!(websocket_client.is_dead());
+    assert_eq!(websocket_server.num_connections(), 0u64);
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(_))) = websocket_client.next()
+    else { panic!("client should be closed by server"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::IsDead(_))) = websocket_client.next()
+    else { panic!("client should be dead"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn next_batch()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send several messages: client -> server
+    for val in 0..5 { websocket_client.send(DemoClientMsg(val)).unwrap(); }
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // drain in batches
+    let batch = websocket_server.next_batch(3);
+    assert_eq!(batch.len(), 3);
+    for (val, (msg_client_id, event)) in batch.into_iter().enumerate()
+    {
+        assert_eq!(msg_client_id, client_id);
+        let DemoServerEvent::Msg(DemoClientMsg(msg_val)) = event else { panic!("server should receive client msg"); };
+        assert_eq!(msg_val, val as u64);
+    }
+
+    let batch = websocket_server.next_batch(3);
+    assert_eq!(batch.len(), 2);
+    let (_, DemoServerEvent::Msg(DemoClientMsg(4))) = batch[1] else { panic!("server should receive last client msg"); };
+
+    assert!(websocket_server.next_batch(3).is_empty());
+    assert!(websocket_server.next().is_none());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn coalesced_events()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server that coalesces its events
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                coalesce_events: true,
+                ..Default::default()
+            }
+        );
+
+    // make clients
+    let websocket_clients: Vec<_> = (0..3u128)
+        .map(
+            |client_id|
+            client_demo_factory().new_client(
+                client_runtime.clone(),
+                websocket_server.url(),
+                bevy_simplenet::AuthRequest::None{ client_id },
+                bevy_simplenet::ClientConfig::default(),
+                DemoConnectMsg(String::from("hello!"))
+            )
+        )
+        .collect();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let connections = websocket_server.next_batch(10);
+    assert_eq!(connections.len(), 3);
+    for (_, event) in connections
+    {
+        let DemoServerEvent::Report(DemoServerReport::Connected(..)) = event
+        else { panic!("server should be connected once clients are connected"); };
+    }
+
+
+    // send several messages from each client: client -> server
+    for val in 0..4
+    {
+        for websocket_client in websocket_clients.iter() { websocket_client.send(DemoClientMsg(val)).unwrap(); }
+    }
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+    assert_eq!(websocket_server.queued_events(), 12);
+
+    // drain one at a time and in bulk, each client's messages are received in order
+    let mut next_vals = [0u64; 3];
+    let mut check_event = |(client_id, event): (bevy_simplenet::SessionID, DemoServerEvent)|
+    {
+        let DemoServerEvent::Msg(DemoClientMsg(val)) = event else { panic!("server should receive client msg"); };
+        assert_eq!(val, next_vals[client_id as usize]);
+        next_vals[client_id as usize] += 1;
+    };
+
+    for _ in 0..5 { check_event(websocket_server.next().unwrap()); }
+    assert_eq!(websocket_server.queued_events(), 7);
+    for event in websocket_server.next_batch(10) { check_event(event); }
+
+    assert_eq!(next_vals, [4u64; 3]);
+    assert_eq!(websocket_server.queued_events(), 0);
+    assert!(websocket_server.next().is_none());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn max_reconnect_duration()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client that reconnects for a limited time
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                reconnect_on_server_close : true,
+                reconnect_interval        : std::time::Duration::from_millis(10),
+                max_reconnect_duration    : Some(std::time::Duration::from_millis(50)),
+                ..Default::default()
+            },
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // stop accepting connections, then close the client from the server
+    websocket_server.set_accepting(false);
+    let closure_frame = bevy_simplenet::CloseReason::Kicked.close_frame("test");
+    websocket_server.close_session(client_id, closure_frame).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // the client keeps trying to reconnect until the duration elapses
+    assert!(!websocket_client.is_dead());
+    assert!(websocket_client.death_reason().is_none());
+
+    std::thread::sleep(std::time::Duration::from_millis(100));  //wait for async machinery
+
+    assert!(websocket_client.is_dead());
+    let Some(bevy_simplenet::DeathReason::ReconnectTimedOut) = websocket_client.death_reason()
+    else { panic!("client should die after failing to reconnect"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(_))) = websocket_client.next()
+    else { panic!("client should be closed by server"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::IsDead(_))) = websocket_client.next()
+    else { panic!("client should be dead"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn connect_attempts_exhausted()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // find an address with no listener
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = url::Url::parse(&format!("ws://{}/ws", listener.local_addr().unwrap())).unwrap();
+    drop(listener);
+
+    // make client that gives up connecting after a few attempts
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            url,
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                reconnect_interval           : std::time::Duration::from_millis(10),
+                max_initial_connect_attempts : 3usize,
+                ..Default::default()
+            },
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(100));  //wait for async machinery
+
+    assert!(websocket_client.is_dead());
+    let Some(bevy_simplenet::DeathReason::ReconnectAttemptsExhausted) = websocket_client.death_reason()
+    else { panic!("client should die after running out of connection attempts"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::IsDead(_))) = websocket_client.next()
+    else { panic!("client should be dead"); };
+
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client that gives up reconnecting after a few attempts
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                reconnect_on_server_close : true,
+                reconnect_interval        : std::time::Duration::from_millis(10),
+                max_reconnect_attempts    : 3usize,
+                ..Default::default()
+            },
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // stop accepting connections, then close the client from the server
+    websocket_server.set_accepting(false);
+    let closure_frame = bevy_simplenet::CloseReason::Kicked.close_frame("test");
+    websocket_server.close_session(client_id, closure_frame).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(100));  //wait for async machinery
+
+    assert!(websocket_client.is_dead());
+    let Some(bevy_simplenet::DeathReason::ReconnectAttemptsExhausted) = websocket_client.death_reason()
+    else { panic!("client should die after running out of reconnect attempts"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(_))) = websocket_client.next()
+    else { panic!("client should be closed by server"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::IsDead(_))) = websocket_client.next()
+    else { panic!("client should be dead"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn close_and_wait()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+
+
+    // close the client and wait for it to shut down
+    assert!(websocket_client.close_and_wait(std::time::Duration::from_secs(1)));
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected(_)))) = websocket_server.next()
+    else { panic!("server should be disconnected after client is closed"); };
+    assert_eq!(client_id, dc_client_id);
+    assert_eq!(websocket_server.num_connections(), 0u64);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn close_payload()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // payloads that don't fit in a close frame are rejected
+    let long_payload = DemoBanDetails{ days: 1, appeal_url: "x".repeat(bevy_simplenet::MAX_CLOSE_REASON_LEN) };
+    assert!(bevy_simplenet::CloseReason::Banned.close_frame_with_payload(&long_payload).is_err());
+
+
+    // server closes client with a payload
+    let payload = DemoBanDetails{ days: 3, appeal_url: String::from("https://example.com/appeal") };
+    let close_frame = bevy_simplenet::CloseReason::Banned.close_frame_with_payload(&payload).unwrap();
+    websocket_server.close_session(client_id, close_frame).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(Some(close_frame)))) = websocket_client.next()
+    else { panic!("client should be closed by server"); };
+    assert_eq!(
+            bevy_simplenet::CloseReason::from_close_frame(&close_frame),
+            Some(bevy_simplenet::CloseReason::Banned)
+        );
+    assert_eq!(bevy_simplenet::CloseReason::payload_from_close_frame::<DemoBanDetails>(&close_frame), Some(payload));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -48,6 +48,20 @@ fn client_demo_factory() -> bevy_simplenet::ClientFactory<DemoChannel>
     bevy_simplenet::ClientFactory::<DemoChannel>::new("test")
 }
 
+/// channel with a type-specific size limit for client messages
+#[derive(Debug, Clone)]
+pub struct DemoHintChannel;
+impl bevy_simplenet::ChannelPack for DemoHintChannel
+{
+    type ConnectMsg = DemoConnectMsg;
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = DemoClientMsg;
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+
+    const MAX_CLIENT_MSG_SIZE: Option<u32> = Some(50);
+}
+
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -68,10 +82,12 @@ fn message_size_limit_test(max_msg_size: u32)
                 max_msg_size,
                 rate_limit_config : bevy_simplenet::RateLimitConfig{
                         period    : std::time::Duration::from_millis(15),
-                        max_count : 25
+                        max_count : 25,
+                        max_bytes : u64::MAX
                     },
                 heartbeat_interval : std::time::Duration::from_secs(5),
                 keepalive_timeout  : std::time::Duration::from_secs(10),
+                ..Default::default()
             }
         );
 
@@ -129,8 +145,21 @@ fn message_size_limit_test(max_msg_size: u32)
     assert_eq!(connect_msg.0, connect_msg.0);
     assert_eq!(websocket_server.num_connections(), 1u64);
 
-    // send message with invalid size: client -> server
-    let signal = websocket_client.send(DemoClientMsg(large_msg)).unwrap();
+    // client learns the server's max message size when connecting
+    assert_eq!(websocket_client.server_max_msg_size(), Some(max_msg_size));
+
+    // send message with invalid size: rejected by the client
+    assert!(websocket_client.send(DemoClientMsg(large_msg.clone())).is_err());
+    let Err(bevy_simplenet::TrySendError::MessageTooLarge{ size, limit }) =
+        websocket_client.try_send(DemoClientMsg(large_msg.clone()))
+    else { panic!("client should reject oversized messages"); };
+    assert!(size > max_msg_size as usize);
+    assert_eq!(limit, max_msg_size);
+    assert!(websocket_client.send_and_forget(DemoClientMsg(large_msg.clone())).is_err());
+    assert!(websocket_client.is_connected());
+
+    // send stream chunk with invalid size (chunks are not checked by the client): client -> server
+    let signal = websocket_client.send_stream(DemoClientMsg(large_msg)).unwrap();
     assert_eq!(signal.status(), ezsockets::MessageStatus::Sending);
 
     std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
@@ -139,7 +168,7 @@ fn message_size_limit_test(max_msg_size: u32)
     assert_eq!(signal.status(), ezsockets::MessageStatus::Sent);  //sent and then server shut us down
     assert!(websocket_client.is_dead());
 
-    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected))) = websocket_server.next()
+    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected(_)))) = websocket_server.next()
     else { panic!("client should be disconnected"); };
     let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(_))) = websocket_client.next()
     else { panic!("client should be closed by server"); };
@@ -168,3 +197,45 @@ fn message_size_limit()
 }
 
 //-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn message_size_hints()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = bevy_simplenet::ServerFactory::<DemoHintChannel>::new("test").new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make client
+    let websocket_client = bevy_simplenet::ClientFactory::<DemoHintChannel>::new("test").new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(String::from(""))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+    assert!(websocket_client.is_connected());
+
+    // messages are limited by the channel's size hint
+    let large_msg = String::from_utf8(vec![1u8; 100]).unwrap();
+    let Err(bevy_simplenet::TrySendError::MessageTooLarge{ limit, .. }) =
+        websocket_client.try_send(DemoClientMsg(large_msg.clone()))
+    else { panic!("client should reject messages that exceed the size hint"); };
+    assert_eq!(limit, 50);
+
+    // requests have no size hint
+    assert!(websocket_client.request(DemoClientMsg(large_msg)).is_ok());
+    assert!(websocket_client.is_connected());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,210 @@
+//local shortcuts
+
+//third-party shortcuts
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoServerMsg(pub u64);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoClientMsg(pub u64);
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = ();
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = ();
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+}
+
+type _DemoServer = bevy_simplenet::Server<DemoChannel>;
+type _DemoClient = bevy_simplenet::Client<DemoChannel>;
+type DemoClientEvent = bevy_simplenet::ClientEventFrom<DemoChannel>;
+type DemoServerEvent = bevy_simplenet::ServerEventFrom<DemoChannel>;
+type DemoServerReport = bevy_simplenet::ServerReport<<DemoChannel as bevy_simplenet::ChannelPack>::ConnectMsg>;
+
+fn server_demo_factory() -> bevy_simplenet::ServerFactory<DemoChannel>
+{
+    bevy_simplenet::ServerFactory::<DemoChannel>::new("test")
+}
+
+fn client_demo_factory() -> bevy_simplenet::ClientFactory<DemoChannel>
+{
+    bevy_simplenet::ClientFactory::<DemoChannel>::new("test")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// TCP proxy in front of a server whose connections can be frozen.
+/// - A frozen connection stays open but silently drops all traffic, like a half-open connection.
+struct FreezableProxy
+{
+    /// url for connecting to the server through the proxy
+    url: url::Url,
+    /// freeze flags of the proxied connections
+    connections: Arc<Mutex<Vec<Arc<AtomicBool>>>>,
+}
+
+impl FreezableProxy
+{
+    fn new(server_url: url::Url) -> Self
+    {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut url = server_url.clone();
+        url.set_port(Some(listener.local_addr().unwrap().port())).unwrap();
+        let server_address = server_url.socket_addrs(|| None).unwrap()[0];
+
+        let connections = Arc::new(Mutex::new(Vec::default()));
+        let connections_clone = connections.clone();
+        std::thread::spawn(
+                move ||
+                {
+                    for client_stream in listener.incoming()
+                    {
+                        let Ok(client_stream) = client_stream else { return; };
+                        let Ok(server_stream) = std::net::TcpStream::connect(server_address) else { return; };
+
+                        let frozen = Arc::new(AtomicBool::new(false));
+                        connections_clone.lock().unwrap().push(frozen.clone());
+                        Self::forward(client_stream.try_clone().unwrap(), server_stream.try_clone().unwrap(), frozen.clone());
+                        Self::forward(server_stream, client_stream, frozen);
+                    }
+                }
+            );
+
+        Self{ url, connections }
+    }
+
+    /// Forward traffic from one stream to another until the connection closes, dropping it while frozen.
+    fn forward(mut from: std::net::TcpStream, mut to: std::net::TcpStream, frozen: Arc<AtomicBool>)
+    {
+        std::thread::spawn(
+                move ||
+                {
+                    let mut buffer = [0u8; 4096];
+                    loop
+                    {
+                        let Ok(num_bytes) = from.read(&mut buffer) else { return; };
+                        if num_bytes == 0 { return; }
+                        if frozen.load(Ordering::Acquire) { continue; }
+                        if to.write_all(&buffer[..num_bytes]).is_err() { return; }
+                    }
+                }
+            );
+    }
+
+    /// Freeze all connections made so far.
+    fn freeze_connections(&self)
+    {
+        for frozen in self.connections.lock().unwrap().iter() { frozen.store(true, Ordering::Release); }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn missed_pongs_half_open_connection()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server that closes sessions after missed pongs
+    // - the keepalive timeout is long, so only missed pongs can drop connections in this test
+    // - the rate limit is lenient, since the client's heartbeat pings and pongs count against it
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                heartbeat_interval : std::time::Duration::from_millis(20),
+                max_missed_pongs   : Some(2),
+                rate_limit_config  : bevy_simplenet::RateLimitConfig{
+                        period    : std::time::Duration::from_millis(100),
+                        max_count : 100,
+                        max_bytes : u64::MAX
+                    },
+                ..Default::default()
+            }
+        );
+
+    // make client that drops its connection after missed pongs, connecting through a proxy
+    // - the client tolerates more missed pongs than the server, so the server's stale session is usually gone when
+    //   the client reconnects (otherwise the server rejects the reconnect and the client tries again)
+    let proxy = FreezableProxy::new(websocket_server.url());
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            proxy.url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                reconnect_interval        : std::time::Duration::from_millis(10),
+                reconnect_on_server_close : true,
+                heartbeat_interval        : std::time::Duration::from_millis(20),
+                max_missed_pongs          : Some(4),
+                ..Default::default()
+            },
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(50));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // answered pings keep the connection alive
+    std::thread::sleep(std::time::Duration::from_millis(200));
+
+    assert!(websocket_server.next().is_none());
+    assert!(websocket_client.next().is_none());
+
+
+    // freeze the connection
+    proxy.freeze_connections();
+
+    std::thread::sleep(std::time::Duration::from_millis(300));  //wait for missed pongs
+
+    // both ends drop the frozen connection, then the client reconnects
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(
+            bevy_simplenet::DisconnectReason::ServerClosed
+        )))) = websocket_server.next()
+    else { panic!("server should close the unresponsive session"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Disconnected(
+            bevy_simplenet::DisconnectReason::TimedOut
+        ))) = websocket_client.next()
+    else { panic!("client should drop the unresponsive connection"); };
+
+    let mut reconnected = false;
+    while let Some(client_event) = websocket_client.next()
+    {
+        match client_event
+        {
+            DemoClientEvent::Report(bevy_simplenet::ClientReport::Reconnected) => { reconnected = true; break; }
+            DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(_)) => (),
+            client_event => panic!("unexpected client event: {:?}", client_event),
+        }
+    }
+    assert!(reconnected);
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client reconnects"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
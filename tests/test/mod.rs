@@ -1,7 +1,21 @@
 //module tree
 mod authentication;
+mod bevy_teardown;
+mod bytes_hooks;
+mod connect_msg;
+mod connect_timeout;
 mod connections_limit;
+mod disconnect_reason;
+mod event_callbacks;
 mod hello_world;
 mod message_size_limit;
+mod missed_pongs;
 mod rate_limit;
+mod ready_barrier;
 mod request_response;
+mod resend;
+mod streams;
+#[cfg(feature = "tls-openssl")]
+mod tls_info;
+mod topics;
+mod wire_format;
@@ -68,10 +68,12 @@ fn rate_limit_test(max_count_per_period: u32)
                 max_msg_size      : 1_000,
                 rate_limit_config : bevy_simplenet::RateLimitConfig{
                         period    : std::time::Duration::from_millis(15),  //15ms to coordinate with async waits
-                        max_count : max_count_per_period
+                        max_count : max_count_per_period,
+                        max_bytes : u64::MAX
                     },
                 heartbeat_interval : std::time::Duration::from_secs(5),
                 keepalive_timeout  : std::time::Duration::from_secs(10),
+                ..Default::default()
             }
         );
 
@@ -170,10 +172,17 @@ fn rate_limit_test(max_count_per_period: u32)
     assert_eq!(signals[max_count_per_period as usize].status(), ezsockets::MessageStatus::Sent);
     assert!(websocket_client.is_dead());
 
-    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected))) = websocket_server.next()
+    let Some((rl_client_id, DemoServerEvent::RateLimited(1))) = websocket_server.next()
+    else { panic!("server should report the rate limit violation"); };
+    assert_eq!(client_id, rl_client_id);
+    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected(_)))) = websocket_server.next()
     else { panic!("client should be disconnected"); };
-    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(_))) = websocket_client.next()
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(Some(close_frame)))) = websocket_client.next()
     else { panic!("client should be closed by server"); };
+    assert_eq!(
+            bevy_simplenet::CloseReason::from_close_frame(&close_frame),
+            Some(bevy_simplenet::CloseReason::RateLimited)
+        );
     let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::IsDead(_))) = websocket_client.next()
     else { panic!("client should be closed by server"); };
     assert_eq!(client_id, dc_client_id);
@@ -189,6 +198,92 @@ fn rate_limit_test(max_count_per_period: u32)
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
+fn rate_limit_drop_test(max_count_per_period: u32)
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                rate_limit_config : bevy_simplenet::RateLimitConfig{
+                        period    : std::time::Duration::from_millis(15),  //15ms to coordinate with async waits
+                        max_count : max_count_per_period,
+                        max_bytes : u64::MAX
+                    },
+                rate_limit_policy : bevy_simplenet::RateLimitPolicy::DropMessages,
+                ..Default::default()
+            }
+        );
+
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 3578762u128 },
+            bevy_simplenet::ClientConfig::default(),
+            DemoConnectMsg(String::from("hello!"))
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(_, _)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send messages to fill up server rate limiter past the brim
+    let client_val = 42;
+    for _ in 0..(max_count_per_period + 3)
+    {
+        websocket_client.send(DemoClientMsg(client_val)).unwrap();
+    }
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // expect all messages up to the limit received
+    for _ in 0..max_count_per_period
+    {
+        let Some((msg_client_id, DemoServerEvent::Msg(DemoClientMsg(msg_client_val)))) = websocket_server.next()
+        else { panic!("server did not receive client msg"); };
+        assert_eq!(client_id, msg_client_id);
+        assert_eq!(client_val, msg_client_val);
+    }
+
+    // expect the excess messages were dropped without closing the session
+    // - the dropped messages are reported at the end of the rate limit period, without waiting for another message
+    let Some((rl_client_id, DemoServerEvent::RateLimited(3))) = websocket_server.next()
+    else { panic!("server should report the dropped messages"); };
+    assert_eq!(client_id, rl_client_id);
+    let None = websocket_server.next()
+    else { panic!("server received more client msgs than expected"); };
+    assert!(!websocket_client.is_dead());
+    assert_eq!(websocket_server.num_connections(), 1u64);
+
+
+    // send another message in a new rate limit period
+    websocket_client.send(DemoClientMsg(client_val)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // expect the new message is received without another report
+    let Some((_, DemoServerEvent::Msg(DemoClientMsg(_)))) = websocket_server.next()
+    else { panic!("server did not receive client msg"); };
+
+    let None = websocket_server.next()
+    else { panic!("server should receive no more values"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
 #[test]
 fn rate_limiter()
 {
@@ -198,3 +293,83 @@ fn rate_limiter()
 }
 
 //-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn rate_limiter_drop_policy()
+{
+    rate_limit_drop_test(1);
+    rate_limit_drop_test(2);
+    rate_limit_drop_test(20);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn rate_limiter_manual_clock()
+{
+    let clock = bevy_simplenet::ManualClock::default();
+    let mut tracker = bevy_simplenet::RateLimitTracker::new_with_clock(
+            bevy_simplenet::RateLimitConfig{
+                period    : std::time::Duration::from_millis(100),
+                max_count : 2,
+                max_bytes : u64::MAX
+            },
+            std::sync::Arc::new(clock.clone())
+        );
+
+    // first period: the tracker starts with one message counted
+    assert!(tracker.try_count_msg());
+    assert!(!tracker.try_count_msg());
+
+    // still in the first period
+    clock.advance(std::time::Duration::from_millis(99));
+    assert!(!tracker.try_count_msg());
+
+    // second period starts at the first message after the period boundary
+    clock.advance(std::time::Duration::from_millis(1));
+    assert!(tracker.try_count_msg());
+    assert!(tracker.try_count_msg());
+    assert!(!tracker.try_count_msg());
+
+    // the second period lasts one full period from its first message
+    clock.advance(std::time::Duration::from_millis(99));
+    assert!(!tracker.try_count_msg());
+    clock.advance(std::time::Duration::from_millis(1));
+    assert!(tracker.try_count_msg());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn rate_limiter_max_bytes()
+{
+    let clock = bevy_simplenet::ManualClock::default();
+    let mut tracker = bevy_simplenet::RateLimitTracker::new_with_clock(
+            bevy_simplenet::RateLimitConfig{
+                period    : std::time::Duration::from_millis(100),
+                max_count : 100,
+                max_bytes : 1_000
+            },
+            std::sync::Arc::new(clock.clone())
+        );
+
+    // first period: a few large messages exhaust the byte budget
+    assert!(tracker.try_count_msg_bytes(600));
+    assert!(tracker.try_count_msg_bytes(400));
+    assert!(!tracker.try_count_msg_bytes(1));
+    assert_eq!(tracker.period_bytes(), 1_001);
+    assert_eq!(tracker.period_count(), 4);  //the tracker starts with one message counted
+
+    // second period: the byte budget is reset
+    clock.advance(std::time::Duration::from_millis(100));
+    assert_eq!(tracker.period_bytes(), 0);
+    assert_eq!(tracker.period_count(), 0);
+    assert!(tracker.try_count_msg_bytes(1_000));
+    assert!(!tracker.try_count_msg_bytes(1));
+
+    // a single message larger than the budget always fails
+    clock.advance(std::time::Duration::from_millis(100));
+    assert!(!tracker.try_count_msg_bytes(1_001));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -112,11 +112,17 @@ fn request_response()
     assert_eq!(client_val, msg_client_val);
     assert_eq!(signal.status(), bevy_simplenet::RequestStatus::Waiting);
     assert!(!token.destination_is_dead());
+    assert_eq!(websocket_server.debug_snapshot().unwrap().sessions[0].pending_requests, 1);
 
 
     // send response: server -> client
     let server_val = 24;
+    let metrics = websocket_server.metrics();
+    assert_eq!(metrics.request_latency().total(), 0);
     websocket_server.respond(token, DemoServerResponse(server_val)).unwrap();
+    assert_eq!(websocket_server.debug_snapshot().unwrap().sessions[0].pending_requests, 0);
+    assert_eq!(metrics.request_latency().total(), 1);
+    assert!(metrics.request_latency().percentile(1.0).is_some());
 
     std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
 
@@ -368,17 +374,21 @@ fn request_dropped()
 
     std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
 
+    let first_generation = websocket_server.session_generation(client_id).unwrap();
     let Some((
             msg_client_id,
+            msg_generation,
             DemoServerEvent::Request(DemoClientRequest(msg_client_val), token)
-        )) = websocket_server.next()
+        )) = websocket_server.next_with_generation()
     else { panic!("server did not receive client msg"); };
     assert_eq!(client_id, msg_client_id);
+    assert_eq!(first_generation, msg_generation);
     assert_eq!(client_id, token.client_id());
     assert_eq!(signal.id(), token.request_id());
     assert_eq!(client_val, msg_client_val);
     assert_eq!(signal.status(), bevy_simplenet::RequestStatus::Waiting);
     assert!(!token.destination_is_dead());
+    assert!(token.is_respondable());
 
 
     // server closes client
@@ -393,7 +403,7 @@ fn request_dropped()
 
     let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(_))) = websocket_client.next()
     else { panic!("client should be closed by server"); };
-    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected))) = websocket_server.next()
+    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected(_)))) = websocket_server.next()
     else { panic!("server should be disconnected after client is disconnected (by server)"); };
     assert_eq!(client_id, dc_client_id);
 
@@ -404,6 +414,7 @@ fn request_dropped()
     // request has updated
     std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
     assert!(token.destination_is_dead());
+    assert!(!token.is_respondable());
     assert_eq!(signal.status(), bevy_simplenet::RequestStatus::ResponseLost);
 
     // receive response lost
@@ -412,10 +423,13 @@ fn request_dropped()
     assert_eq!(signal.id(), request_id);
 
     // client receives connection report
-    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    let Some((_, generation, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) =
+        websocket_server.next_with_generation()
     else { panic!("server should be connected once client is connected"); };
-    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
-    else { panic!("client should be connected to server"); };
+    assert!(generation > first_generation);
+    assert_eq!(websocket_server.session_generation(client_id), Some(generation));
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Reconnected)) = websocket_client.next()
+    else { panic!("client should be reconnected to server"); };
     assert_eq!(websocket_server.num_connections(), 1u64);
 
 
@@ -435,3 +449,293 @@ fn request_dropped()
 }
 
 //-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn request_message_ordering()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default(),
+        );
+
+    let websocket_url = websocket_server.url();
+
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_url.clone(),
+            bevy_simplenet::AuthRequest::None{ client_id: 44718u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // interleave messages and requests: client -> server
+    websocket_client.send(()).unwrap();
+    let signal1 = websocket_client.request(DemoClientRequest(1)).unwrap();
+    websocket_client.send(()).unwrap();
+    let signal2 = websocket_client.request(DemoClientRequest(2)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+
+    // server receives them in the order they were sent
+    let Some((_, DemoServerEvent::Msg(()))) = websocket_server.next()
+    else { panic!("server did not receive first msg"); };
+    let Some((_, DemoServerEvent::Request(DemoClientRequest(1), token1))) = websocket_server.next()
+    else { panic!("server did not receive first request"); };
+    assert_eq!(signal1.id(), token1.request_id());
+    let Some((_, DemoServerEvent::Msg(()))) = websocket_server.next()
+    else { panic!("server did not receive second msg"); };
+    let Some((_, DemoServerEvent::Request(DemoClientRequest(2), token2))) = websocket_server.next()
+    else { panic!("server did not receive second request"); };
+    assert_eq!(signal2.id(), token2.request_id());
+
+
+    // no more outputs
+    let None = websocket_server.next()
+    else { panic!("server should receive no more values"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn max_concurrent_requests()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server that allows one outstanding request per session
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                max_concurrent_requests_per_session: Some(1),
+                ..Default::default()
+            },
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 44718u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send two requests: client -> server
+    let signal1 = websocket_client.request(DemoClientRequest(1)).unwrap();
+    let signal2 = websocket_client.request(DemoClientRequest(2)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // the second request is rejected while the first is outstanding
+    let Some((_, DemoServerEvent::Request(DemoClientRequest(1), token))) = websocket_server.next()
+    else { panic!("server did not receive first client request"); };
+    let None = websocket_server.next()
+    else { panic!("server should not receive the second client request"); };
+    let Some(DemoClientEvent::Reject(request_id)) = websocket_client.next()
+    else { panic!("client should receive a rejection"); };
+    assert_eq!(signal2.id(), request_id);
+    assert_eq!(signal2.status(), bevy_simplenet::RequestStatus::Rejected);
+
+
+    // respond to the first request, then send another request
+    websocket_server.respond(token, DemoServerResponse(1)).unwrap();
+    let signal3 = websocket_client.request(DemoClientRequest(3)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Response(DemoServerResponse(1), request_id)) = websocket_client.next()
+    else { panic!("client did not receive server response"); };
+    assert_eq!(signal1.id(), request_id);
+    let Some((_, DemoServerEvent::Request(DemoClientRequest(3), token))) = websocket_server.next()
+    else { panic!("server did not receive third client request"); };
+    assert_eq!(signal3.id(), token.request_id());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn drain_grouped()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default(),
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 44718u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+
+
+    // send requests: client -> server
+    let signals: Vec<_> = (0..3u64).map(|val| websocket_client.request(DemoClientRequest(val)).unwrap()).collect();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // respond, ack, and reject the requests
+    let mut tokens: Vec<_> = (0..3)
+        .map(
+            |_|
+            {
+                let Some((_, DemoServerEvent::Request(_, token))) = websocket_server.next()
+                else { panic!("server did not receive client request"); };
+                token
+            }
+        )
+        .collect();
+    websocket_server.reject(tokens.pop().unwrap());
+    websocket_server.ack(tokens.pop().unwrap()).unwrap();
+    websocket_server.respond(tokens.pop().unwrap(), DemoServerResponse(24)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // drain client events by type
+    let grouped = websocket_client.drain_grouped();
+    assert_eq!(grouped.reports.len(), 1);
+    let bevy_simplenet::ClientReport::Connected = grouped.reports[0]
+    else { panic!("client should be connected to server"); };
+    assert_eq!(grouped.responses.len(), 1);
+    assert_eq!(grouped.responses[0].0.0, 24);
+    assert_eq!(grouped.responses[0].1, signals[0].id());
+    assert_eq!(grouped.acks, vec![signals[1].id()]);
+    assert_eq!(grouped.rejections, vec![signals[2].id()]);
+    assert!(grouped.msgs.is_empty());
+    assert!(websocket_client.drain_grouped().is_empty());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn respond_many()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default(),
+        );
+
+    // make clients
+    let websocket_client1 = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 1u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+    let websocket_client2 = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 2u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(..)))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client1.next()
+    else { panic!("client should be connected to server"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client2.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send requests: clients -> server
+    let signal1 = websocket_client1.request(DemoClientRequest(1)).unwrap();
+    let signal2 = websocket_client1.request(DemoClientRequest(2)).unwrap();
+    let signal3 = websocket_client2.request(DemoClientRequest(3)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let mut responses = Vec::default();
+    while let Some((_, DemoServerEvent::Request(DemoClientRequest(val), token))) = websocket_server.next()
+    {
+        responses.push((token, DemoServerResponse(val * 10)));
+    }
+    assert_eq!(responses.len(), 3);
+
+
+    // respond to all requests at once
+    let metrics = websocket_server.metrics();
+    websocket_server.respond_many(responses).unwrap();
+    assert_eq!(metrics.request_latency().total(), 3);
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Response(DemoServerResponse(10), request_id)) = websocket_client1.next()
+    else { panic!("client did not receive server response"); };
+    assert_eq!(signal1.id(), request_id);
+    let Some(DemoClientEvent::Response(DemoServerResponse(20), request_id)) = websocket_client1.next()
+    else { panic!("client did not receive server response"); };
+    assert_eq!(signal2.id(), request_id);
+    let Some(DemoClientEvent::Response(DemoServerResponse(30), request_id)) = websocket_client2.next()
+    else { panic!("client did not receive server response"); };
+    assert_eq!(signal3.id(), request_id);
+    assert_eq!(signal3.status(), bevy_simplenet::RequestStatus::Responded);
+
+
+    // no more outputs
+    let None = websocket_client1.next()
+    else { panic!("client should receive no more values"); };
+    let None = websocket_client2.next()
+    else { panic!("client should receive no more values"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
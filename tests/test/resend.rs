@@ -0,0 +1,237 @@
+//local shortcuts
+
+//third-party shortcuts
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoServerMsg(pub u64);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoClientMsg(pub u64);
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = ();
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = ();
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+}
+
+type _DemoServer = bevy_simplenet::Server<DemoChannel>;
+type _DemoClient = bevy_simplenet::Client<DemoChannel>;
+type DemoClientEvent = bevy_simplenet::ClientEventFrom<DemoChannel>;
+type DemoServerEvent = bevy_simplenet::ServerEventFrom<DemoChannel>;
+type DemoServerReport = bevy_simplenet::ServerReport<<DemoChannel as bevy_simplenet::ChannelPack>::ConnectMsg>;
+
+fn server_demo_factory() -> bevy_simplenet::ServerFactory<DemoChannel>
+{
+    bevy_simplenet::ServerFactory::<DemoChannel>::new("test")
+}
+
+fn client_demo_factory() -> bevy_simplenet::ClientFactory<DemoChannel>
+{
+    bevy_simplenet::ClientFactory::<DemoChannel>::new("test")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn resend()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                resend_buffer_size : 10,
+                ..Default::default()
+            }
+        );
+
+    let websocket_url = websocket_server.url();
+
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_url,
+            bevy_simplenet::AuthRequest::None{ client_id: 7766u128 },
+            bevy_simplenet::ClientConfig{
+                reconnect_on_server_close : true,
+                ..Default::default()
+            },
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send message: server -> client
+    websocket_server.send(client_id, DemoServerMsg(1)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Msg(DemoServerMsg(1))) = websocket_client.next()
+    else { panic!("client did not receive server msg"); };
+
+
+    // server closes client, then sends a message that will be lost by the closing session
+    let closure_frame =
+        ezsockets::CloseFrame{
+            code   : ezsockets::CloseCode::Normal,
+            reason : String::from("test")
+        };
+    websocket_server.close_session(client_id, closure_frame).unwrap();
+    websocket_server.send(client_id, DemoServerMsg(2)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(_))) = websocket_client.next()
+    else { panic!("client should be closed by server"); };
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(_)))) = websocket_server.next()
+    else { panic!("server should be disconnected after client is disconnected (by server)"); };
+
+
+    // client auto-reconnects and receives the missed message exactly once
+    std::thread::sleep(std::time::Duration::from_millis(50));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is reconnected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Reconnected)) = websocket_client.next()
+    else { panic!("client should be reconnected to server"); };
+    let Some(DemoClientEvent::Msg(DemoServerMsg(2))) = websocket_client.next()
+    else { panic!("client did not receive resent server msg"); };
+
+
+    // new messages are sent after the resent messages
+    websocket_server.send(client_id, DemoServerMsg(3)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Msg(DemoServerMsg(3))) = websocket_client.next()
+    else { panic!("client did not receive server msg"); };
+
+
+    // no more outputs
+    let None = websocket_server.next()
+    else { panic!("server should receive no more values"); };
+    let None = websocket_client.next()
+    else { panic!("client should receive no more values"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn resend_gap_detected()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                resend_buffer_size : 1,
+                ..Default::default()
+            }
+        );
+
+    let websocket_url = websocket_server.url();
+
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_url,
+            bevy_simplenet::AuthRequest::None{ client_id: 7767u128 },
+            bevy_simplenet::ClientConfig{
+                reconnect_on_server_close : true,
+                ..Default::default()
+            },
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // send message: server -> client
+    websocket_server.send(client_id, DemoServerMsg(1)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Msg(DemoServerMsg(1))) = websocket_client.next()
+    else { panic!("client did not receive server msg"); };
+
+
+    // server closes client, then sends more messages than the resend buffer can hold
+    let closure_frame =
+        ezsockets::CloseFrame{
+            code   : ezsockets::CloseCode::Normal,
+            reason : String::from("test")
+        };
+    websocket_server.close_session(client_id, closure_frame).unwrap();
+    websocket_server.send(client_id, DemoServerMsg(2)).unwrap();
+    websocket_server.send(client_id, DemoServerMsg(3)).unwrap();
+    websocket_server.send(client_id, DemoServerMsg(4)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::ClosedByServer(_))) = websocket_client.next()
+    else { panic!("client should be closed by server"); };
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(_)))) = websocket_server.next()
+    else { panic!("server should be disconnected after client is disconnected (by server)"); };
+
+
+    // client auto-reconnects, detects the evicted messages, and receives the retained message
+    std::thread::sleep(std::time::Duration::from_millis(50));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is reconnected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Reconnected)) = websocket_client.next()
+    else { panic!("client should be reconnected to server"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::GapDetected{ missing_from, missing_to })) =
+        websocket_client.next()
+    else { panic!("client should detect lost server msgs"); };
+    assert_eq!((missing_from, missing_to), (2, 3));
+    let Some(DemoClientEvent::Msg(DemoServerMsg(4))) = websocket_client.next()
+    else { panic!("client did not receive resent server msg"); };
+
+
+    // no more outputs
+    let None = websocket_client.next()
+    else { panic!("client should receive no more values"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
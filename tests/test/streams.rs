@@ -0,0 +1,139 @@
+//local shortcuts
+
+//third-party shortcuts
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoServerMsg(pub Vec<u8>);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoClientMsg(pub Vec<u8>);
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = ();
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = ();
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+}
+
+type _DemoServer = bevy_simplenet::Server<DemoChannel>;
+type _DemoClient = bevy_simplenet::Client<DemoChannel>;
+type DemoClientEvent = bevy_simplenet::ClientEventFrom<DemoChannel>;
+type DemoServerEvent = bevy_simplenet::ServerEventFrom<DemoChannel>;
+type DemoServerReport = bevy_simplenet::ServerReport<<DemoChannel as bevy_simplenet::ChannelPack>::ConnectMsg>;
+
+fn server_demo_factory() -> bevy_simplenet::ServerFactory<DemoChannel>
+{
+    bevy_simplenet::ServerFactory::<DemoChannel>::new("test")
+}
+
+fn client_demo_factory() -> bevy_simplenet::ClientFactory<DemoChannel>
+{
+    bevy_simplenet::ClientFactory::<DemoChannel>::new("test")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn streams()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig{
+                max_msg_size      : 1_000,
+                max_stream_size   : 10_000,
+                stream_chunk_size : 500,
+                rate_limit_config : bevy_simplenet::RateLimitConfig{
+                        period    : std::time::Duration::from_millis(100),
+                        max_count : 1_000,
+                        max_bytes : u64::MAX
+                    },
+                ..Default::default()
+            }
+        );
+
+    // make client
+    let websocket_client = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 0u128 },
+            bevy_simplenet::ClientConfig{
+                stream_chunk_size : 500,
+                ..Default::default()
+            },
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // stream message: client -> server
+    // - the message is larger than the server's max message size
+    let client_val: Vec<u8> = (0..5_000u32).map(|i| i as u8).collect();
+    websocket_client.send_stream(DemoClientMsg(client_val.clone())).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((msg_client_id, DemoServerEvent::Msg(DemoClientMsg(msg_client_val)))) = websocket_server.next()
+    else { panic!("server did not receive client stream"); };
+    assert_eq!(client_id, msg_client_id);
+    assert_eq!(client_val, msg_client_val);
+
+
+    // stream message: server -> client
+    let server_val: Vec<u8> = (0..5_000u32).map(|i| (i * 7) as u8).collect();
+    websocket_server.send_stream(client_id, DemoServerMsg(server_val.clone())).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Msg(DemoServerMsg(msg_server_val))) = websocket_client.next()
+    else { panic!("client did not receive server stream"); };
+    assert_eq!(server_val, msg_server_val);
+
+
+    // stream message that exceeds the server's max stream size: client -> server
+    // - the server may close the session before the client finishes sending chunks, so sending can fail
+    let client_val: Vec<u8> = vec![0u8; 20_000];
+    let _ = websocket_client.send_stream(DemoClientMsg(client_val));
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((dc_client_id, DemoServerEvent::Report(DemoServerReport::Disconnected(
+            bevy_simplenet::DisconnectReason::ServerClosed
+        )))) = websocket_server.next()
+    else { panic!("server should close the session"); };
+    assert_eq!(client_id, dc_client_id);
+
+
+    // no more outputs
+    let None = websocket_server.next()
+    else { panic!("server should receive no more values"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,171 @@
+//local shortcuts
+
+//third-party shortcuts
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+use std::io::{Read, Write};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoServerMsg(pub u64);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoClientMsg(pub u64);
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = ();
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = ();
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+}
+
+type _DemoServer = bevy_simplenet::Server<DemoChannel>;
+type DemoServerEvent = bevy_simplenet::ServerEventFrom<DemoChannel>;
+type DemoServerReport = bevy_simplenet::ServerReport<<DemoChannel as bevy_simplenet::ChannelPack>::ConnectMsg>;
+
+fn server_demo_factory() -> bevy_simplenet::ServerFactory<DemoChannel>
+{
+    bevy_simplenet::ServerFactory::<DemoChannel>::new("test")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Make a self-signed certificate and its private key.
+fn self_signed_certificate(common_name: &str) -> (openssl::x509::X509, openssl::pkey::PKey<openssl::pkey::Private>)
+{
+    let key = openssl::pkey::PKey::from_rsa(openssl::rsa::Rsa::generate(2048).unwrap()).unwrap();
+
+    let mut name = openssl::x509::X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", common_name).unwrap();
+    let name = name.build();
+
+    let mut certificate = openssl::x509::X509Builder::new().unwrap();
+    certificate.set_version(2).unwrap();
+    certificate.set_subject_name(&name).unwrap();
+    certificate.set_issuer_name(&name).unwrap();
+    certificate.set_pubkey(&key).unwrap();
+    certificate.set_not_before(&openssl::asn1::Asn1Time::days_from_now(0).unwrap()).unwrap();
+    certificate.set_not_after(&openssl::asn1::Asn1Time::days_from_now(1).unwrap()).unwrap();
+    certificate.sign(&key, openssl::hash::MessageDigest::sha256()).unwrap();
+
+    (certificate.build(), key)
+}
+
+/// Open a raw websocket connection to the server over TLS, authenticating with a client certificate.
+fn tls_websocket_connect(
+    server_url         : url::Url,
+    client_id          : u128,
+    client_certificate : &openssl::x509::X509,
+    client_key         : &openssl::pkey::PKey<openssl::pkey::Private>,
+) -> openssl::ssl::SslStream<std::net::TcpStream>
+{
+    // build the connection request the same way clients do
+    let auth = serde_json::to_string(&bevy_simplenet::AuthRequest::None{ client_id }).unwrap();
+    let mut url = server_url.clone();
+    url.query_pairs_mut()
+        .append_pair("v", "test")
+        .append_pair("t", bevy_simplenet::env_type_as_str(bevy_simplenet::EnvType::Native))
+        .append_pair("a", auth.as_str())
+        .append_pair("c", "null");
+
+    // connect with TLS
+    // - the server's certificate is self-signed, so it isn't verified
+    let address = server_url.socket_addrs(|| None).unwrap()[0];
+    let mut connector = openssl::ssl::SslConnector::builder(openssl::ssl::SslMethod::tls()).unwrap();
+    connector.set_verify(openssl::ssl::SslVerifyMode::NONE);
+    connector.set_certificate(client_certificate).unwrap();
+    connector.set_private_key(client_key).unwrap();
+    let mut stream = connector.build().connect("localhost", std::net::TcpStream::connect(address).unwrap()).unwrap();
+
+    // send the upgrade request
+    let request = format!(
+            "GET {} HTTP/1.1\r\n\
+            Host: {}\r\n\
+            Upgrade: websocket\r\n\
+            Connection: Upgrade\r\n\
+            Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+            Sec-WebSocket-Version: 13\r\n\r\n",
+            &url[url::Position::BeforePath..],
+            address
+        );
+    stream.write_all(request.as_bytes()).unwrap();
+
+    // read the upgrade response
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n")
+    {
+        stream.read_exact(&mut byte).unwrap();
+        response.push(byte[0]);
+    }
+    assert!(String::from_utf8_lossy(&response).starts_with("HTTP/1.1 101"));
+
+    stream
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn tls_info_mtls()
+{
+    // prepare tokio runtimes for server
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+
+    // prepare a TLS config that requests client certificates (mTLS)
+    // - client certificates are self-signed, so any certificate is accepted
+    let (server_certificate, server_key) = self_signed_certificate("localhost");
+    let (client_certificate, client_key) = self_signed_certificate("test client");
+    let mut acceptor = openssl::ssl::SslAcceptor::mozilla_intermediate_v5(openssl::ssl::SslMethod::tls()).unwrap();
+    acceptor.set_certificate(&server_certificate).unwrap();
+    acceptor.set_private_key(&server_key).unwrap();
+    acceptor.set_verify_callback(openssl::ssl::SslVerifyMode::PEER, |_, _| true);
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::OpenSSL(
+                axum_server::tls_openssl::OpenSSLConfig::try_from(acceptor).unwrap()
+            ),
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // connect with a client certificate
+    let stream = tls_websocket_connect(websocket_server.url(), 0u128, &client_certificate, &client_key);
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    // the connection report and the session registry expose the handshake details
+    let Some((client_id, DemoServerEvent::Report(DemoServerReport::Connected(details, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(tls_info) = details.tls_info
+    else { panic!("server should record the session's TLS details"); };
+    assert!(tls_info.protocol_version.is_some());
+    assert!(tls_info.cipher_suite.is_some());
+    assert_eq!(tls_info.server_name.as_deref(), Some("localhost"));
+    assert_eq!(tls_info.peer_certificates, vec![client_certificate.to_der().unwrap()]);
+    assert_eq!(websocket_server.session_tls_info(client_id), Some(tls_info));
+
+    // the details are discarded when the session disconnects
+    drop(stream);
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Disconnected(_)))) = websocket_server.next()
+    else { panic!("server should be disconnected after client is disconnected"); };
+    assert!(websocket_server.session_tls_info(client_id).is_none());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,200 @@
+//local shortcuts
+
+//third-party shortcuts
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoServerMsg(pub u64);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DemoClientMsg(pub u64);
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = ();
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = ();
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = ();
+}
+
+type _DemoServer = bevy_simplenet::Server<DemoChannel>;
+type _DemoClient = bevy_simplenet::Client<DemoChannel>;
+type DemoClientEvent = bevy_simplenet::ClientEventFrom<DemoChannel>;
+type DemoServerEvent = bevy_simplenet::ServerEventFrom<DemoChannel>;
+type DemoServerReport = bevy_simplenet::ServerReport<<DemoChannel as bevy_simplenet::ChannelPack>::ConnectMsg>;
+
+fn server_demo_factory() -> bevy_simplenet::ServerFactory<DemoChannel>
+{
+    bevy_simplenet::ServerFactory::<DemoChannel>::new("test")
+}
+
+fn client_demo_factory() -> bevy_simplenet::ClientFactory<DemoChannel>
+{
+    bevy_simplenet::ClientFactory::<DemoChannel>::new("test")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn topics()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make clients
+    let websocket_client_a = client_demo_factory().new_client(
+            client_runtime.clone(),
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 1u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+    let websocket_client_b = client_demo_factory().new_client(
+            client_runtime,
+            websocket_server.url(),
+            bevy_simplenet::AuthRequest::None{ client_id: 2u128 },
+            bevy_simplenet::ClientConfig::default(),
+            ()
+        );
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+    else { panic!("server should be connected once client is connected"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client_a.next()
+    else { panic!("client should be connected to server"); };
+    let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = websocket_client_b.next()
+    else { panic!("client should be connected to server"); };
+
+
+    // subscribe: client a -> 'zone1', client b -> 'zone2'
+    websocket_client_a.subscribe("zone1").unwrap();
+    websocket_client_b.subscribe("zone2").unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+
+    // publish to 'zone1': only client a receives it
+    websocket_server.publish("zone1", DemoServerMsg(1)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Msg(DemoServerMsg(1))) = websocket_client_a.next()
+    else { panic!("client a should receive published msg"); };
+    let None = websocket_client_b.next()
+    else { panic!("client b should not receive msg for topic it isn't subscribed to"); };
+
+
+    // unsubscribe client a then publish to 'zone1' again: nobody receives it
+    websocket_client_a.unsubscribe("zone1").unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    websocket_server.publish("zone1", DemoServerMsg(2)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let None = websocket_client_a.next()
+    else { panic!("client a should not receive msg after unsubscribing"); };
+
+
+    // publish to 'zone2': only client b receives it
+    websocket_server.publish("zone2", DemoServerMsg(3)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Msg(DemoServerMsg(3))) = websocket_client_b.next()
+    else { panic!("client b should receive published msg"); };
+    let None = websocket_client_a.next()
+    else { panic!("client a should not receive msg for topic it isn't subscribed to"); };
+
+
+    // no more outputs
+    let None = websocket_server.next()
+    else { panic!("server should receive no more values"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn broadcast_except()
+{
+    // prepare tokio runtimes for server and client
+    let server_runtime = enfync::builtin::native::TokioHandle::default();
+    let client_runtime = enfync::builtin::Handle::default();
+
+    // launch websocket server
+    let websocket_server = server_demo_factory().new_server(
+            server_runtime,
+            "127.0.0.1:0",
+            bevy_simplenet::AcceptorConfig::Default,
+            bevy_simplenet::Authenticator::None,
+            bevy_simplenet::ServerConfig::default()
+        );
+
+    // make clients
+    let clients: Vec<_> = (1..=3u128)
+        .map(
+            |client_id|
+            client_demo_factory().new_client(
+                client_runtime.clone(),
+                websocket_server.url(),
+                bevy_simplenet::AuthRequest::None{ client_id },
+                bevy_simplenet::ClientConfig::default(),
+                ()
+            )
+        )
+        .collect();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    for _ in 0..clients.len()
+    {
+        let Some((_, DemoServerEvent::Report(DemoServerReport::Connected(_, ())))) = websocket_server.next()
+        else { panic!("server should be connected once client is connected"); };
+    }
+    for client in clients.iter()
+    {
+        let Some(DemoClientEvent::Report(bevy_simplenet::ClientReport::Connected)) = client.next()
+        else { panic!("client should be connected to server"); };
+    }
+
+
+    // broadcast to everyone except client 2
+    websocket_server.broadcast_except(2u128, DemoServerMsg(7)).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(25));  //wait for async machinery
+
+    let Some(DemoClientEvent::Msg(DemoServerMsg(7))) = clients[0].next()
+    else { panic!("client 1 should receive broadcast msg"); };
+    let None = clients[1].next()
+    else { panic!("client 2 should not receive broadcast msg"); };
+    let Some(DemoClientEvent::Msg(DemoServerMsg(7))) = clients[2].next()
+    else { panic!("client 3 should receive broadcast msg"); };
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,99 @@
+//local shortcuts
+
+//third-party shortcuts
+use serde::{Serialize, Deserialize};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// connect message
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct DemoConnectMsg(pub String);
+
+/// message from server
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct DemoServerMsg(pub Vec<u8>);
+
+/// message from client
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub enum DemoClientMsg
+{
+    Empty,
+    Bytes(Vec<u8>),
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct DemoChannel;
+impl bevy_simplenet::ChannelPack for DemoChannel
+{
+    type ConnectMsg = DemoConnectMsg;
+    type ClientMsg = DemoClientMsg;
+    type ClientRequest = Vec<u8>;
+    type ServerMsg = DemoServerMsg;
+    type ServerResponse = Option<u64>;
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn wire_format_messages()
+{
+    // connect messages
+    for connect_msg in [DemoConnectMsg(String::from("")), DemoConnectMsg(String::from("hello ?&= world"))]
+    {
+        assert_eq!(bevy_simplenet::round_trip_connect_msg::<DemoChannel>(&connect_msg), Ok(connect_msg));
+    }
+
+    // server messages
+    for bytes in [Vec::default(), vec![0u8], vec![u8::MAX; 1_000_000]]
+    {
+        let msg = DemoServerMsg(bytes);
+        assert_eq!(bevy_simplenet::round_trip_server_msg::<DemoChannel>(msg.clone()), Ok(msg));
+    }
+
+    // client messages
+    for msg in [
+            DemoClientMsg::Empty,
+            DemoClientMsg::Bytes(Vec::default()),
+            DemoClientMsg::Bytes(vec![7u8; 1_000_000]),
+            DemoClientMsg::Text(String::from("")),
+            DemoClientMsg::Text(String::from("hello")),
+        ]
+    {
+        assert_eq!(bevy_simplenet::round_trip_client_msg::<DemoChannel>(msg.clone()), Ok(msg));
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn wire_format_requests()
+{
+    for request_id in [0u64, 1u64, u64::MAX - 1, u64::MAX]
+    {
+        // client requests
+        for request in [Vec::default(), vec![1u8; 1_000_000]]
+        {
+            assert_eq!(
+                    bevy_simplenet::round_trip_client_request::<DemoChannel>(request.clone(), request_id),
+                    Ok((request, request_id))
+                );
+        }
+
+        // server responses
+        for response in [None, Some(0u64), Some(u64::MAX)]
+        {
+            assert_eq!(
+                    bevy_simplenet::round_trip_server_response::<DemoChannel>(response, request_id),
+                    Ok((response, request_id))
+                );
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------